@@ -0,0 +1,195 @@
+//! The rooms of the server: who sits where, when rounds start and who won them. The
+//! games themselves are played by the clients; the server only passes their inputs and
+//! garbage on to the other player.
+
+use wretris::config::Config;
+use wretris::net::{ ClientMessage, ServerMessage };
+use wretris::versus::PLAYERS;
+
+use std::collections::HashMap;
+
+/// Connections are told apart by a number the server gives them.
+pub type ClientId = usize;
+
+/// Messages to deliver, each to the client it is addressed to.
+pub type Outbox = Vec<(ClientId, ServerMessage)>;
+
+struct Seat {
+    client: ClientId,
+    name: String,
+    ready: bool,
+}
+
+struct Room {
+    /// The rules, as the player who opened the room offered them.
+    config: String,
+    best_of: usize,
+    seats: Vec<Seat>,
+    round: usize,
+    playing: bool,
+    wins: [usize; PLAYERS],
+    match_over: bool,
+}
+
+impl Room {
+    fn new(config: String) -> Self {
+        Self {
+            best_of: Config::from_query(&config).best_of,
+            config,
+            seats: Vec::new(),
+            round: 0,
+            playing: false,
+            wins: [0; PLAYERS],
+            match_over: false,
+        }
+    }
+
+    fn seat_of(&self, client: ClientId) -> Option<usize> {
+        self.seats.iter().position(|seat| seat.client == client)
+    }
+
+    fn broadcast(&self, message: &ServerMessage, outbox: &mut Outbox) {
+        outbox.extend(self.seats.iter().map(|seat| (seat.client, message.clone())));
+    }
+
+    /// Tells every player who is in the room.
+    fn lobby(&self, name: &str, outbox: &mut Outbox) {
+        let players: Vec<String> = self.seats.iter().map(|seat| seat.name.clone()).collect();
+        let ready: Vec<bool> = self.seats.iter().map(|seat| seat.ready).collect();
+        for (seat, player) in self.seats.iter().enumerate() {
+            let message = ServerMessage::Lobby { room: name.to_string(), players: players.clone(), ready: ready.clone(), seat };
+            outbox.push((player.client, message));
+        }
+    }
+
+    /// Starts the next round with a new seed once both players are ready.
+    fn start_if_ready(&mut self, outbox: &mut Outbox) {
+        if self.playing || self.seats.len() < PLAYERS || self.seats.iter().any(|seat| !seat.ready) {
+            return;
+        }
+        if self.match_over {
+            self.wins = [0; PLAYERS];
+            self.match_over = false;
+        }
+        for seat in &mut self.seats {
+            seat.ready = false;
+        }
+        self.round += 1;
+        self.playing = true;
+        let message = ServerMessage::Start { round: self.round, seed: rand::random(), config: self.config.clone() };
+        self.broadcast(&message, outbox);
+    }
+
+    /// Ends the round the player in `loser` topped out in.
+    fn knock_out(&mut self, loser: usize, outbox: &mut Outbox) {
+        let winner = 1 - loser;
+        self.playing = false;
+        self.wins[winner] += 1;
+        let wins = self.wins.to_vec();
+        let message = if self.wins[winner] > self.best_of / 2 {
+            self.match_over = true;
+            ServerMessage::MatchOver { winner, wins }
+        } else {
+            ServerMessage::RoundOver { round: self.round, winner, wins }
+        };
+        self.broadcast(&message, outbox);
+    }
+}
+
+#[derive(Default)]
+pub struct Lobby {
+    rooms: HashMap<String, Room>,
+    /// The room each client joined.
+    joined: HashMap<ClientId, String>,
+}
+
+impl Lobby {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles a message of `client`. Returns the messages it causes.
+    pub fn handle(&mut self, client: ClientId, message: ClientMessage) -> Outbox {
+        let mut outbox = Vec::new();
+        if let ClientMessage::Join { room, name, config } = message {
+            self.join(client, room, name, config, &mut outbox);
+            return outbox;
+        }
+        let name = match self.joined.get(&client) {
+            Some(name) => name.clone(),
+            None => return vec![(client, ServerMessage::Error { message: "Join a room first".to_string() })],
+        };
+        let room = match self.rooms.get_mut(&name) {
+            Some(room) => room,
+            None => return outbox,
+        };
+        let seat = match room.seat_of(client) {
+            Some(seat) => seat,
+            None => return outbox,
+        };
+        let other = room.seats.get(1 - seat).map(|seat| seat.client);
+        match message {
+            ClientMessage::Join { .. } => {}
+            ClientMessage::Ready if !room.playing => {
+                room.seats[seat].ready = true;
+                room.lobby(&name, &mut outbox);
+                room.start_if_ready(&mut outbox);
+            }
+            ClientMessage::Sync { round, elapsed, steps } if room.playing && round == room.round => {
+                outbox.extend(other.map(|other| (other, ServerMessage::Sync { round, elapsed, steps })));
+            }
+            ClientMessage::Attack { round, lines } if room.playing && round == room.round => {
+                outbox.extend(other.map(|other| (other, ServerMessage::Attack { round, lines })));
+            }
+            ClientMessage::ToppedOut { round } if room.playing && round == room.round => room.knock_out(seat, &mut outbox),
+            // Late messages of a round that is over.
+            ClientMessage::Ready | ClientMessage::Sync { .. } | ClientMessage::Attack { .. } | ClientMessage::ToppedOut { .. } => {}
+        }
+        outbox
+    }
+
+    /// Takes `client` out of its room, which ends the match of the player left behind.
+    pub fn leave(&mut self, client: ClientId) -> Outbox {
+        let mut outbox = Vec::new();
+        let name = match self.joined.remove(&client) {
+            Some(name) => name,
+            None => return outbox,
+        };
+        let room = match self.rooms.get_mut(&name) {
+            Some(room) => room,
+            None => return outbox,
+        };
+        let seat = match room.seat_of(client) {
+            Some(seat) => room.seats.remove(seat),
+            None => return outbox,
+        };
+        if room.seats.is_empty() {
+            self.rooms.remove(&name);
+            return outbox;
+        }
+        room.playing = false;
+        room.match_over = false;
+        room.wins = [0; PLAYERS];
+        for seat in &mut room.seats {
+            seat.ready = false;
+        }
+        room.broadcast(&ServerMessage::Left { name: seat.name }, &mut outbox);
+        room.lobby(&name, &mut outbox);
+        outbox
+    }
+
+    fn join(&mut self, client: ClientId, name: String, player: String, config: String, outbox: &mut Outbox) {
+        if self.joined.contains_key(&client) {
+            outbox.push((client, ServerMessage::Error { message: "You are in a room already".to_string() }));
+            return;
+        }
+        let room = self.rooms.entry(name.clone()).or_insert_with(|| Room::new(config));
+        if room.seats.len() >= PLAYERS {
+            outbox.push((client, ServerMessage::Error { message: format!("The room {} is full", name) }));
+            return;
+        }
+        room.seats.push(Seat { client, name: player, ready: false });
+        room.lobby(&name, outbox);
+        self.joined.insert(client, name);
+    }
+}
@@ -0,0 +1,112 @@
+//! Relays online versus matches of wretris: `wretris-server [address]`, listening on
+//! `127.0.0.1:9000` by default. Players meet in rooms by name, e.g. with the options
+//! `mode=versus server=ws://127.0.0.1:9000 room=friday name=alice`.
+
+mod lobby;
+
+use lobby::{ ClientId, Lobby, Outbox };
+use wretris::net::{ ClientMessage, ServerMessage };
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::{ TcpListener, TcpStream };
+use std::sync::mpsc::{ self, Receiver, Sender, TryRecvError };
+use std::sync::{ Arc, Mutex };
+use std::thread;
+use std::time::Duration;
+use tungstenite::{ Message, WebSocket };
+
+const ADDRESS: &str = "127.0.0.1:9000";
+
+/// How long a connection waits for a message before it sends what is queued for it.
+const POLL: Duration = Duration::from_millis(5);
+
+/// The lobby and a way to reach every client connected.
+struct Relay {
+    lobby: Lobby,
+    clients: HashMap<ClientId, Sender<ServerMessage>>,
+}
+
+impl Relay {
+    fn new() -> Self {
+        Self {
+            lobby: Lobby::new(),
+            clients: HashMap::new(),
+        }
+    }
+
+    fn deliver(&self, outbox: Outbox) {
+        for (client, message) in outbox {
+            if let Some(sender) = self.clients.get(&client) {
+                sender.send(message).ok();
+            }
+        }
+    }
+}
+
+fn main() {
+    let address = std::env::args().nth(1).unwrap_or_else(|| ADDRESS.to_string());
+    let listener = match TcpListener::bind(&address) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Cannot listen on {}: {}", address, error);
+            std::process::exit(1);
+        }
+    };
+    if let Ok(address) = listener.local_addr() {
+        println!("Listening on ws://{}", address);
+        std::io::stdout().flush().ok();
+    }
+    let relay = Arc::new(Mutex::new(Relay::new()));
+    for (client, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let relay = relay.clone();
+        thread::spawn(move || {
+            if let Err(error) = serve(client, stream, &relay) {
+                eprintln!("Client {}: {}", client, error);
+            }
+            let mut relay = relay.lock().unwrap();
+            relay.clients.remove(&client);
+            let outbox = relay.lobby.leave(client);
+            relay.deliver(outbox);
+        });
+    }
+}
+
+/// Passes messages between `client` and the lobby until it hangs up.
+fn serve(client: ClientId, stream: TcpStream, relay: &Mutex<Relay>) -> Result<(), String> {
+    let mut socket = tungstenite::accept(stream).map_err(|error| format!("Handshake failed: {}", error))?;
+    socket.get_mut().set_read_timeout(Some(POLL)).map_err(|error| error.to_string())?;
+    let (sender, queued) = mpsc::channel();
+    relay.lock().unwrap().clients.insert(client, sender);
+    loop {
+        send_queued(&mut socket, &queued)?;
+        let text = match socket.read() {
+            Ok(Message::Text(text)) => text,
+            Ok(Message::Close(_)) => return Ok(()),
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => continue,
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(error) => return Err(error.to_string()),
+        };
+        let mut shared = relay.lock().unwrap();
+        let outbox = match ClientMessage::decode(&text) {
+            Ok(message) => shared.lobby.handle(client, message),
+            Err(message) => vec![(client, ServerMessage::Error { message })],
+        };
+        shared.deliver(outbox);
+    }
+}
+
+fn send_queued(socket: &mut WebSocket<TcpStream>, queued: &Receiver<ServerMessage>) -> Result<(), String> {
+    loop {
+        match queued.try_recv() {
+            Ok(message) => socket.send(Message::Text(message.encode())).map_err(|error| error.to_string())?,
+            Err(TryRecvError::Empty) => return Ok(()),
+            Err(TryRecvError::Disconnected) => return Err("The relay hung up".to_string()),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use wretris::block::{ BColor, Rotation, Shape };
+use wretris::config::Config;
+use wretris::engine::Input;
+use wretris::fumen::{ self, Page, Piece, HEIGHT, WIDTH };
+use wretris::mode::GameModeKind;
+use wretris::net::{ Match, State };
+use wretris::socket::Socket;
+
+use std::io::{ BufRead, BufReader };
+use std::process::{ Child, Command, Stdio };
+use std::thread;
+use std::time::{ Duration, Instant };
+
+/// The server binary, listening on a free port until dropped.
+struct Server {
+    child: Child,
+    url: String,
+}
+
+impl Server {
+    fn start() -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_wretris-server"))
+            .arg("127.0.0.1:0")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap()).read_line(&mut line).unwrap();
+        let url = line.trim().trim_start_matches("Listening on ").to_string();
+        Server { child, url }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+/// A client without a screen: its match and its connection.
+struct Client {
+    game: Match,
+    socket: Socket,
+    errors: Vec<String>,
+}
+
+impl Client {
+    fn join(server: &Server, config: &Config, name: &str) -> Client {
+        Client {
+            game: Match::new(config.clone(), "test", name),
+            socket: Socket::connect(&server.url).unwrap(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn exchange(&mut self) {
+        for message in self.socket.receive() {
+            if let Err(error) = message.and_then(|message| self.game.on_message(message)) {
+                self.errors.push(error);
+            }
+        }
+        for message in self.game.outgoing() {
+            self.socket.send(&message).unwrap();
+        }
+    }
+}
+
+/// Passes messages of one client until `done` holds, or fails after a few seconds.
+fn wait(client: &mut Client, done: impl Fn(&Client) -> bool) {
+    let start = Instant::now();
+    while !done(client) {
+        assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+        client.exchange();
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Passes messages until `done` holds for both clients, or fails after a few seconds.
+fn settle(clients: &mut [Client; 2], done: impl Fn(&[Client; 2]) -> bool) {
+    let start = Instant::now();
+    while !done(clients) {
+        assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+        for client in clients.iter_mut() {
+            client.exchange();
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// Both players start over four rows open in the last column, with an I block.
+fn tetris_ready() -> Config {
+    let mut page = Page::default();
+    for y in 0..4 {
+        for x in 0..WIDTH - 1 {
+            page.field[(HEIGHT - 1 - y) * WIDTH + x] = Some(BColor::GARBAGE);
+        }
+    }
+    page.piece = Some(Piece { shape: Shape::I, rotation: Rotation::R90, pos: (9, 4) });
+    Config { mode: GameModeKind::Versus, fumen: Some(fumen::encode(&[page]).unwrap()), best_of: 1, ..Config::default() }
+}
+
+#[test]
+fn two_clients_play_a_match_through_the_server() {
+    let server = Server::start();
+    let config = tetris_ready();
+    // Whoever comes first opens the room.
+    let mut alice = Client::join(&server, &config, "alice");
+    wait(&mut alice, |alice| alice.game.players().len() == 1);
+    let mut clients = [alice, Client::join(&server, &Config::default(), "bob")];
+    settle(&mut clients, |clients| clients.iter().all(|client| client.game.players().len() == 2));
+    assert_eq!(clients[0].game.players(), ["alice".to_string(), "bob".to_string()]);
+    assert_eq!((clients[0].game.seat(), clients[1].game.seat()), (0, 1));
+
+    // The round starts on the rules of the room with a shared seed.
+    for client in clients.iter_mut() {
+        client.game.ready();
+    }
+    settle(&mut clients, |clients| clients.iter().all(|client| client.game.state() == State::Playing));
+    assert_eq!(clients[0].game.engine().seed(), clients[1].game.engine().seed());
+    assert_eq!(clients[1].game.config().fumen, config.fumen);
+    assert_eq!(clients[1].game.config().best_of, 1);
+
+    // A tetris clearing the whole board sends garbage, which the other player's next
+    // block raises; each client follows the other's game exactly.
+    clients[0].game.tick(100);
+    clients[0].game.handle_input(Input::HardDrop);
+    settle(&mut clients, |clients| clients[1].game.engine().garbage() == 14);
+    clients[1].game.tick(200);
+    clients[1].game.handle_input(Input::LeftMost);
+    clients[1].game.handle_input(Input::HardDrop);
+    clients[0].game.tick(300);
+    settle(&mut clients, |clients| {
+        clients.iter().enumerate().all(|(player, client)| client.game.opponent().elapsed() == clients[1 - player].game.engine().elapsed())
+    });
+    for player in 0..2 {
+        assert_eq!(clients[player].game.opponent().snapshot().cells, clients[1 - player].game.engine().snapshot().cells);
+    }
+    assert_eq!(clients[1].game.engine().garbage(), 0);
+
+    // Bob tops out, which wins Alice the match.
+    while !clients[1].game.engine().is_gameover() {
+        clients[1].game.handle_input(Input::HardDrop);
+    }
+    settle(&mut clients, |clients| clients.iter().all(|client| client.game.state() == State::MatchOver { winner: 0 }));
+    assert_eq!(clients[1].game.wins(), [1, 0]);
+    assert!(clients.iter().all(|client| client.errors.is_empty()));
+
+    // A third player finds the room full, and Alice learns when Bob leaves.
+    let mut third = Client::join(&server, &Config::default(), "carol");
+    wait(&mut third, |third| !third.errors.is_empty());
+    assert_eq!(third.errors, ["The room test is full".to_string()]);
+    let [mut alice, bob] = clients;
+    drop(bob);
+    wait(&mut alice, |alice| !alice.errors.is_empty());
+    assert_eq!(alice.errors, ["bob left the match".to_string()]);
+    assert_eq!(alice.game.state(), State::Lobby);
+}
@@ -0,0 +1,48 @@
+use crate::engine::Snapshot;
+
+use cursive::{
+    theme::{ Color, ColorStyle,},
+    View,
+    Printer,
+};
+
+const WIDTH: usize = 20;
+const HEIGHT: usize = 4;
+
+/// Announces the last notable clear, such as a T-spin or a back-to-back tetris.
+pub struct Action {
+    labels: Vec<String>,
+}
+
+impl Default for Action {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Action {
+    pub fn new() -> Self {
+        Self {
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.labels = snapshot.action.map(|award| award.labels()).unwrap_or_default();
+    }
+}
+
+impl View for Action {
+    fn draw(&self, printer: &Printer) {
+        let color_style = ColorStyle::new(Color::Rgb(183, 85, 224), Color::Rgb(255,255,255));
+        printer.with_color(color_style, |printer| {
+            for (y, label) in self.labels.iter().take(HEIGHT).enumerate() {
+                printer.print((1, y), label);
+            }
+        });
+    }
+
+    fn required_size(&mut self, _: cursive::Vec2) -> cursive::Vec2 {
+        cursive::Vec2::new(WIDTH, HEIGHT)
+    }
+}
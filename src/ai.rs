@@ -0,0 +1,148 @@
+//! The built-in bot: it tries every place the falling block can reach, and the held or
+//! next block if holding is allowed, and picks the one that leaves the best board.
+
+use crate::block::{ Block, BlockWithPos };
+use crate::color_grid::ColorGrid;
+use crate::engine::{ Engine, GameEvent, Input };
+use crate::level::MAX_GRAVITY;
+use crate::search;
+
+/// How much each feature of a board counts. Features that make a board worse have
+/// negative weights.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Weights {
+    pub height: f64,
+    pub lines: f64,
+    pub holes: f64,
+    pub bumpiness: f64,
+    pub wells: f64,
+}
+
+impl Default for Weights {
+    /// Weights tuned by a genetic algorithm to survive as long as possible, with a small
+    /// penalty on wells so the stack keeps one column open at most.
+    fn default() -> Self {
+        Self {
+            height: -0.510066,
+            lines: 0.760666,
+            holes: -0.35663,
+            bumpiness: -0.184483,
+            wells: -0.05,
+        }
+    }
+}
+
+/// What the heuristic looks at on a board after a block locks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Features {
+    /// Heights of all the columns added up.
+    pub height: usize,
+    /// Lines the block cleared.
+    pub lines: usize,
+    /// Empty cells with a taken cell somewhere above them.
+    pub holes: usize,
+    /// Height differences of neighbouring columns added up.
+    pub bumpiness: usize,
+    /// Depths of the columns lower than both neighbours added up, walls counting as high.
+    pub wells: usize,
+}
+
+impl Features {
+    /// The features of the locked cells of `grid`, after a lock that cleared `lines`.
+    pub fn of(grid: &ColorGrid, lines: usize) -> Features {
+        let heights: Vec<usize> = (0..grid.width)
+            .map(|x| (0..grid.height).find(|y| grid.is_occupied(x, *y)).map_or(0, |top| grid.height - top))
+            .collect();
+        let holes = (0..grid.width)
+            .map(|x| (grid.height - heights[x]..grid.height).filter(|y| !grid.is_occupied(x, *y)).count())
+            .sum();
+        let bumpiness = heights.windows(2).map(|pair| (pair[0] as i64 - pair[1] as i64).unsigned_abs() as usize).sum();
+        let wells = (0..grid.width)
+            .map(|x| {
+                let left = if x == 0 { usize::MAX } else { heights[x - 1] };
+                let right = heights.get(x + 1).copied().unwrap_or(usize::MAX);
+                left.min(right).saturating_sub(heights[x])
+            })
+            .filter(|depth| *depth < usize::MAX / 2)
+            .sum();
+        Features {
+            height: heights.iter().sum(),
+            lines,
+            holes,
+            bumpiness,
+            wells,
+        }
+    }
+
+    pub fn score(&self, weights: &Weights) -> f64 {
+        weights.height * self.height as f64
+            + weights.lines * self.lines as f64
+            + weights.holes * self.holes as f64
+            + weights.bumpiness * self.bumpiness as f64
+            + weights.wells * self.wells as f64
+    }
+}
+
+/// Where the bot puts the next block and how.
+#[derive(Clone, Debug)]
+pub struct Plan {
+    pub block: BlockWithPos,
+    /// Inputs from where the block is now, ending with the hard drop that locks it.
+    pub inputs: Vec<Input>,
+    pub score: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Ai {
+    pub weights: Weights,
+}
+
+impl Ai {
+    pub fn new(weights: Weights) -> Self {
+        Self { weights }
+    }
+
+    /// The best move for the game on `engine`, or `None` once it is over.
+    pub fn plan(&self, engine: &Engine) -> Option<Plan> {
+        if engine.is_gameover() {
+            return None;
+        }
+        let snapshot = engine.snapshot();
+        let sonic = engine.gravity() >= MAX_GRAVITY;
+        let mut best = self.best(engine.grid(), sonic, Vec::new());
+        let swapped = snapshot.hold.as_ref().or_else(|| snapshot.queue.first());
+        if let Some(swapped) = swapped.filter(|_| snapshot.hold_enabled && snapshot.can_hold) {
+            let held = search::respawned(engine.grid(), Block::new(swapped.shape()));
+            if let Some(plan) = self.best(&held, sonic, vec![Input::Hold]) {
+                if !matches!(&best, Some(best) if best.score >= plan.score) {
+                    best = Some(plan);
+                }
+            }
+        }
+        best
+    }
+
+    /// Plans the next move and plays it. Returns what happened, or `None` once the game is over.
+    pub fn play(&self, engine: &mut Engine) -> Option<Vec<GameEvent>> {
+        let plan = self.plan(engine)?;
+        Some(plan.inputs.into_iter().flat_map(|input| engine.handle_input(input)).collect())
+    }
+
+    /// The best place for the active block of `grid`, reached after `before`.
+    fn best(&self, grid: &ColorGrid, sonic: bool, before: Vec<Input>) -> Option<Plan> {
+        let mut best: Option<Plan> = None;
+        for placement in search::placements(grid, sonic) {
+            let mut locked = grid.clone();
+            locked.block = placement.block.clone();
+            let lines = locked.merge_block();
+            let score = Features::of(&locked, lines).score(&self.weights);
+            if !matches!(&best, Some(best) if best.score >= score) {
+                let mut inputs = before.clone();
+                inputs.extend(placement.inputs);
+                inputs.push(Input::HardDrop);
+                best = Some(Plan { block: placement.block, inputs, score });
+            }
+        }
+        best
+    }
+}
@@ -1,13 +1,80 @@
+#[cfg(any(target_arch = "wasm32", feature = "native"))]
 use cursive::backend::Backend;
+#[cfg(target_arch = "wasm32")]
 use cursive::backends::wasm;
-use web_sys::HtmlCanvasElement;
+#[cfg(target_arch = "wasm32")]
+use cursive::event::{ Event, Key };
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{ closure::Closure, JsCast };
+#[cfg(target_arch = "wasm32")]
+use web_sys::{ HtmlCanvasElement, KeyboardEvent };
 
+#[cfg(target_arch = "wasm32")]
 pub fn backend() -> Box<dyn Backend> {
     let wasm_backend: Box<dyn Backend> = wasm::Backend::init().unwrap();
     wasm_backend
 }
 
+#[cfg(target_arch = "wasm32")]
 pub fn backend_with_canvas(canvas: HtmlCanvasElement) -> Box<dyn Backend> {
     let wasm_backend: Box<dyn Backend> = wasm::Backend::new(canvas).unwrap();
     wasm_backend
 }
+
+/// The terminal the game runs in natively.
+#[cfg(feature = "native")]
+pub fn terminal() -> Box<dyn Backend> {
+    cursive::backends::crossterm::Backend::init().expect("cannot open the terminal")
+}
+
+/// Calls `on_release` with the event of every key the player lets go of.
+/// The wasm backend only reports key presses.
+#[cfg(target_arch = "wasm32")]
+pub fn forward_key_releases(on_release: impl Fn(Event) + 'static) {
+    let listener = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        if let Some(event) = key_event(&event.key()) {
+            on_release(event);
+        }
+    }) as Box<dyn FnMut(KeyboardEvent)>);
+    web_sys::window()
+        .expect("no window")
+        .add_event_listener_with_callback("keyup", listener.as_ref().unchecked_ref())
+        .expect("cannot listen to keyup");
+    listener.forget();
+}
+
+/// Calls `on_hidden` each time the page goes out of sight.
+#[cfg(target_arch = "wasm32")]
+pub fn on_page_hidden(on_hidden: impl Fn() + 'static) {
+    let document = web_sys::window().expect("no window").document().expect("no document");
+    let page = document.clone();
+    let listener = Closure::wrap(Box::new(move || {
+        if page.hidden() {
+            on_hidden();
+        }
+    }) as Box<dyn FnMut()>);
+    document
+        .add_event_listener_with_callback("visibilitychange", listener.as_ref().unchecked_ref())
+        .expect("cannot listen to visibilitychange");
+    listener.forget();
+}
+
+/// The cursive event for a `KeyboardEvent.key` value.
+#[cfg(target_arch = "wasm32")]
+fn key_event(key: &str) -> Option<Event> {
+    match key {
+        "ArrowLeft" => Some(Event::Key(Key::Left)),
+        "ArrowRight" => Some(Event::Key(Key::Right)),
+        "ArrowUp" => Some(Event::Key(Key::Up)),
+        "ArrowDown" => Some(Event::Key(Key::Down)),
+        "Enter" => Some(Event::Key(Key::Enter)),
+        "Escape" => Some(Event::Key(Key::Esc)),
+        _ => {
+            let mut chars = key.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(Event::Char(c)),
+                _ => None,
+            }
+        }
+    }
+}
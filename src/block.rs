@@ -1,11 +1,6 @@
 use crate::pos::Pos;
 
 use wasm_bindgen::prelude::*;
-use cursive::theme::{
-    BaseColor, ColorStyle, self,
-};
-use rand::thread_rng;
-use rand::seq::SliceRandom;
 
 
 
@@ -23,10 +18,6 @@ impl BlockWithPos {
         }
     }
 
-    pub fn to_cursive_color(&self) -> ColorStyle {
-        self.block.shape.to_cursive()
-    }
-
     pub fn cells(&self) -> Vec<Pos> {
         self.block.cells().into_iter().map(|(x,y)| (x + self.pos.0, y + self.pos.1)).collect()
     }
@@ -48,6 +39,23 @@ impl BlockWithPos {
     pub fn color(&self) -> BColor {
         self.block.color()
     }
+
+    /// The block whose cells are those of the guideline piece turned the same way around
+    /// `center`, as fumen and bots place pieces.
+    pub fn from_guideline(block: Block, center: Pos) -> Self {
+        let cells = block.shape.guideline_cells(block.rotation, center);
+        let mut own = block.cells();
+        own.sort();
+        BlockWithPos::from(block, (cells[0].0 - own[0].0, cells[0].1 - own[0].1))
+    }
+
+    /// The center of the guideline piece with the cells of this block.
+    pub fn guideline_center(&self) -> Pos {
+        let mut cells = self.cells();
+        cells.sort();
+        let origin = self.block.shape.guideline_cells(self.block.rotation, (0, 0));
+        (cells[0].0 - origin[0].0, cells[0].1 - origin[0].1)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -57,23 +65,29 @@ pub struct Block
     rotation: Rotation,
 }
 
-impl Default for Block {
-    fn default() -> Self {
+impl Block {
+    pub fn new(shape: Shape) -> Self {
         Self {
-            shape: Shape::random(),
+            shape,
             rotation: Rotation::R0,
         }
     }
-}
 
-impl Block {
-    pub fn new(shape: Shape) -> Self {
+    pub fn with_rotation(shape: Shape, rotation: Rotation) -> Self {
         Self {
             shape,
-            rotation: Rotation::R0,
+            rotation,
         }
     }
 
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
     pub fn cells(&self) -> Vec<Pos> {
         match self.rotation {
             Rotation::R0 => self.shape.cells(),
@@ -122,22 +136,8 @@ pub enum Shape {
 }
 
 impl Shape {
-    fn random() -> Self {
-        Self::all().pop().unwrap()
-    }
-
-    pub fn all() -> Vec<Shape> {
-        let mut shapes = vec![Shape::I, Shape::O, Shape::T, Shape::S, Shape::Z, Shape::J, Shape::L];
-        shapes.shuffle(&mut thread_rng());
-        shapes
-    }
 
-
-    pub fn to_cursive(&self) -> ColorStyle {
-        self.to_color().to_cursive()
-    }
-
-    fn to_color(&self) -> BColor {
+    pub fn to_color(&self) -> BColor {
         match self {
             Shape::I => BColor::I,
             Shape::O => BColor::O,
@@ -160,6 +160,33 @@ impl Shape {
             Shape::L => vec![(0,0),(-1,0),(1,0),(1,-1)],
         }
     }
+
+    /// Sorted cells of the guideline piece around its rotation center, which sits on a
+    /// cell for every piece but I and O.
+    fn guideline_cells(&self, rotation: Rotation, center: Pos) -> Vec<Pos> {
+        let up: [Pos; 4] = match self {
+            Shape::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+            Shape::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+            Shape::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+            Shape::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+            Shape::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+            Shape::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+            Shape::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+        };
+        // The guideline counts rows upwards, the board downwards.
+        let mut cells: Vec<Pos> = up
+            .iter()
+            .map(|&(x, y)| match rotation {
+                Rotation::R0 => (x, y),
+                Rotation::R90 => (y, -x),
+                Rotation::R180 => (-x, -y),
+                Rotation::R270 => (-y, x),
+            })
+            .map(|(x, y)| (center.0 + x, center.1 - y))
+            .collect();
+        cells.sort();
+        cells
+    }
 }
 
 #[wasm_bindgen]
@@ -177,25 +204,8 @@ pub enum BColor {
     GRID2 = 8,
     HINT = 9,
     WARNING = 10,
-}
-
-impl BColor {
-    pub fn to_cursive(&self) -> ColorStyle {
-        match self {
-            BColor::I => ColorStyle::new(theme::Color::Light(BaseColor::Blue), theme::Color::Light(BaseColor::Blue)),
-            BColor::O => ColorStyle::new(theme::Color::Light(BaseColor::Yellow), theme::Color::Light(BaseColor::Yellow)),
-            BColor::T => ColorStyle::new(theme::Color::Light(BaseColor::Magenta), theme::Color::Light(BaseColor::Magenta)),
-            BColor::S => ColorStyle::new(theme::Color::Light(BaseColor::Green), theme::Color::Light(BaseColor::Green)),
-            BColor::Z => ColorStyle::new(theme::Color::Light(BaseColor::Red), theme::Color::Light(BaseColor::Red)),
-            BColor::J => ColorStyle::new(theme::Color::Light(BaseColor::Cyan), theme::Color::Light(BaseColor::Cyan)),
-            BColor::L => ColorStyle::new(theme::Color::Light(BaseColor::White), theme::Color::Light(BaseColor::White)),
-            BColor::GRID1 => ColorStyle::new(theme::Color::Rgb(20, 20, 20), theme::Color::Rgb(20, 20, 20)),
-            BColor::GRID2 => ColorStyle::new(theme::Color::Rgb(80, 80, 80), theme::Color::Rgb(80, 80, 80)),
-            BColor::HINT => ColorStyle::new(theme::Color::Rgb(120, 120, 120), theme::Color::Rgb(120, 120, 120)),
-            BColor::WARNING => ColorStyle::new(theme::Color::Light(BaseColor::Yellow), theme::Color::Light(BaseColor::Yellow)),
-
-        }
-    }
+    /// Locked cells that belong to no piece, such as garbage rows.
+    GARBAGE = 11,
 }
 
 #[wasm_bindgen]
@@ -1,82 +1,109 @@
-use wasm_bindgen::prelude::*;
+use crate::block::{ BlockWithPos, BColor as Color, };
+use crate::engine::Snapshot;
 
-use crate::color_grid::ColorGrid;
-use crate::block::{ Block, BColor as Color, };
-use crate::lrd::LR;
-
-#[wasm_bindgen]
-pub struct Board {
-    grid: ColorGrid,
-}
 use cursive::{
-    event::{Event, EventResult, Key, },
     Printer, Vec2, View,
 };
 
+pub struct Board {
+    width: usize,
+    height: usize,
+    buffer: usize,
+    cells: Vec<Color>,
+    block: Option<BlockWithPos>,
+    hint: Option<BlockWithPos>,
+    hint_color: Color,
+    /// Where the built-in bot would put the block, when hints are on.
+    suggestion: Option<BlockWithPos>,
+    lock_progress: f64,
+}
+
 impl Board {
     pub fn new(width: usize, height: usize) -> Self {
         Board {
-            grid: ColorGrid::new(width, height, (Color::GRID1, Color::GRID2), Color::HINT),
+            width,
+            height,
+            buffer: 0,
+            cells: Vec::new(),
+            block: None,
+            hint: None,
+            hint_color: Color::HINT,
+            suggestion: None,
+            lock_progress: 0.0,
         }
     }
+
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.width = snapshot.width;
+        self.height = snapshot.height;
+        self.buffer = snapshot.buffer;
+        self.cells = snapshot.cells.clone();
+        self.block = Some(snapshot.block.clone());
+        self.hint = Some(snapshot.hint.clone());
+        self.hint_color = snapshot.hint_color;
+        self.lock_progress = snapshot.lock_progress;
+    }
+
+    pub fn suggest(&mut self, suggestion: Option<BlockWithPos>) {
+        self.suggestion = suggestion;
+    }
+
     fn draw_background(&self, printer: &Printer) {
         // console::log_1(&"draw_background".into());
-        let width = self.grid.width;
-        let height = self.grid.height;
-        for j in 0..height {
-            for i in 0..width {
-                printer.with_color(self.grid[self.grid.width * j + i].to_cursive(), |printer| {
-                    printer.print((2*i, j), "  ");
-                });
+        for j in 0..self.height {
+            for i in 0..self.width {
+                if let Some(color) = self.cells.get(self.width * (self.buffer + j) + i) {
+                    printer.with_color(color.to_cursive(), |printer| {
+                        printer.print((2*i, j), "  ");
+                    });
+                }
             }
         }
     }
 
     fn draw_block(&self, printer: &Printer) {
         // console::log_1(&"draw_block".into());
-        for (x, y) in self.grid.block.cells() {
-            printer.with_color(self.grid.block.to_cursive_color(), |printer| {
-                printer.print((2*x as usize, y as usize), "  ");
-            });
+        if let Some(block) = &self.block {
+            // The block fades as its lock delay runs out.
+            let color_style = if self.lock_progress > 0.0 {
+                block.color().dimmed(0.6 * self.lock_progress)
+            } else {
+                block.to_cursive_color()
+            };
+            for (x, y) in self.visible(block) {
+                printer.with_color(color_style, |printer| {
+                    printer.print((2*x, y), "  ");
+                });
+            }
         }
     }
 
     fn draw_hint(&self, printer: &Printer) {
-        for (x, y) in self.grid.hint().cells() {
-            printer.with_color(self.grid.hint_color.to_cursive(), |printer| {
-                printer.print((2*x as usize, y as usize), "  ");
-            });
+        if let Some(hint) = &self.hint {
+            for (x, y) in self.visible(hint) {
+                printer.with_color(self.hint_color.to_cursive(), |printer| {
+                    printer.print((2*x, y), "  ");
+                });
+            }
         }
     }
 
-    pub fn renew(&mut self) {
-        self.grid.renew();
-    }
-
-    pub fn merge_block(&mut self) -> usize {
-        self.grid.merge_block()
-    }
-
-
-    pub fn insert(&mut self, block: Block) {
-        self.grid.insert(block);
-    }
-
-    pub fn on_down(&mut self, is_drop: bool, is_begin: bool) -> (bool, bool) {
-        self.grid.on_down(is_drop, is_begin)
+    fn draw_suggestion(&self, printer: &Printer) {
+        if let Some(suggestion) = &self.suggestion {
+            for (x, y) in self.visible(suggestion) {
+                printer.with_color(suggestion.color().dimmed(0.5), |printer| {
+                    printer.print((2*x, y), "  ");
+                });
+            }
+        }
     }
 
-    pub fn handle_event(&mut self, event: Event, hit_bottom: bool) -> bool {
-        match event {
-            Event::Key(Key::Left)  => self.grid.handle_lr(LR::Left, hit_bottom, false),
-            Event::Key(Key::Right) => self.grid.handle_lr(LR::Right, hit_bottom, false),
-            Event::Key(Key::Up) | Event::Char('e') | Event::Char('E') => self.grid.rotate(hit_bottom, true),
-            Event::Char('s') | Event::Char('S') => self.grid.flip_turn(hit_bottom),
-            Event::Char('w') | Event::Char('W') => self.grid.rotate(hit_bottom, false),
-            Event::Char('a') | Event::Char('A') => self.grid.handle_lr(LR::Left, hit_bottom, true),
-            Event::Char('d') | Event::Char('D') => self.grid.handle_lr(LR::Right, hit_bottom, true),
-            _ => false,
-        }
+    /// Cells of `block` inside the visible field, relative to its top left corner.
+    fn visible(&self, block: &BlockWithPos) -> Vec<(usize, usize)> {
+        block.cells().into_iter()
+            .filter(|(_, y)| *y >= self.buffer as i32)
+            .map(|(x, y)| (x as usize, y as usize - self.buffer))
+            .collect()
     }
 }
 
@@ -84,15 +111,11 @@ impl View for Board {
     fn draw(&self, printer: &Printer) {
         self.draw_background(printer);
         self.draw_hint(printer);
+        self.draw_suggestion(printer);
         self.draw_block(printer)
     }
 
     fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
-        Vec2::new(20,20)
-    }
-
-    fn on_event(&mut self, event: Event) -> EventResult {
-        self.handle_event(event, false);
-        EventResult::Consumed(None)
+        Vec2::new(2 * self.width, self.height)
     }
 }
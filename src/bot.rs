@@ -0,0 +1,133 @@
+//! Carries Tetris Bot Protocol messages to an external bot: a child process speaking one
+//! JSON message per line natively, and a Web Worker on wasm.
+
+use crate::tbp::{ BotMessage, FrontendMessage };
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{ BufRead, BufReader, Write };
+#[cfg(not(target_arch = "wasm32"))]
+use std::process::{ Child, ChildStdin, Command, Stdio };
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{ self, Receiver, TryRecvError };
+
+#[cfg(target_arch = "wasm32")]
+use std::{ cell::RefCell, collections::VecDeque, rc::Rc };
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{ closure::Closure, JsCast, JsValue };
+#[cfg(target_arch = "wasm32")]
+use web_sys::{ MessageEvent, Worker };
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Bot {
+    child: Child,
+    stdin: ChildStdin,
+    /// Lines the bot wrote, read on a thread of their own so the game never waits.
+    lines: Receiver<String>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Bot {
+    /// Runs `command`, split on whitespace into the program and its arguments.
+    pub fn start(command: &str) -> Result<Bot, String> {
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or("No bot command given")?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|error| format!("Cannot run the bot {}: {}", program, error))?;
+        let stdin = child.stdin.take().ok_or("The bot has no input")?;
+        let stdout = child.stdout.take().ok_or("The bot has no output")?;
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Bot { child, stdin, lines })
+    }
+
+    pub fn send(&mut self, message: &FrontendMessage) -> Result<(), String> {
+        writeln!(self.stdin, "{}", message.encode())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|error| format!("Cannot reach the bot: {}", error))
+    }
+
+    /// Messages that arrived since the last call, oldest first.
+    pub fn receive(&mut self) -> Vec<Result<BotMessage, String>> {
+        let mut messages = Vec::new();
+        loop {
+            match self.lines.try_recv() {
+                Ok(line) if line.trim().is_empty() => {}
+                Ok(line) => messages.push(BotMessage::decode(&line)),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    messages.push(Err("The bot quit".to_string()));
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Bot {
+    fn drop(&mut self) {
+        self.send(&FrontendMessage::Quit).ok();
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct Bot {
+    worker: Worker,
+    /// Messages the worker posted, as JSON.
+    inbox: Rc<RefCell<VecDeque<String>>>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Bot {
+    /// Starts the worker script at `url`.
+    pub fn start(url: &str) -> Result<Bot, String> {
+        let worker = Worker::new(url).map_err(|_| format!("Cannot start the bot {}", url))?;
+        let inbox = Rc::new(RefCell::new(VecDeque::new()));
+        let queued = inbox.clone();
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let data = event.data();
+            let json = match data.as_string() {
+                Some(text) => Some(text),
+                None => js_sys::JSON::stringify(&data).ok().and_then(|json| json.as_string()),
+            };
+            if let Some(json) = json {
+                queued.borrow_mut().push_back(json);
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        worker.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        Ok(Bot { worker, inbox, _on_message: on_message })
+    }
+
+    pub fn send(&mut self, message: &FrontendMessage) -> Result<(), String> {
+        let message: JsValue = js_sys::JSON::parse(&message.encode()).map_err(|_| "Cannot encode the message")?;
+        self.worker.post_message(&message).map_err(|_| "Cannot reach the bot".to_string())
+    }
+
+    /// Messages that arrived since the last call, oldest first.
+    pub fn receive(&mut self) -> Vec<Result<BotMessage, String>> {
+        self.inbox.borrow_mut().drain(..).map(|json| BotMessage::decode(&json)).collect()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Bot {
+    fn drop(&mut self) {
+        self.send(&FrontendMessage::Quit).ok();
+        self.worker.terminate();
+    }
+}
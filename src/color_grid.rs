@@ -1,27 +1,36 @@
 use crate::block::{ Block, BlockWithPos, BColor as Color, };
 use crate::lrd::{ LRD, LR };
+use crate::rotation::{ RotationSystemKind, Turn };
 
 use std::ops::Index;
 
-#[derive(Clone, Copy)]
-enum FlipRotate {
-    FlipTurn,
-    Rotate {
-        clockwise: bool,
-    }
+/// The last thing that moved the active block, needed to recognise spins.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Move {
+    Shift,
+    Fall,
+    /// A turn and the index of the kick that made it fit.
+    Turn { turn: Turn, kick: usize },
 }
 
+#[derive(Clone)]
 pub struct ColorGrid {
     pub width: usize,
+    /// Rows of the whole grid, hidden buffer rows included.
     pub height: usize,
+    /// Hidden rows above the visible field where blocks spawn.
+    pub buffer: usize,
     data: Vec<Color>,
     pub block: BlockWithPos,
     background_color: (Color, Color),
     pub hint_color: Color,
+    rotation_system: RotationSystemKind,
+    last_move: Option<Move>,
 }
 
 impl ColorGrid {
-    pub fn new(width: usize, height: usize, background_color: (Color, Color), hint_color: Color) -> ColorGrid {
+    pub fn new(width: usize, visible_height: usize, buffer: usize, background_color: (Color, Color), hint_color: Color, rotation_system: RotationSystemKind, block: Block) -> ColorGrid {
+        let height = visible_height + buffer;
         let mut data = Vec::with_capacity(width * height);
         for h in 0..height {
             for w in 0..width {
@@ -33,100 +42,96 @@ impl ColorGrid {
                     data.push(color);
             }
         }
-        ColorGrid {
+        let mut grid = ColorGrid {
             width,
             height,
+            buffer,
             data,
-            block: Self::insert_random(width),
+            block: BlockWithPos::from(block.clone(), (0, 0)),
             background_color,
             hint_color,
-        }
+            rotation_system,
+            last_move: None,
+        };
+        grid.insert(block);
+        grid
+    }
+
+    /// How the active block last moved, or `None` if it hasn't moved since it spawned.
+    pub fn last_move(&self) -> Option<Move> {
+        self.last_move
     }
 
-    pub fn handle_lr(&mut self, lr: LR, hit_bottom: bool, is_hard: bool) -> bool {
+    pub fn handle_lr(&mut self, lr: LR, is_hard: bool) -> bool {
         let lrd = lr.to_lrd();
         let mut stopped = false;
         let mut moved = false;
         while !stopped {
             let (block, hit_wall) = self.move_block_lrd(&self.block, lrd);
-            if block.is_some() {
+            if let Some(block) = block {
                 moved = true;
-                self.block = block.unwrap();
+                self.block = block;
             }
             stopped = hit_wall || !is_hard;
         }
-        if hit_bottom {
-            self.on_down(true, false);
+        if moved {
+            self.last_move = Some(Move::Shift);
         }
         moved
     }
 
-    pub fn on_down(&mut self, is_drop: bool, is_begin: bool) -> (bool, bool) {
-        let mut stopped = false;
-        let mut hit_bottom = is_drop;
-        let mut current: Option<BlockWithPos>;
-        let gameover = false;
-        while !stopped {
-           (current, hit_bottom)= self.move_block_lrd(&self.block, LRD::Down);
-            match current {
-                Some(b) => self.block = b,
-                None => return (is_begin, true),
+    /// Moves the block one row down, returning whether there was room for it.
+    pub fn step_down(&mut self) -> bool {
+        match self.move_block_lrd(&self.block, LRD::Down) {
+            (Some(block), _) => {
+                self.block = block;
+                self.last_move = Some(Move::Fall);
+                true
             }
-            stopped = hit_bottom || !is_drop;
+            (None, _) => false,
         }
-        (gameover, hit_bottom)
     }
 
-    pub fn rotate(&mut self, hit_bottom: bool, clockwise: bool) -> bool {
-        self.flip_rotate(hit_bottom, FlipRotate::Rotate { clockwise })
+    /// Drops the block as far as it goes and returns the number of rows it fell.
+    pub fn hard_drop(&mut self) -> usize {
+        let mut rows = 0;
+        while self.step_down() {
+            rows += 1;
+        }
+        rows
     }
 
-    pub fn flip_turn(&mut self, hit_bottom: bool) -> bool {
-        self.flip_rotate(hit_bottom, FlipRotate::FlipTurn)
+    pub fn is_grounded(&self) -> bool {
+        !self.can_move(&self.block, LRD::Down).0
     }
 
-    fn flip_rotate(&mut self, hit_bottom: bool, flip_rotate: FlipRotate) -> bool {
-        for cell in self.block.cells() {
-            let mut pos = cell;
-            for _ in 0..10 {
-                let mut possible = true;
-                let next_block = match flip_rotate {
-                    FlipRotate::FlipTurn => self.block.flip_turn(),
-                    FlipRotate::Rotate { clockwise } => self.block.rotate(clockwise),
-                };
-
-                for (x, y) in next_block.cells() {
-                    if x < 0 {
-                        pos.0 += 1;
-                        possible = false;
-                        break;
-                    } else if x >= self.width as i32 {
-                        pos.0 -= 1;
-                        possible = false;
-                        break;
-                    } else if y < 0 {
-                        pos.1 += 1;
-                        possible = false;
-                        break;
-                    } else if y >= self.height as i32 {
-                        pos.1 -= 1;
-                        possible = false;
-                        break;
-                    } else if self.is_occupied(x as usize, y as usize) {
-                        possible = false;
-                        break;
-                    }
-                }
-                if possible {
-                    self.block = BlockWithPos::from(next_block.block, pos);
-                    if hit_bottom {
-                        self.on_down(true, false);
-                    }
-                    return true
-                }
+    pub fn rotate(&mut self, clockwise: bool) -> bool {
+        self.turn(if clockwise { Turn::Clockwise } else { Turn::CounterClockwise })
+    }
+
+    pub fn flip_turn(&mut self) -> bool {
+        self.turn(Turn::Flip)
+    }
+
+    fn turn(&mut self, turn: Turn) -> bool {
+        match self.rotation_system.system().rotate(self, &self.block, turn) {
+            Some((block, kick)) => {
+                self.block = block;
+                self.last_move = Some(Move::Turn { turn, kick });
+                true
             }
+            None => false,
         }
-        false
+    }
+
+    /// Whether every cell of `block` lies inside the grid on an empty square.
+    pub fn fits(&self, block: &BlockWithPos) -> bool {
+        block.cells().into_iter().all(|(x, y)| !self.is_blocked(x, y))
+    }
+
+    /// Whether a cell is outside the grid or taken by the stack.
+    pub fn is_blocked(&self, x: i32, y: i32) -> bool {
+        x < 0 || x >= self.width as i32 || y < 0 || y >= self.height as i32 || self.is_occupied(x as usize, y as usize)
     }
 
     fn move_block_lrd(&self, block: &BlockWithPos, lrd: LRD) -> (Option<BlockWithPos>, bool) {
@@ -159,17 +164,31 @@ impl ColorGrid {
         (moved, stop)
     }
 
-    fn is_occupied(&self, x: usize, y: usize) -> bool {
+    pub fn cells(&self) -> &[Color] {
+        &self.data
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.data[self.width * y + x] = color;
+    }
+
+    pub fn is_occupied(&self, x: usize, y: usize) -> bool {
         self.data[self.width * y  + x] != self.background_color.0 && self.data[self.width * y  + x] != self.background_color.1
     }
 
+    /// Whether no locked cell is left on the grid, e.g. after a perfect clear.
+    pub fn is_empty(&self) -> bool {
+        (0..self.height).all(|y| (0..self.width).all(|x| !self.is_occupied(x, y)))
+    }
+
     pub fn merge_block(&mut self) -> usize {
         self.fill_board_with_block();
         self.remove_rows_if_possible()
     }
 
     pub fn insert(&mut self, block: Block) {
-        self.block = BlockWithPos::from(block, (self.width as i32 / 2, 1));
+        self.block = self.spawn(block);
+        self.last_move = None;
     }
 
     fn fill_board_with_block(&mut self) {
@@ -202,28 +221,21 @@ impl ColorGrid {
         if rows_to_remove.is_empty() {
             return;
         }
-        let mut fill_y = self.height - 1;
-        let mut check_y = self.height - 1;
-        for row in rows_to_remove {
-            while check_y > row {
-                if fill_y != check_y {
-                    self.set_background_row(check_y, fill_y);
-                }
-                fill_y -= 1;
-                check_y -= 1;
+        // Moves the rows that stay down, bottom first, then empties the rows left on top.
+        let mut fill_y = self.height;
+        for y in (0..self.height).rev() {
+            if rows_to_remove.contains(&y) {
+                continue;
             }
-            check_y = row - 1;
-        }
-        while check_y > 0 {
-            self.set_background_row(check_y, fill_y);
             fill_y -= 1;
-            check_y -= 1;
+            if fill_y != y {
+                self.set_background_row(y, fill_y);
+            }
         }
-        while fill_y > 0 {
+        for y in 0..fill_y {
             for x in 0..self.width {
-                self.set_background(x, fill_y);
+                self.set_background(x, y);
             }
-            fill_y -= 1;
         }
     }
 
@@ -236,13 +248,35 @@ impl ColorGrid {
         self.data[self.width * y  + x] = color;
     }
 
-    pub fn renew(&mut self) {
+    /// Pushes the stack up by a row of garbage for each of `holes`, from the top down, each
+    /// open at its column. Returns whether taken cells were pushed off the top. Only call
+    /// it between a lock and the next spawn, as the active block doesn't move along.
+    pub fn push_garbage(&mut self, holes: &[usize]) -> bool {
+        let rows = holes.len().min(self.height);
+        let overflow = (0..rows).any(|y| (0..self.width).any(|x| self.is_occupied(x, y)));
+        for y in 0..self.height - rows {
+            self.set_background_row(y + rows, y);
+        }
+        for (i, hole) in holes.iter().take(rows).enumerate() {
+            let y = self.height - rows + i;
+            for x in 0..self.width {
+                if x == *hole {
+                    self.set_background(x, y);
+                } else {
+                    self.data[self.width * y + x] = Color::GARBAGE;
+                }
+            }
+        }
+        overflow
+    }
+
+    pub fn renew(&mut self, block: Block) {
         for x in 0..self.width {
             for y in 0..self.height {
                 self.set_background(x, y);
             }
         }
-        self.block = ColorGrid::insert_random(self.width)
+        self.insert(block)
     }
 
     fn set_background_row(&mut self, from: usize, to: usize) {
@@ -260,8 +294,18 @@ impl ColorGrid {
         }
     }
 
-    pub fn insert_random(width: usize) -> BlockWithPos {
-        BlockWithPos::from(Block::default(), (width as i32 / 2, 1))
+    /// Places a new block with its flat side on the top visible row and the rest in the
+    /// buffer. When that is taken it spawns a row higher, if the buffer has room.
+    fn spawn(&self, block: Block) -> BlockWithPos {
+        let x = self.width as i32 / 2;
+        let y = self.buffer.max(1) as i32;
+        let spawned = BlockWithPos::from(block, (x, y));
+        let raised = BlockWithPos::from(spawned.block.clone(), (x, y - 1));
+        if !self.fits(&spawned) && self.buffer >= 2 && self.fits(&raised) {
+            raised
+        } else {
+            spawned
+        }
     }
 
     pub fn hint(&self) -> BlockWithPos {
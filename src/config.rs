@@ -0,0 +1,197 @@
+use crate::fumen;
+use crate::garbage::{ AttackTable, HolePlacement };
+use crate::level::GravityCurve;
+use crate::lock_delay::LockReset;
+use crate::mode::GameModeKind;
+use crate::randomizer::RandomizerKind;
+use crate::rotation::RotationSystemKind;
+use crate::top_out::TopOutRules;
+
+/// Smallest board width and height that still fit every piece in every rotation.
+const MIN_SIZE: usize = 4;
+
+/// Most upcoming blocks the queue can show.
+pub const MAX_PREVIEWS: usize = 7;
+
+/// Settings fixed for the duration of a game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    pub mode: GameModeKind,
+    /// Overrides the top-out rules of the mode when set.
+    pub top_out: Option<TopOutRules>,
+    pub width: usize,
+    /// Visible rows of the board.
+    pub height: usize,
+    /// Hidden rows above the visible board where blocks spawn.
+    pub buffer: usize,
+    /// Replay the same piece sequence in every game when set, otherwise each game draws a new seed.
+    pub seed: Option<u64>,
+    pub randomizer: RandomizerKind,
+    pub rotation_system: RotationSystemKind,
+    /// Upcoming blocks shown, from 0 for a "no preview" challenge to `MAX_PREVIEWS`.
+    pub previews: usize,
+    /// Draw the next block larger than the rest of the queue.
+    pub big_preview: bool,
+    /// Allow swapping the active piece into the hold slot; off for classic play.
+    pub hold: bool,
+    /// Gravity at each level in cells per frame at 60 frames per second (G): 1/60 G is
+    /// one cell per second, and from 20 G on blocks appear directly on the stack.
+    pub gravity_curve: GravityCurve,
+    pub start_level: usize,
+    /// Cleared lines it takes to advance one level.
+    pub lines_per_level: usize,
+    /// How many times faster the block falls while soft dropping.
+    pub soft_drop_factor: f64,
+    /// Delayed auto shift: milliseconds a direction is held before it repeats.
+    pub das: u64,
+    /// Auto repeat rate: milliseconds between repeated moves, 0 for straight to the wall.
+    pub arr: u64,
+    /// Milliseconds a grounded block waits before it locks.
+    pub lock_delay: u64,
+    pub lock_reset: LockReset,
+    pub max_lock_resets: usize,
+    /// Name of the saved key binding profile to start with.
+    pub keymap: String,
+    /// A fumen every game starts from: the field of its first page, and its piece as the
+    /// first block.
+    pub fumen: Option<String>,
+    /// A Tetris Bot Protocol bot that plays instead of the player: a command to run
+    /// natively, or the URL of a Web Worker script on wasm.
+    pub bot: Option<String>,
+    /// Let the built-in bot play from the start, as a demo.
+    pub autoplay: bool,
+    /// Rounds a versus match lasts at most: whoever wins more than half of them wins it.
+    pub best_of: usize,
+    /// Rows of garbage each kind of clear sends in versus.
+    pub attack: AttackTable,
+    /// Where the holes of garbage rows go.
+    pub garbage: HolePlacement,
+    /// The relay server to play versus on with someone elsewhere, e.g. `ws://localhost:9000`.
+    pub server: Option<String>,
+    /// The room on the server to meet the other player in.
+    pub room: String,
+    /// The name the other player sees.
+    pub name: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            mode: GameModeKind::default(),
+            top_out: None,
+            width: 10,
+            height: 20,
+            buffer: 20,
+            seed: None,
+            randomizer: RandomizerKind::default(),
+            rotation_system: RotationSystemKind::default(),
+            previews: 3,
+            big_preview: false,
+            hold: true,
+            gravity_curve: GravityCurve::default(),
+            start_level: 1,
+            lines_per_level: 10,
+            soft_drop_factor: 10.0,
+            das: 167,
+            arr: 33,
+            lock_delay: 500,
+            lock_reset: LockReset::default(),
+            max_lock_resets: 15,
+            keymap: "default".to_string(),
+            fumen: None,
+            bot: None,
+            autoplay: false,
+            best_of: 3,
+            attack: AttackTable::default(),
+            garbage: HolePlacement::default(),
+            server: None,
+            room: "lobby".to_string(),
+            name: "player".to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Reads settings from a URL query string such as `seed=42&randomizer=tgm`.
+    /// Unknown keys and malformed values keep their defaults.
+    pub fn from_query(query: &str) -> Self {
+        let mut config = Self::default();
+        for pair in query.trim_start_matches('?').split('&') {
+            let mut kv = pair.splitn(2, '=');
+            let (key, value) = (kv.next().unwrap_or(""), kv.next().unwrap_or(""));
+            match key {
+                "mode" => config.mode = GameModeKind::from_name(value).unwrap_or(config.mode),
+                "top_out" => config.top_out = TopOutRules::from_name(value).or(config.top_out),
+                "width" => config.width = value.parse().ok().filter(|width| *width >= MIN_SIZE).unwrap_or(config.width),
+                "height" => config.height = value.parse().ok().filter(|height| *height >= MIN_SIZE).unwrap_or(config.height),
+                "buffer" => config.buffer = value.parse().unwrap_or(config.buffer),
+                "seed" => config.seed = value.parse().ok().or(config.seed),
+                "randomizer" => config.randomizer = RandomizerKind::from_name(value).unwrap_or(config.randomizer),
+                "rotation" => config.rotation_system = RotationSystemKind::from_name(value).unwrap_or(config.rotation_system),
+                "previews" => config.previews = value.parse().ok().filter(|previews| *previews <= MAX_PREVIEWS).unwrap_or(config.previews),
+                "big_preview" => config.big_preview = value == "on",
+                "hold" => config.hold = value != "off",
+                "gravity" => config.gravity_curve = GravityCurve::from_name(value).unwrap_or(config.gravity_curve),
+                "level" => config.start_level = value.parse().unwrap_or(config.start_level),
+                "lines_per_level" => config.lines_per_level = value.parse().unwrap_or(config.lines_per_level),
+                "das" => config.das = value.parse().unwrap_or(config.das),
+                "arr" => config.arr = value.parse().unwrap_or(config.arr),
+                "sdf" => config.soft_drop_factor = value.parse().ok().filter(|sdf: &f64| *sdf >= 1.0).unwrap_or(config.soft_drop_factor),
+                "lock_delay" => config.lock_delay = value.parse().unwrap_or(config.lock_delay),
+                "lock_reset" => config.lock_reset = LockReset::from_name(value).unwrap_or(config.lock_reset),
+                "lock_resets" => config.max_lock_resets = value.parse().unwrap_or(config.max_lock_resets),
+                "best_of" => config.best_of = value.parse().ok().filter(|best_of| *best_of >= 1).unwrap_or(config.best_of),
+                "attack" => config.attack = AttackTable::from_name(value).unwrap_or(config.attack),
+                "garbage" => config.garbage = HolePlacement::from_name(value).unwrap_or(config.garbage),
+                "keymap" if !value.is_empty() => config.keymap = value.to_string(),
+                "autoplay" => config.autoplay = value == "on",
+                "bot" if !value.is_empty() => config.bot = Some(value.to_string()),
+                "server" if !value.is_empty() => config.server = Some(value.to_string()),
+                "room" if !value.is_empty() => config.room = value.to_string(),
+                "name" if !value.is_empty() => config.name = value.to_string(),
+                "fumen" if fumen::decode(value).is_ok() => config.fumen = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Writes the rules of the game as a query `from_query` reads back to the same
+    /// config. Who plays is left out: the key binding profile, the bot, autoplay and
+    /// where to meet online.
+    pub fn to_query(&self) -> String {
+        let mut pairs = vec![
+            format!("mode={}", self.mode.name()),
+            format!("width={}", self.width),
+            format!("height={}", self.height),
+            format!("buffer={}", self.buffer),
+            format!("randomizer={}", self.randomizer.name()),
+            format!("rotation={}", self.rotation_system.name()),
+            format!("previews={}", self.previews),
+            format!("big_preview={}", if self.big_preview { "on" } else { "off" }),
+            format!("hold={}", if self.hold { "on" } else { "off" }),
+            format!("gravity={}", self.gravity_curve.name()),
+            format!("level={}", self.start_level),
+            format!("lines_per_level={}", self.lines_per_level),
+            format!("sdf={}", self.soft_drop_factor),
+            format!("das={}", self.das),
+            format!("arr={}", self.arr),
+            format!("lock_delay={}", self.lock_delay),
+            format!("lock_reset={}", self.lock_reset.name()),
+            format!("lock_resets={}", self.max_lock_resets),
+            format!("best_of={}", self.best_of),
+            format!("attack={}", self.attack.name()),
+            format!("garbage={}", self.garbage.name()),
+        ];
+        if let Some(top_out) = self.top_out {
+            pairs.push(format!("top_out={}", top_out.name()));
+        }
+        if let Some(seed) = self.seed {
+            pairs.push(format!("seed={}", seed));
+        }
+        if let Some(fumen) = &self.fumen {
+            pairs.push(format!("fumen={}", fumen));
+        }
+        pairs.join("&")
+    }
+}
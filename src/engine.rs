@@ -0,0 +1,601 @@
+use crate::block::{ Block, BlockWithPos, BColor as Color, };
+use crate::color_grid::ColorGrid;
+use crate::config::Config;
+use crate::fumen::{ self, Page };
+use crate::garbage::GarbageQueue;
+use crate::level::{ self, MAX_GRAVITY };
+use crate::lock_delay::LockDelay;
+use crate::lrd::LR;
+use crate::mode::{ GameMode, GameModeKind, Progress };
+use crate::piece_queue::PieceQueue;
+use crate::save::Save;
+use crate::scoring::{ Award, Scoring };
+use crate::top_out::{ TopOut, TopOutRules };
+use crate::tspin;
+
+use rand::{ rngs::StdRng, SeedableRng };
+use serde::{ Deserialize, Serialize };
+
+/// Milliseconds the label of a clear stays on screen.
+const ACTION_TIME: u64 = 2000;
+
+/// Abstract player inputs understood by the engine, independent of any front end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    Left,
+    Right,
+    LeftMost,
+    RightMost,
+    RotateClockwise,
+    RotateCounterClockwise,
+    FlipTurn,
+    SoftDrop,
+    /// Stops soft dropping before the next block spawns.
+    SoftDropRelease,
+    HardDrop,
+    /// Drops the block onto the stack without locking it, as bots tuck pieces under overhangs.
+    SonicDrop,
+    Hold,
+}
+
+/// Things that happened while the engine processed an input or a tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    Locked { lines: usize },
+    Scored(Award),
+    /// Rows of garbage for the opponent in versus: what is left of the attack of a clear
+    /// after it cancelled waiting garbage.
+    Attack(usize),
+    GameOver,
+}
+
+/// Why a game ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The goal of the mode was reached.
+    Complete,
+    ToppedOut(TopOut),
+}
+
+/// Everything a renderer needs to draw one frame.
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    pub width: usize,
+    /// Visible rows; `cells` and block positions also cover the `buffer` rows above them.
+    pub height: usize,
+    pub buffer: usize,
+    pub cells: Vec<Color>,
+    pub block: BlockWithPos,
+    pub hint: BlockWithPos,
+    pub hint_color: Color,
+    pub queue: Vec<Block>,
+    pub hold: Option<Block>,
+    pub can_hold: bool,
+    pub hold_enabled: bool,
+    /// How far the lock delay of the grounded block has run, from 0 to 1.
+    pub lock_progress: f64,
+    pub points: u64,
+    pub lines: usize,
+    pub level: usize,
+    pub combo: Option<usize>,
+    pub back_to_back: bool,
+    /// The last clear or spin worth announcing, while it is recent.
+    pub action: Option<Award>,
+    pub mode: GameModeKind,
+    /// Lines describing the progress towards the goal of the mode.
+    pub hud: Vec<String>,
+    pub elapsed: u64,
+    /// Milliseconds to show on the clock, which counts down in timed modes.
+    pub clock: u64,
+    pub seed: u64,
+    pub is_paused: bool,
+    pub gameover: bool,
+    pub outcome: Option<Outcome>,
+    /// Rows of garbage waiting to rise.
+    pub garbage: usize,
+}
+
+pub struct Engine {
+    config: Config,
+    grid: ColorGrid,
+    queue: PieceQueue,
+    hold: Option<Block>,
+    can_hold: bool,
+    lock_delay: LockDelay,
+    seed: u64,
+    rng: StdRng,
+    /// Picks the holes of garbage, apart from `rng` so garbage doesn't change the blocks dealt.
+    garbage_rng: StdRng,
+    /// Garbage waiting to rise under the stack.
+    garbage: GarbageQueue,
+    scoring: Scoring,
+    action: Option<(Award, u64)>,
+    lines: usize,
+    mode: Box<dyn GameMode>,
+    elapsed: u64,
+    is_paused: bool,
+    fall: f64,
+    soft_drop: bool,
+    outcome: Option<Outcome>,
+    /// Every input the game took, with the elapsed milliseconds it came at.
+    inputs: Vec<(u64, Input)>,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+impl Engine {
+    pub fn new(config: Config) -> Self {
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut queue = PieceQueue::new(config.randomizer, config.previews, &mut rng);
+        let block = queue.pop_and_spawn_new_block(&mut rng);
+        let grid = ColorGrid::new(config.width, config.height, config.buffer, (Color::GRID1, Color::GRID2), Color::HINT, config.rotation_system, block);
+        let mut lock_delay = LockDelay::new(config.lock_delay, config.lock_reset, config.max_lock_resets);
+        let mode = config.mode.create();
+        lock_delay.renew(grid.block.pos.1);
+        let mut engine = Engine {
+            grid,
+            queue,
+            hold: None,
+            can_hold: true,
+            lock_delay,
+            config,
+            seed,
+            rng,
+            garbage_rng: StdRng::seed_from_u64(!seed),
+            garbage: GarbageQueue::new(),
+            scoring: Scoring::new(),
+            action: None,
+            lines: 0,
+            mode,
+            elapsed: 0,
+            is_paused: false,
+            fall: 0.0,
+            soft_drop: false,
+            outcome: None,
+            inputs: Vec::new(),
+        };
+        engine.set_up_field();
+        engine
+    }
+
+    pub fn new_game(&mut self) {
+        self.new_game_with_seed(self.config.seed.unwrap_or_else(rand::random));
+    }
+
+    pub fn new_game_with_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+        self.garbage_rng = StdRng::seed_from_u64(!seed);
+        self.garbage.clear();
+        self.queue.renew(self.config.randomizer, self.config.previews, &mut self.rng);
+        let block = self.queue.pop_and_spawn_new_block(&mut self.rng);
+        self.grid.renew(block);
+        self.lock_delay.renew(self.grid.block.pos.1);
+        self.hold = None;
+        self.can_hold = true;
+        self.scoring = Scoring::new();
+        self.action = None;
+        self.lines = 0;
+        self.elapsed = 0;
+        self.is_paused = false;
+        self.fall = 0.0;
+        self.soft_drop = false;
+        self.mode = self.config.mode.create();
+        self.outcome = None;
+        self.inputs.clear();
+        self.set_up_field();
+    }
+
+    /// Lays out the first page of the fumen in the config, if any. Its piece replaces the
+    /// first block when it fits, otherwise the first block spawns again over the new stack.
+    fn set_up_field(&mut self) {
+        let page = match self.config.fumen.as_deref().map(fumen::decode) {
+            Some(Ok(pages)) => pages.into_iter().next().unwrap_or_default(),
+            _ => return,
+        };
+        let (cells, block) = page.to_board(self.grid.width, self.grid.height);
+        for (i, cell) in cells.iter().enumerate() {
+            if let Some(color) = cell {
+                self.grid.set(i % self.grid.width, i / self.grid.width, *color);
+            }
+        }
+        match block.filter(|block| self.grid.fits(block)) {
+            Some(block) => self.grid.block = block,
+            None => self.grid.insert(self.grid.block.block.clone()),
+        }
+        self.lock_delay.renew(self.grid.block.pos.1);
+    }
+
+    /// Locked cells row by row, buffer rows first.
+    pub fn locked_cells(&self) -> Vec<Option<Color>> {
+        let width = self.grid.width;
+        self.grid.cells().iter().enumerate().map(|(i, color)| Some(*color).filter(|_| self.grid.is_occupied(i % width, i / width))).collect()
+    }
+
+    /// The board and the falling block, when it is within the field, as a fumen of one page.
+    pub fn fumen(&self) -> Result<String, String> {
+        let page = Page::from_board(&self.locked_cells(), self.grid.width, Some(&self.grid.block))?;
+        fumen::encode(&[page])
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Sets the level the next game starts at.
+    pub fn set_start_level(&mut self, level: usize) {
+        self.config.start_level = level;
+    }
+
+    pub fn level(&self) -> usize {
+        level::level(self.config.start_level, self.lines, self.config.lines_per_level)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Milliseconds played, not counting pauses.
+    pub fn elapsed(&self) -> u64 {
+        self.elapsed
+    }
+
+    /// The inputs of the current game so far, enough to replay it from its seed.
+    pub fn inputs(&self) -> &[(u64, Input)] {
+        &self.inputs
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    /// Whether the active block soft drops. Every new block starts without.
+    pub fn is_soft_dropping(&self) -> bool {
+        self.soft_drop
+    }
+
+    /// Whether the active block rests on the stack or the floor.
+    pub fn is_grounded(&self) -> bool {
+        self.grid.is_grounded()
+    }
+
+    pub fn grid(&self) -> &ColorGrid {
+        &self.grid
+    }
+
+    /// Blocks dealt so far, the queue included.
+    pub fn drawn(&self) -> usize {
+        self.queue.drawn()
+    }
+
+    /// Captures the game in progress.
+    pub fn save(&self) -> Save {
+        Save {
+            config: Config { seed: Some(self.seed), ..self.config.clone() },
+            cells: self.locked_cells(),
+            shape: self.grid.block.block.shape(),
+            rotation: self.grid.block.block.rotation(),
+            pos: self.grid.block.pos,
+            hold: self.hold.as_ref().map(Block::shape),
+            can_hold: self.can_hold,
+            drawn: self.queue.drawn(),
+            points: self.scoring.points(),
+            combo: self.scoring.combo(),
+            back_to_back: self.scoring.is_back_to_back(),
+            lines: self.lines,
+            elapsed: self.elapsed,
+            lock_delay: self.lock_delay.state(),
+            fall: self.fall,
+            soft_drop: self.soft_drop,
+            inputs: self.inputs.clone(),
+        }
+    }
+
+    /// Goes on with a saved game, unless the save doesn't fit its own board.
+    pub fn resume(save: Save) -> Result<Engine, String> {
+        let mut engine = Engine::new(save.config.clone());
+        let (width, height) = (engine.grid.width, engine.grid.height);
+        if save.cells.len() != width * height {
+            return Err("The saved board has the wrong size".to_string());
+        }
+        engine.rng = StdRng::seed_from_u64(engine.seed);
+        engine.queue = PieceQueue::resume(save.config.randomizer, save.config.previews, save.drawn, &mut engine.rng);
+        engine.grid.renew(Block::new(save.shape));
+        for (i, cell) in save.cells.iter().enumerate() {
+            if let Some(color) = cell {
+                engine.grid.set(i % width, i / width, *color);
+            }
+        }
+        engine.grid.block = BlockWithPos::from(Block::with_rotation(save.shape, save.rotation), save.pos);
+        if !engine.grid.fits(&engine.grid.block) {
+            return Err("The saved block doesn't fit the board".to_string());
+        }
+        engine.hold = save.hold.map(Block::new);
+        engine.can_hold = save.can_hold;
+        engine.lock_delay.restore(save.lock_delay);
+        engine.scoring = Scoring::resume(save.points, save.combo, save.back_to_back);
+        engine.lines = save.lines;
+        engine.elapsed = save.elapsed;
+        engine.fall = save.fall;
+        engine.soft_drop = save.soft_drop;
+        engine.inputs = save.inputs;
+        Ok(engine)
+    }
+
+    /// Queues `lines` rows of garbage sent by an opponent. They rise under the stack when
+    /// the next block locks without clearing a line.
+    pub fn receive_garbage(&mut self, lines: usize) {
+        self.garbage.receive(lines);
+    }
+
+    /// Rows of garbage waiting to rise.
+    pub fn garbage(&self) -> usize {
+        self.garbage.rows()
+    }
+
+    /// Sets the mode of the next game.
+    pub fn set_mode(&mut self, mode: GameModeKind) {
+        self.config.mode = mode;
+    }
+
+    pub fn is_gameover(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.outcome
+    }
+
+    pub fn toggle_pause(&mut self) -> bool {
+        if !self.is_gameover() {
+            self.is_paused = !self.is_paused;
+        }
+        self.is_paused
+    }
+
+    /// Advances the clock by `dt` milliseconds. Gravity and the lock delay are
+    /// stepped one millisecond at a time, so the outcome doesn't depend on how
+    /// the elapsed time is split between calls.
+    pub fn tick(&mut self, dt: u64) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        for _ in 0..dt {
+            if self.is_paused || self.is_gameover() {
+                break;
+            }
+            self.elapsed += 1;
+            self.apply_gravity(1);
+            if self.grid.is_grounded() && self.lock_delay.tick(1) {
+                self.lock_block(&mut events);
+            }
+            self.check_complete(&mut events);
+        }
+        events
+    }
+
+    /// Gravity of the current level in G.
+    pub fn gravity(&self) -> f64 {
+        self.config.gravity_curve.gravity(self.level())
+    }
+
+    /// Cells per millisecond the block currently falls.
+    fn fall_speed(&self) -> f64 {
+        let gravity = if self.soft_drop {
+            self.gravity() * self.config.soft_drop_factor
+        } else {
+            self.gravity()
+        };
+        gravity * 60.0 / 1000.0
+    }
+
+    fn apply_gravity(&mut self, dt: u64) {
+        if self.gravity() >= MAX_GRAVITY {
+            self.sonic_drop();
+            return;
+        }
+        self.fall += self.fall_speed() * dt as f64;
+        while self.fall >= 1.0 {
+            if !self.grid.step_down() {
+                break;
+            }
+            self.lock_delay.on_step_down(self.grid.block.pos.1);
+            if self.soft_drop {
+                self.scoring.soft_drop(1);
+            }
+            self.fall -= 1.0;
+        }
+        if self.grid.is_grounded() {
+            self.fall = 0.0;
+        }
+    }
+
+    /// Drops the block onto the stack without locking it.
+    fn sonic_drop(&mut self) {
+        if self.grid.hard_drop() > 0 {
+            self.lock_delay.on_step_down(self.grid.block.pos.1);
+        }
+        self.fall = 0.0;
+    }
+
+    pub fn handle_input(&mut self, input: Input) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.is_paused || self.is_gameover() {
+            return events;
+        }
+        self.inputs.push((self.elapsed, input));
+        match input {
+            Input::SoftDrop => {
+                if self.grid.is_grounded() {
+                    self.lock_block(&mut events);
+                } else {
+                    self.soft_drop = true;
+                }
+            }
+            Input::SoftDropRelease => self.soft_drop = false,
+            Input::HardDrop => {
+                let rows = self.grid.hard_drop();
+                self.scoring.hard_drop(rows);
+                self.lock_block(&mut events);
+            }
+            Input::Hold => self.hold_block(&mut events),
+            Input::SonicDrop => self.sonic_drop(),
+            _ => {
+                let grounded = self.grid.is_grounded();
+                if self.move_block(input) {
+                    // Only moves on the stack use up resets; a piece in the air moves freely.
+                    if grounded || self.grid.is_grounded() {
+                        self.lock_delay.on_move();
+                    }
+                    if self.gravity() >= MAX_GRAVITY {
+                        self.sonic_drop();
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            width: self.grid.width,
+            height: self.grid.height - self.grid.buffer,
+            buffer: self.grid.buffer,
+            cells: self.grid.cells().to_vec(),
+            block: self.grid.block.clone(),
+            hint: self.grid.hint(),
+            hint_color: self.grid.hint_color,
+            queue: self.queue.preview(),
+            hold: self.hold.clone(),
+            can_hold: self.can_hold,
+            hold_enabled: self.config.hold,
+            lock_progress: if self.grid.is_grounded() { self.lock_delay.progress() } else { 0.0 },
+            points: self.scoring.points(),
+            lines: self.lines,
+            level: self.level(),
+            combo: self.scoring.combo(),
+            back_to_back: self.scoring.is_back_to_back(),
+            action: self.action.filter(|(_, at)| self.elapsed < at + ACTION_TIME).map(|(award, _)| award),
+            mode: self.config.mode,
+            hud: self.mode.hud(&self.progress()),
+            elapsed: self.elapsed,
+            clock: self.mode.clock(&self.progress()),
+            seed: self.seed,
+            is_paused: self.is_paused,
+            gameover: self.is_gameover(),
+            outcome: self.outcome,
+            garbage: self.garbage.rows(),
+        }
+    }
+
+    fn move_block(&mut self, input: Input) -> bool {
+        match input {
+            Input::Left => self.grid.handle_lr(LR::Left, false),
+            Input::Right => self.grid.handle_lr(LR::Right, false),
+            Input::LeftMost => self.grid.handle_lr(LR::Left, true),
+            Input::RightMost => self.grid.handle_lr(LR::Right, true),
+            Input::RotateClockwise => self.grid.rotate(true),
+            Input::RotateCounterClockwise => self.grid.rotate(false),
+            Input::FlipTurn => self.grid.flip_turn(),
+            Input::SoftDrop | Input::SoftDropRelease | Input::HardDrop | Input::SonicDrop | Input::Hold => false,
+        }
+    }
+
+    /// Swaps the active piece with the held one, at most once per piece.
+    fn hold_block(&mut self, events: &mut Vec<GameEvent>) {
+        if !self.config.hold || !self.can_hold {
+            return;
+        }
+        let current = Block::new(self.grid.block.block.shape());
+        let next = match self.hold.take() {
+            Some(block) => block,
+            None => self.queue.pop_and_spawn_new_block(&mut self.rng),
+        };
+        self.hold = Some(current);
+        self.spawn(next, events);
+        self.can_hold = false;
+    }
+
+    fn lock_block(&mut self, events: &mut Vec<GameEvent>) {
+        let level = self.level();
+        let spin = tspin::detect(&self.grid);
+        let cells = self.grid.block.cells();
+        let cells_above = cells.iter().filter(|(_, y)| *y < self.grid.buffer as i32).count();
+        let lines = self.grid.merge_block();
+        let award = self.scoring.lock(lines, spin, level, lines > 0 && self.grid.is_empty());
+        self.lines += lines;
+        events.push(GameEvent::Locked { lines });
+        if award.points > 0 {
+            self.action = Some((award, self.elapsed));
+            events.push(GameEvent::Scored(award));
+            if self.config.mode == GameModeKind::Versus {
+                let sent = self.garbage.cancel(self.config.attack.attack(&award));
+                if sent > 0 {
+                    events.push(GameEvent::Attack(sent));
+                }
+            }
+        }
+        self.check_complete(events);
+        if self.is_gameover() {
+            return;
+        }
+        if let Some(top_out) = self.top_out_rules().on_lock(cells_above, cells.len(), lines) {
+            self.finish(Outcome::ToppedOut(top_out), events);
+            return;
+        }
+        if lines == 0 && self.garbage.rows() > 0 {
+            let holes = self.garbage.rise(self.config.garbage, self.grid.width, &mut self.garbage_rng);
+            if self.grid.push_garbage(&holes) && self.top_out_rules().block_out {
+                self.finish(Outcome::ToppedOut(TopOut::GarbageOut), events);
+                return;
+            }
+        }
+        let block = self.queue.pop_and_spawn_new_block(&mut self.rng);
+        self.spawn(block, events);
+        self.can_hold = true;
+    }
+
+    fn spawn(&mut self, block: Block, events: &mut Vec<GameEvent>) {
+        self.grid.insert(block);
+        self.lock_delay.renew(self.grid.block.pos.1);
+        self.fall = 0.0;
+        self.soft_drop = false;
+        if !self.grid.fits(&self.grid.block) {
+            if self.top_out_rules().block_out {
+                self.finish(Outcome::ToppedOut(TopOut::BlockOut), events);
+            } else {
+                let block = self.grid.block.block.clone();
+                self.grid.renew(block);
+                self.lock_delay.renew(self.grid.block.pos.1);
+            }
+        }
+    }
+
+    fn top_out_rules(&self) -> TopOutRules {
+        self.config.top_out.unwrap_or_else(|| self.mode.top_out_rules())
+    }
+
+    fn progress(&self) -> Progress {
+        Progress {
+            points: self.scoring.points(),
+            lines: self.lines,
+            level: self.level(),
+            elapsed: self.elapsed,
+        }
+    }
+
+    fn check_complete(&mut self, events: &mut Vec<GameEvent>) {
+        if !self.is_gameover() && self.mode.is_complete(&self.progress()) {
+            self.finish(Outcome::Complete, events);
+        }
+    }
+
+    fn finish(&mut self, outcome: Outcome, events: &mut Vec<GameEvent>) {
+        self.outcome = Some(outcome);
+        events.push(GameEvent::GameOver);
+    }
+}
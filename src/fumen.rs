@@ -0,0 +1,451 @@
+//! Fumen v115, the field encoding community tools use to share setups: one page per
+//! field, each with an optional piece and comment, packed into base64 characters.
+
+use crate::block::{ BColor, Block, BlockWithPos, Rotation, Shape };
+use crate::pos::Pos;
+
+/// Columns of a fumen field.
+pub const WIDTH: usize = 10;
+/// Rows of a fumen field above the floor. One more row of garbage waits below it.
+pub const HEIGHT: usize = 23;
+const CELLS: usize = WIDTH * (HEIGHT + 1);
+
+const PREFIX: &str = "115@";
+const TABLE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// Comments are escaped to printable ASCII and packed four characters to five digits.
+const COMMENT_BASE: u32 = 96;
+const MAX_COMMENT: usize = 4095;
+/// Digits of a repeat count: up to 63 further pages keep the field unchanged.
+const MAX_REPEAT: u32 = 63;
+
+/// Fumen numbers its pieces 1 to 7, then 8 for gray cells.
+const SHAPES: [Shape; 7] = [Shape::I, Shape::L, Shape::O, Shape::Z, Shape::T, Shape::J, Shape::S];
+/// Fumen numbers its rotations from the upside down one, then clockwise.
+const ROTATIONS: [Rotation; 4] = [Rotation::R180, Rotation::R90, Rotation::R0, Rotation::R270];
+
+/// A piece on a page, placed like the active block on the board with rows counted from
+/// the top of the field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Piece {
+    pub shape: Shape,
+    pub rotation: Rotation,
+    pub pos: Pos,
+}
+
+impl Piece {
+    pub fn block(&self) -> BlockWithPos {
+        BlockWithPos::from(Block::with_rotation(self.shape, self.rotation), self.pos)
+    }
+
+    fn cells(&self) -> Vec<Pos> {
+        let mut cells = self.block().cells();
+        cells.sort();
+        cells
+    }
+}
+
+/// One page of a fumen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Page {
+    /// Cells row by row from the top of the field, then the garbage row below the floor.
+    pub field: Vec<Option<BColor>>,
+    pub piece: Option<Piece>,
+    pub comment: String,
+    /// Whether the piece is put on the field, and full rows cleared, for the next page.
+    pub lock: bool,
+}
+
+impl Default for Page {
+    fn default() -> Self {
+        Self {
+            field: vec![None; CELLS],
+            piece: None,
+            comment: String::new(),
+            lock: true,
+        }
+    }
+}
+
+impl Page {
+    /// The field bottom aligned on a board `width` cells wide with `rows` rows in all, row
+    /// by row from the top, and the piece moved along. Whatever doesn't fit is cut off.
+    pub fn to_board(&self, width: usize, rows: usize) -> (Vec<Option<BColor>>, Option<BlockWithPos>) {
+        let shift = rows as i32 - HEIGHT as i32;
+        let mut cells = vec![None; width * rows];
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH.min(width) {
+                let row = y as i32 + shift;
+                if row >= 0 {
+                    cells[row as usize * width + x] = self.field[y * WIDTH + x];
+                }
+            }
+        }
+        let block = self.piece.map(|piece| {
+            let mut block = piece.block();
+            block.pos.1 += shift;
+            block
+        });
+        (cells, block)
+    }
+
+    /// A page of the bottom rows of a board `width` cells wide, with its active block
+    /// unless that is above the field.
+    pub fn from_board(cells: &[Option<BColor>], width: usize, block: Option<&BlockWithPos>) -> Result<Page, String> {
+        if width != WIDTH {
+            return Err(format!("Fumen fields are {} cells wide, not {}", WIDTH, width));
+        }
+        let rows = cells.len() / width;
+        let shift = rows as i32 - HEIGHT as i32;
+        let mut page = Page::default();
+        for (i, cell) in cells.iter().enumerate() {
+            let row = (i / width) as i32 - shift;
+            match cell {
+                Some(_) if row < 0 => return Err(format!("The stack is higher than the {} rows of a fumen field", HEIGHT)),
+                Some(_) => page.field[row as usize * WIDTH + i % width] = *cell,
+                None => {}
+            }
+        }
+        page.piece = block
+            .map(|block| Piece {
+                shape: block.block.shape(),
+                rotation: block.block.rotation(),
+                pos: (block.pos.0, block.pos.1 - shift),
+            })
+            .filter(|piece| on_field(&piece.cells()));
+        Ok(page)
+    }
+}
+
+fn number(cell: Option<BColor>) -> u32 {
+    match cell {
+        None => 0,
+        Some(color) => SHAPES.iter().position(|shape| shape.to_color() == color).map_or(8, |i| i as u32 + 1),
+    }
+}
+
+fn color(number: u32) -> Option<BColor> {
+    match number {
+        0 => None,
+        8 => Some(BColor::GARBAGE),
+        n => Some(SHAPES[n as usize - 1].to_color()),
+    }
+}
+
+/// Cells of a piece whose center fumen puts at `x`, `y` counted up from the floor, as
+/// rows from the top of the field.
+fn fumen_cells(shape: Shape, rotation: Rotation, x: i32, y: i32) -> Vec<Pos> {
+    let cells: [Pos; 4] = match shape {
+        Shape::I => [(0, 0), (-1, 0), (1, 0), (2, 0)],
+        Shape::T => [(0, 0), (-1, 0), (1, 0), (0, 1)],
+        Shape::O => [(0, 0), (1, 0), (0, 1), (1, 1)],
+        Shape::L => [(0, 0), (-1, 0), (1, 0), (1, 1)],
+        Shape::J => [(0, 0), (-1, 0), (1, 0), (-1, 1)],
+        Shape::S => [(0, 0), (-1, 0), (0, 1), (1, 1)],
+        Shape::Z => [(0, 0), (1, 0), (0, 1), (-1, 1)],
+    };
+    let mut cells: Vec<Pos> = cells
+        .iter()
+        .map(|&(dx, dy)| match rotation {
+            Rotation::R0 => (dx, dy),
+            Rotation::R90 => (dy, -dx),
+            Rotation::R180 => (-dx, -dy),
+            Rotation::R270 => (-dy, dx),
+        })
+        .map(|(dx, dy)| (x + dx, HEIGHT as i32 - 1 - (y + dy)))
+        .collect();
+    cells.sort();
+    cells
+}
+
+/// The corrections fumen applies between the stored location of a piece and its center.
+fn center_offset(shape: Shape, rotation: Rotation) -> Pos {
+    match (shape, rotation) {
+        (Shape::O, Rotation::R270) => (1, -1),
+        (Shape::O, Rotation::R180) => (1, 0),
+        (Shape::O, Rotation::R0) => (0, -1),
+        (Shape::I, Rotation::R180) => (1, 0),
+        (Shape::I, Rotation::R270) => (0, -1),
+        (Shape::S, Rotation::R0) => (0, -1),
+        (Shape::S, Rotation::R90) => (-1, 0),
+        (Shape::Z, Rotation::R0) => (0, -1),
+        (Shape::Z, Rotation::R270) => (1, 0),
+        _ => (0, 0),
+    }
+}
+
+fn on_field(cells: &[Pos]) -> bool {
+    cells.iter().all(|&(x, y)| x >= 0 && x < WIDTH as i32 && y >= 0 && y < HEIGHT as i32)
+}
+
+fn decode_piece(shape: Shape, rotation: Rotation, location: u32) -> Option<Piece> {
+    let (dx, dy) = center_offset(shape, rotation);
+    let x = (location as usize % WIDTH) as i32 + dx;
+    let y = HEIGHT as i32 - 1 - (location as usize / WIDTH) as i32 + dy;
+    let cells = fumen_cells(shape, rotation, x, y);
+    if !on_field(&cells) {
+        return None;
+    }
+    let origin = Piece { shape, rotation, pos: (0, 0) }.cells();
+    Some(Piece { shape, rotation, pos: (cells[0].0 - origin[0].0, cells[0].1 - origin[0].1) })
+}
+
+fn encode_piece(piece: &Piece) -> Option<u32> {
+    let cells = piece.cells();
+    if !on_field(&cells) {
+        return None;
+    }
+    let origin = fumen_cells(piece.shape, piece.rotation, 0, 0);
+    let x = cells[0].0 - origin[0].0;
+    let y = origin[0].1 - cells[0].1;
+    let (dx, dy) = center_offset(piece.shape, piece.rotation);
+    Some(((HEIGHT as i32 - 1 - (y - dy)) * WIDTH as i32 + x - dx) as u32)
+}
+
+/// The field of the next page: the piece locked in and full rows cleared.
+fn next_field(field: &[u32], piece: Option<&Piece>) -> Vec<u32> {
+    let mut field = field.to_vec();
+    if let Some(piece) = piece {
+        for (x, y) in piece.cells() {
+            field[y as usize * WIDTH + x as usize] = SHAPES.iter().position(|shape| *shape == piece.shape).unwrap() as u32 + 1;
+        }
+    }
+    let mut rows: Vec<&[u32]> = field[..HEIGHT * WIDTH].chunks(WIDTH).filter(|row| row.contains(&0)).collect();
+    let empty = [0; WIDTH];
+    while rows.len() < HEIGHT {
+        rows.insert(0, &empty);
+    }
+    let mut next: Vec<u32> = rows.concat();
+    next.extend_from_slice(&field[HEIGHT * WIDTH..]);
+    next
+}
+
+/// Escapes like JavaScript's `escape`, which fumen applies to comments.
+fn escape(text: &str) -> String {
+    text.encode_utf16()
+        .map(|unit| match char::from_u32(unit as u32) {
+            Some(c) if c.is_ascii_alphanumeric() || "@*_+-./".contains(c) => c.to_string(),
+            _ if unit < 256 => format!("%{:02X}", unit),
+            _ => format!("%u{:04X}", unit),
+        })
+        .collect()
+}
+
+fn unescape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let hex = |from: usize, len: usize| -> Option<u16> {
+        let digits: String = chars.get(from..from + len)?.iter().collect();
+        u16::from_str_radix(&digits, 16).ok()
+    };
+    let mut units = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '%' {
+            if chars.get(i + 1) == Some(&'u') {
+                if let Some(unit) = hex(i + 2, 4) {
+                    units.push(unit);
+                    i += 6;
+                    continue;
+                }
+            } else if let Some(unit) = hex(i + 1, 2) {
+                units.push(unit);
+                i += 3;
+                continue;
+            }
+        }
+        let mut buffer = [0; 2];
+        units.extend_from_slice(chars[i].encode_utf16(&mut buffer));
+        i += 1;
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Base64 digits read in little endian groups.
+struct Reader {
+    digits: Vec<u32>,
+    next: usize,
+}
+
+impl Reader {
+    fn is_empty(&self) -> bool {
+        self.next >= self.digits.len()
+    }
+
+    fn poll(&mut self, count: usize) -> Result<u32, String> {
+        let digits = self.digits.get(self.next..self.next + count).ok_or("The fumen ends too early")?;
+        self.next += count;
+        Ok(digits.iter().rev().fold(0, |value, digit| value * 64 + digit))
+    }
+}
+
+/// Takes the lowest digit of `value` in `base` off it.
+fn take(value: &mut u32, base: u32) -> u32 {
+    let digit = *value % base;
+    *value /= base;
+    digit
+}
+
+fn push(digits: &mut Vec<u32>, mut value: u32, count: usize) {
+    for _ in 0..count {
+        digits.push(value % 64);
+        value /= 64;
+    }
+}
+
+/// Reads every page of a fumen such as `v115@vhAAgH`, with or without the address of a
+/// fumen viewer in front.
+pub fn decode(text: &str) -> Result<Vec<Page>, String> {
+    let text = text.trim();
+    let data = match text.find(PREFIX) {
+        Some(at) if text[..at].ends_with(['v', 'm', 'd']) => &text[at + PREFIX.len()..],
+        _ => return Err("Not a v115 fumen".to_string()),
+    };
+    let digits = data
+        .chars()
+        .filter(|c| *c != '?')
+        .map(|c| TABLE.find(c).map(|digit| digit as u32).ok_or_else(|| format!("Bad fumen character {}", c)))
+        .collect::<Result<_, _>>()?;
+    let mut reader = Reader { digits, next: 0 };
+    let mut pages = Vec::new();
+    let mut field = vec![0; CELLS];
+    let mut repeat = 0;
+    let mut comment = String::new();
+    while !reader.is_empty() {
+        if repeat > 0 {
+            repeat -= 1;
+        } else {
+            let mut index = 0;
+            while index < CELLS {
+                let value = reader.poll(2)?;
+                let (diff, count) = ((value as usize / CELLS) as i32 - 8, value as usize % CELLS + 1);
+                if diff == 0 && count == CELLS {
+                    repeat = reader.poll(1)?;
+                }
+                let cells = field.get_mut(index..index + count).ok_or("The fumen field overflows")?;
+                for cell in cells {
+                    let number = *cell as i32 + diff;
+                    if !(0..=8).contains(&number) {
+                        return Err("Bad fumen field".to_string());
+                    }
+                    *cell = number as u32;
+                }
+                index += count;
+            }
+        }
+        let mut action = reader.poll(3)?;
+        let kind = take(&mut action, 8);
+        let rotation = ROTATIONS[take(&mut action, 4) as usize];
+        let location = take(&mut action, CELLS as u32);
+        // Rising garbage, mirroring and guideline colors aren't supported.
+        take(&mut action, 8);
+        let has_comment = take(&mut action, 2) == 1;
+        let lock = take(&mut action, 2) == 0;
+        if has_comment {
+            let len = reader.poll(2)? as usize;
+            let mut escaped = String::new();
+            for _ in 0..len.div_ceil(4) {
+                let mut value = reader.poll(5)?;
+                for _ in 0..4 {
+                    if escaped.len() < len {
+                        escaped.push(char::from_u32(32 + value % COMMENT_BASE).filter(|c| *c <= '~').ok_or("Bad fumen comment")?);
+                    }
+                    value /= COMMENT_BASE;
+                }
+            }
+            comment = unescape(&escaped);
+        }
+        let piece = match kind {
+            0 => None,
+            kind => Some(decode_piece(SHAPES[kind as usize - 1], rotation, location).ok_or_else(|| format!("The piece of page {} is off the field", pages.len() + 1))?),
+        };
+        pages.push(Page {
+            field: field.iter().map(|n| color(*n)).collect(),
+            piece,
+            comment: comment.clone(),
+            lock,
+        });
+        if lock {
+            field = next_field(&field, piece.as_ref());
+        }
+    }
+    if pages.is_empty() {
+        return Err("The fumen has no pages".to_string());
+    }
+    Ok(pages)
+}
+
+/// Writes pages as a v115 fumen. Each field follows from the page before, so a field
+/// left as it was costs a single character.
+pub fn encode(pages: &[Page]) -> Result<String, String> {
+    let mut digits = Vec::new();
+    let mut field = vec![0; CELLS];
+    let mut repeat_at: Option<usize> = None;
+    let mut comment = "";
+    for (i, page) in pages.iter().enumerate() {
+        if page.field.len() != CELLS {
+            return Err(format!("Page {} doesn't have {} cells", i + 1, CELLS));
+        }
+        let current: Vec<u32> = page.field.iter().map(|cell| number(*cell)).collect();
+        let mut runs = Vec::new();
+        let mut run = (current[0] + 8 - field[0], 0);
+        for (now, before) in current.iter().zip(&field).skip(1) {
+            let diff = now + 8 - before;
+            if diff == run.0 {
+                run.1 += 1;
+            } else {
+                runs.push(run);
+                run = (diff, 0);
+            }
+        }
+        runs.push(run);
+        let unchanged = runs == [(8, CELLS as u32 - 1)];
+        match repeat_at {
+            Some(at) if unchanged && digits[at] < MAX_REPEAT => digits[at] += 1,
+            _ => {
+                for (diff, count) in runs {
+                    push(&mut digits, diff * CELLS as u32 + count, 2);
+                }
+                repeat_at = None;
+                if unchanged {
+                    digits.push(0);
+                    repeat_at = Some(digits.len() - 1);
+                }
+            }
+        }
+
+        let (kind, rotation, location) = match &page.piece {
+            Some(piece) => (
+                SHAPES.iter().position(|shape| *shape == piece.shape).unwrap() as u32 + 1,
+                ROTATIONS.iter().position(|rotation| *rotation == piece.rotation).unwrap() as u32,
+                encode_piece(piece).ok_or_else(|| format!("The piece of page {} is off the field", i + 1))?,
+            ),
+            None => (0, 0, 0),
+        };
+        let has_comment = page.comment != comment;
+        let flags = [0, 0, (i == 0) as u32, has_comment as u32, !page.lock as u32];
+        let action = flags.iter().rev().fold(0, |value, flag| value * 2 + flag);
+        push(&mut digits, ((action * CELLS as u32 + location) * 4 + rotation) * 8 + kind, 3);
+        if has_comment {
+            let escaped: Vec<u32> = escape(&page.comment).bytes().take(MAX_COMMENT).map(|byte| byte as u32 - 32).collect();
+            push(&mut digits, escaped.len() as u32, 2);
+            for group in escaped.chunks(4) {
+                push(&mut digits, group.iter().rev().fold(0, |value, c| value * COMMENT_BASE + c), 5);
+            }
+            comment = &page.comment;
+        }
+        if page.lock {
+            field = next_field(&current, page.piece.as_ref());
+        } else {
+            field = current;
+        }
+    }
+    let data: String = digits.iter().map(|digit| TABLE.as_bytes()[*digit as usize] as char).collect();
+    // Fumen breaks long data with a `?` after the first 42 characters and then every 47.
+    let mut chunks = vec![&data[..data.len().min(42)]];
+    let mut rest = &data[data.len().min(42)..];
+    while !rest.is_empty() {
+        let (chunk, tail) = rest.split_at(rest.len().min(47));
+        chunks.push(chunk);
+        rest = tail;
+    }
+    Ok(format!("v{}{}", PREFIX, chunks.join("?")))
+}
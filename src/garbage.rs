@@ -0,0 +1,257 @@
+//! Garbage: rows with one hole that an opponent's clears push up under the stack.
+
+use crate::scoring::Award;
+use crate::tspin::TSpin;
+
+use rand::{ rngs::StdRng, Rng };
+use std::collections::VecDeque;
+use std::convert::TryInto;
+
+/// Where the holes of rising garbage go.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HolePlacement {
+    /// All rows of an attack share one hole.
+    #[default]
+    Clean,
+    /// Every row has its hole somewhere else.
+    Messy,
+    /// Each row moves its hole away from the one below with this probability, from 0 to 1.
+    Change(f64),
+}
+
+impl HolePlacement {
+    pub fn name(&self) -> String {
+        match self {
+            HolePlacement::Clean => "clean".to_string(),
+            HolePlacement::Messy => "messy".to_string(),
+            HolePlacement::Change(chance) => format!("change-{}", percent(*chance)),
+        }
+    }
+
+    /// Parses `clean`, `messy` or `change-<percent>`, e.g. `change-30`.
+    pub fn from_name(name: &str) -> Option<HolePlacement> {
+        match name {
+            "clean" => Some(HolePlacement::Clean),
+            "messy" => Some(HolePlacement::Messy),
+            _ => {
+                let percent: f64 = name.strip_prefix("change-")?.parse().ok()?;
+                Some(HolePlacement::Change(percent / 100.0)).filter(|_| (0.0..=100.0).contains(&percent))
+            }
+        }
+    }
+
+    /// Hole columns for an attack of `rows`, the lowest row first, on a board `width`
+    /// wide. `last` is the hole of the row below, if any.
+    pub fn holes(&self, rows: usize, width: usize, last: Option<usize>, rng: &mut StdRng) -> Vec<usize> {
+        let mut holes = Vec::with_capacity(rows);
+        let mut hole = last;
+        for row in 0..rows {
+            let moves = match self {
+                HolePlacement::Clean => row == 0,
+                HolePlacement::Messy => true,
+                HolePlacement::Change(chance) => hole.is_none() || rng.gen_bool(chance.clamp(0.0, 1.0)),
+            };
+            if moves || hole.is_none() {
+                hole = Some(other_column(hole, width, rng));
+            }
+            holes.extend(hole);
+        }
+        holes
+    }
+}
+
+/// `chance` in percent, in as few digits as read back to the same chance: 0.3 is `30`,
+/// not the `30.000000000000004` that multiplying gives.
+fn percent(chance: f64) -> String {
+    (0..17)
+        .map(|digits| format!("{:.*}", digits, chance * 100.0))
+        .find(|text| text.parse::<f64>().ok().map(|percent| percent / 100.0) == Some(chance))
+        .unwrap_or_else(|| (chance * 100.0).to_string())
+}
+
+/// A random column other than `column`, when the board has room for one.
+fn other_column(column: Option<usize>, width: usize, rng: &mut StdRng) -> usize {
+    match column {
+        Some(column) if width > 1 => {
+            let other = rng.gen_range(0..width - 1);
+            if other >= column { other + 1 } else { other }
+        }
+        _ => rng.gen_range(0..width.max(1)),
+    }
+}
+
+/// Rows of garbage each kind of clear sends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttackTable {
+    /// For clearing 0 to 4 lines.
+    pub lines: [usize; 5],
+    /// For T-spins clearing 0 to 3 lines.
+    pub t_spin: [usize; 4],
+    /// For T-spin minis clearing 0 to 2 lines.
+    pub mini: [usize; 3],
+    /// Added to a clear that continues a back-to-back chain.
+    pub back_to_back: usize,
+    /// Added to the clear that follows `n` clears in a row, the last entry for longer combos.
+    pub combo: Vec<usize>,
+    /// Added for clearing the whole board.
+    pub perfect_clear: usize,
+}
+
+impl Default for AttackTable {
+    /// The guideline table.
+    fn default() -> Self {
+        Self {
+            lines: [0, 0, 1, 2, 4],
+            t_spin: [0, 2, 4, 6],
+            mini: [0, 0, 1],
+            back_to_back: 1,
+            combo: vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 4, 5],
+            perfect_clear: 10,
+        }
+    }
+}
+
+impl AttackTable {
+    /// Only the lines count, spins or not: a double sends one row, a triple two and a
+    /// tetris four.
+    pub fn classic() -> Self {
+        Self {
+            lines: [0, 0, 1, 2, 4],
+            t_spin: [0, 0, 1, 2],
+            mini: [0, 0, 1],
+            back_to_back: 0,
+            combo: vec![0],
+            perfect_clear: 0,
+        }
+    }
+
+    /// Rows of garbage sent for `award`.
+    pub fn attack(&self, award: &Award) -> usize {
+        let mut rows = match award.spin {
+            TSpin::Full => self.t_spin[award.lines.min(3)],
+            TSpin::Mini => self.mini[award.lines.min(2)],
+            TSpin::None => self.lines[award.lines.min(4)],
+        };
+        if award.lines == 0 {
+            return rows;
+        }
+        if award.back_to_back {
+            rows += self.back_to_back;
+        }
+        rows += self.combo.get(award.combo).or_else(|| self.combo.last()).copied().unwrap_or(0);
+        if award.perfect_clear {
+            rows += self.perfect_clear;
+        }
+        rows
+    }
+
+    /// `guideline`, `classic`, or every entry: the tables for lines, T-spins and minis,
+    /// the back-to-back bonus, the combo table and the perfect clear bonus, separated by
+    /// `/`, with `.` between the entries of a table, e.g. `0.0.1.2.4/0.2.4.6/0.0.1/1/0/10`.
+    pub fn name(&self) -> String {
+        if *self == Self::default() {
+            return "guideline".to_string();
+        }
+        if *self == Self::classic() {
+            return "classic".to_string();
+        }
+        let join = |rows: &[usize]| rows.iter().map(usize::to_string).collect::<Vec<_>>().join(".");
+        [
+            join(&self.lines),
+            join(&self.t_spin),
+            join(&self.mini),
+            self.back_to_back.to_string(),
+            join(&self.combo),
+            self.perfect_clear.to_string(),
+        ]
+        .join("/")
+    }
+
+    pub fn from_name(name: &str) -> Option<AttackTable> {
+        match name {
+            "guideline" => return Some(Self::default()),
+            "classic" => return Some(Self::classic()),
+            _ => {}
+        }
+        let tables: Vec<Vec<usize>> = name
+            .split('/')
+            .map(|table| table.split('.').map(|rows| rows.parse().ok()).collect::<Option<Vec<usize>>>())
+            .collect::<Option<_>>()?;
+        let single = |table: &Vec<usize>| -> Option<usize> {
+            let [rows]: [usize; 1] = table.as_slice().try_into().ok()?;
+            Some(rows)
+        };
+        match &tables[..] {
+            [lines, t_spin, mini, back_to_back, combo, perfect_clear] => Some(AttackTable {
+                lines: lines.as_slice().try_into().ok()?,
+                t_spin: t_spin.as_slice().try_into().ok()?,
+                mini: mini.as_slice().try_into().ok()?,
+                back_to_back: single(back_to_back)?,
+                combo: combo.clone(),
+                perfect_clear: single(perfect_clear)?,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Attacks received and not risen yet, the oldest first.
+#[derive(Clone, Debug, Default)]
+pub struct GarbageQueue {
+    attacks: VecDeque<usize>,
+    /// Hole of the highest garbage row that rose, where the next rows continue from.
+    hole: Option<usize>,
+}
+
+impl GarbageQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn receive(&mut self, rows: usize) {
+        if rows > 0 {
+            self.attacks.push_back(rows);
+        }
+    }
+
+    /// Rows waiting in all.
+    pub fn rows(&self) -> usize {
+        self.attacks.iter().sum()
+    }
+
+    /// Cancels up to `rows` waiting rows, the oldest first. Returns what is left of `rows`
+    /// to send on.
+    pub fn cancel(&mut self, mut rows: usize) -> usize {
+        while rows > 0 {
+            let oldest = match self.attacks.front_mut() {
+                Some(oldest) => oldest,
+                None => break,
+            };
+            let cancelled = rows.min(*oldest);
+            *oldest -= cancelled;
+            rows -= cancelled;
+            if *oldest == 0 {
+                self.attacks.pop_front();
+            }
+        }
+        rows
+    }
+
+    /// Takes every waiting row out of the queue as the holes of the rows to push, from the
+    /// top down as `ColorGrid::push_garbage` takes them.
+    pub fn rise(&mut self, placement: HolePlacement, width: usize, rng: &mut StdRng) -> Vec<usize> {
+        let mut holes = Vec::new();
+        while let Some(rows) = self.attacks.pop_front() {
+            let attack = placement.holes(rows, width, self.hole, rng);
+            self.hole = attack.last().copied().or(self.hole);
+            holes.extend(attack);
+        }
+        holes.reverse();
+        holes
+    }
+
+    pub fn clear(&mut self) {
+        self.attacks.clear();
+        self.hole = None;
+    }
+}
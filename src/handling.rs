@@ -0,0 +1,95 @@
+use crate::engine::{ Engine, Input };
+
+/// Turns key presses and releases into engine inputs. A held direction moves once,
+/// then after `das` milliseconds repeats every `arr` milliseconds; an `arr` of 0
+/// moves the block straight to the wall. Other held keys act once per press.
+pub struct Handling {
+    das: u64,
+    arr: u64,
+    held: Vec<Input>,
+    /// The sideways input repeating, the most recently pressed of those held.
+    shift: Option<Input>,
+    charge: u64,
+}
+
+impl Handling {
+    pub fn new(das: u64, arr: u64) -> Self {
+        Self {
+            das,
+            arr,
+            held: Vec::new(),
+            shift: None,
+            charge: 0,
+        }
+    }
+
+    pub fn press(&mut self, input: Input) -> Vec<Input> {
+        if self.held.contains(&input) {
+            // Key repeat from the operating system.
+            return Vec::new();
+        }
+        self.held.push(input);
+        if input == Input::Left || input == Input::Right {
+            self.shift = Some(input);
+            self.charge = 0;
+        }
+        vec![input]
+    }
+
+    pub fn release(&mut self, input: Input) -> Vec<Input> {
+        if !self.held.contains(&input) {
+            return Vec::new();
+        }
+        self.held.retain(|held| *held != input);
+        if self.shift == Some(input) {
+            self.shift = self.held.iter().rev().find(|held| **held == Input::Left || **held == Input::Right).copied();
+            self.charge = 0;
+        }
+        if input == Input::SoftDrop {
+            vec![Input::SoftDropRelease]
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn is_held(&self, input: Input) -> bool {
+        self.held.contains(&input)
+    }
+
+    /// Soft drops a new block while the key stays held, since the engine ends a soft drop
+    /// when a block spawns and key repeat from the operating system is ignored. Call it
+    /// after the inputs and ticks of `engine`.
+    pub fn keep_soft_drop(&self, engine: &Engine) -> Vec<Input> {
+        // On the stack a soft drop would lock the block at once.
+        if self.is_held(Input::SoftDrop) && !engine.is_soft_dropping() && !engine.is_grounded() {
+            vec![Input::SoftDrop]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Advances the held keys by `dt` milliseconds and returns the repeated moves.
+    pub fn tick(&mut self, dt: u64) -> Vec<Input> {
+        let shift = match self.shift {
+            Some(shift) => shift,
+            None => return Vec::new(),
+        };
+        let before = self.repeats(self.charge);
+        self.charge += dt;
+        if self.charge < self.das {
+            Vec::new()
+        } else if self.arr == 0 {
+            vec![if shift == Input::Left { Input::LeftMost } else { Input::RightMost }]
+        } else {
+            vec![shift; (self.repeats(self.charge) - before) as usize]
+        }
+    }
+
+    fn repeats(&self, charge: u64) -> u64 {
+        if charge < self.das || self.arr == 0 {
+            0
+        } else {
+            (charge - self.das) / self.arr + 1
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::block::{ Block, BColor };
+use crate::engine::Snapshot;
+use crate::queue::draw_block;
+use cursive:: {
+    Printer,
+    View,
+    theme::{Color, ColorStyle},
+};
+
+pub struct Hold {
+    block: Option<Block>,
+    can_hold: bool,
+    enabled: bool,
+}
+
+impl Default for Hold {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hold {
+    pub fn new() -> Self {
+        Self {
+            block: None,
+            can_hold: true,
+            enabled: true,
+        }
+    }
+
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.block = snapshot.hold.clone();
+        self.can_hold = snapshot.can_hold;
+        self.enabled = snapshot.hold_enabled;
+    }
+
+    fn draw_container(&self, printer: &Printer) {
+        let color_style = ColorStyle::new(Color::Rgb(255,255,255), Color::Rgb(183, 85, 224));
+        printer.with_color(color_style, |printer| {
+            printer.print((0, 0), "|   Hold   |");
+        });
+        for j in 1..5 {
+                printer.with_color(color_style, |printer| {
+                    printer.print((0, j), "|          |");
+                });
+        }
+        printer.with_color(color_style, |printer| {
+            printer.print((0, 5), "|__________|");
+        });
+    }
+}
+
+impl View for Hold {
+    fn draw(&self, printer: &Printer) {
+        if !self.enabled {
+            return;
+        }
+        self.draw_container(printer);
+        if let Some(block) = &self.block {
+            // A held piece can't be swapped back until the next one locks.
+            let color = if self.can_hold { block.color() } else { BColor::HINT };
+            draw_block(printer, block, color.to_cursive(), 3);
+        }
+    }
+
+    fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
+        if self.enabled {
+            cursive::Vec2::new(12, 6)
+        } else {
+            cursive::Vec2::new(0, 0)
+        }
+    }
+}
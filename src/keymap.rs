@@ -0,0 +1,369 @@
+use crate::engine::Input;
+use std::fmt;
+
+/// Something the player does with a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Left,
+    Right,
+    LeftMost,
+    RightMost,
+    RotateClockwise,
+    RotateCounterClockwise,
+    FlipTurn,
+    SoftDrop,
+    HardDrop,
+    Hold,
+    Pause,
+    NewGame,
+    Settings,
+    Replay,
+    Fumen,
+    Hint,
+    Autoplay,
+}
+
+impl Action {
+    /// Every action in the order the manual and the settings list them.
+    pub fn all() -> Vec<Action> {
+        vec![
+            Action::RotateClockwise,
+            Action::RotateCounterClockwise,
+            Action::FlipTurn,
+            Action::SoftDrop,
+            Action::Left,
+            Action::LeftMost,
+            Action::Right,
+            Action::RightMost,
+            Action::HardDrop,
+            Action::Hold,
+            Action::Pause,
+            Action::NewGame,
+            Action::Settings,
+            Action::Replay,
+            Action::Fumen,
+            Action::Hint,
+            Action::Autoplay,
+        ]
+    }
+
+    /// The engine input of the action, or `None` for actions handled outside the game.
+    pub fn input(&self) -> Option<Input> {
+        match self {
+            Action::Left => Some(Input::Left),
+            Action::Right => Some(Input::Right),
+            Action::LeftMost => Some(Input::LeftMost),
+            Action::RightMost => Some(Input::RightMost),
+            Action::RotateClockwise => Some(Input::RotateClockwise),
+            Action::RotateCounterClockwise => Some(Input::RotateCounterClockwise),
+            Action::FlipTurn => Some(Input::FlipTurn),
+            Action::SoftDrop => Some(Input::SoftDrop),
+            Action::HardDrop => Some(Input::HardDrop),
+            Action::Hold => Some(Input::Hold),
+            Action::Pause
+            | Action::NewGame
+            | Action::Settings
+            | Action::Replay
+            | Action::Fumen
+            | Action::Hint
+            | Action::Autoplay => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::LeftMost => "left_most",
+            Action::RightMost => "right_most",
+            Action::RotateClockwise => "rotate_clockwise",
+            Action::RotateCounterClockwise => "rotate_counterclockwise",
+            Action::FlipTurn => "flip_turn",
+            Action::SoftDrop => "soft_drop",
+            Action::HardDrop => "hard_drop",
+            Action::Hold => "hold",
+            Action::Pause => "pause",
+            Action::NewGame => "new_game",
+            Action::Settings => "settings",
+            Action::Replay => "replay",
+            Action::Fumen => "fumen",
+            Action::Hint => "hint",
+            Action::Autoplay => "autoplay",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::all().into_iter().find(|action| action.name() == name)
+    }
+
+    /// What the action does, as the manual puts it.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::LeftMost => "left most",
+            Action::RightMost => "right most",
+            Action::RotateClockwise => "rotate clockwise",
+            Action::RotateCounterClockwise => "rotate counterclockwise",
+            Action::FlipTurn => "flip turn",
+            Action::SoftDrop => "speed up",
+            Action::HardDrop => "hard drop",
+            Action::Hold => "hold",
+            Action::Pause => "stop and resume",
+            Action::NewGame => "new game",
+            Action::Settings => "key bindings",
+            Action::Replay => "watch last game",
+            Action::Fumen => "copy board as fumen",
+            Action::Hint => "show best place",
+            Action::Autoplay => "let the computer play",
+        }
+    }
+}
+
+/// A key that can be bound. Letters are kept in lower case so both cases act the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Enter,
+}
+
+impl Key {
+    pub fn char(c: char) -> Key {
+        Key::Char(c.to_lowercase().next().unwrap_or(c))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Key::Char(' ') => "space".to_string(),
+            Key::Char(c) => c.to_string(),
+            Key::Left => "left".to_string(),
+            Key::Right => "right".to_string(),
+            Key::Up => "up".to_string(),
+            Key::Down => "down".to_string(),
+            Key::Enter => "enter".to_string(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Key> {
+        match name {
+            "space" => Some(Key::Char(' ')),
+            "left" => Some(Key::Left),
+            "right" => Some(Key::Right),
+            "up" => Some(Key::Up),
+            "down" => Some(Key::Down),
+            "enter" => Some(Key::Enter),
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if c != ',' && c != '=' && !c.is_whitespace() => Some(Key::char(c)),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// How the manual shows the key.
+    pub fn label(&self) -> String {
+        match self {
+            Key::Left => "←".to_string(),
+            Key::Right => "→".to_string(),
+            Key::Up => "↑".to_string(),
+            Key::Down => "↓".to_string(),
+            _ => self.name(),
+        }
+    }
+
+    fn normalized(self) -> Key {
+        match self {
+            Key::Char(c) => Key::char(c),
+            key => key,
+        }
+    }
+}
+
+/// A key that is already bound to another action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub key: Key,
+    pub action: Action,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} is already bound to {}", self.key.name(), self.action.description())
+    }
+}
+
+/// Which keys trigger which action. Every action may have several keys, but a key
+/// belongs to one action at most.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keymap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Keymap::empty();
+        for (action, keys) in [
+            (Action::RotateClockwise, vec![Key::Up, Key::Char('e')]),
+            (Action::RotateCounterClockwise, vec![Key::Char('w')]),
+            (Action::FlipTurn, vec![Key::Char('s')]),
+            (Action::SoftDrop, vec![Key::Down]),
+            (Action::Left, vec![Key::Left]),
+            (Action::LeftMost, vec![Key::Char('a')]),
+            (Action::Right, vec![Key::Right]),
+            (Action::RightMost, vec![Key::Char('d')]),
+            (Action::HardDrop, vec![Key::Char(' ')]),
+            (Action::Hold, vec![Key::Char('c')]),
+            (Action::Pause, vec![Key::Char('m')]),
+            (Action::NewGame, vec![Key::Char('n')]),
+            (Action::Settings, vec![Key::Char('k')]),
+            (Action::Replay, vec![Key::Char('r')]),
+            (Action::Fumen, vec![Key::Char('f')]),
+            (Action::Hint, vec![Key::Char('h')]),
+            (Action::Autoplay, vec![Key::Char('o')]),
+        ] {
+            for key in keys {
+                keymap.rebind(action, key);
+            }
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// The default keys of one of two players at the same keyboard: the letters around
+    /// WASD for the first player, the arrow keys for the second. Either may pause with p.
+    pub fn versus(player: usize) -> Self {
+        let bindings = if player == 0 {
+            [
+                (Action::Left, Key::Char('a')),
+                (Action::Right, Key::Char('d')),
+                (Action::SoftDrop, Key::Char('s')),
+                (Action::HardDrop, Key::Char('w')),
+                (Action::RotateClockwise, Key::Char('e')),
+                (Action::RotateCounterClockwise, Key::Char('q')),
+                (Action::FlipTurn, Key::Char('x')),
+                (Action::Hold, Key::Char('c')),
+                (Action::Pause, Key::Char('p')),
+            ]
+        } else {
+            [
+                (Action::Left, Key::Left),
+                (Action::Right, Key::Right),
+                (Action::SoftDrop, Key::Down),
+                (Action::HardDrop, Key::Enter),
+                (Action::RotateClockwise, Key::Up),
+                (Action::RotateCounterClockwise, Key::Char('/')),
+                (Action::FlipTurn, Key::Char(';')),
+                (Action::Hold, Key::Char('.')),
+                (Action::Pause, Key::Char('p')),
+            ]
+        };
+        let mut keymap = Keymap::empty();
+        for (action, key) in bindings {
+            keymap.rebind(action, key);
+        }
+        keymap
+    }
+
+    /// A keymap without any keys bound.
+    pub fn empty() -> Self {
+        Self {
+            bindings: Action::all().into_iter().map(|action| (action, Vec::new())).collect(),
+        }
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        let key = key.normalized();
+        self.bindings.iter().find(|(_, keys)| keys.contains(&key)).map(|(action, _)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.bindings.iter().find(|(bound, _)| *bound == action).map_or(&[], |(_, keys)| keys)
+    }
+
+    /// Adds `key` to the keys of `action`, unless another action already has it.
+    pub fn bind(&mut self, action: Action, key: Key) -> Result<(), Conflict> {
+        let key = key.normalized();
+        match self.action(key) {
+            Some(bound) if bound == action => Ok(()),
+            Some(bound) => Err(Conflict { key, action: bound }),
+            None => {
+                self.keys_mut(action).push(key);
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds `key` to the keys of `action`, taking it from the action that had it.
+    /// Returns that action.
+    pub fn rebind(&mut self, action: Action, key: Key) -> Option<Action> {
+        let key = key.normalized();
+        let previous = self.action(key).filter(|bound| *bound != action);
+        if let Some(previous) = previous {
+            self.keys_mut(previous).retain(|bound| *bound != key);
+        }
+        self.bind(action, key).ok();
+        previous
+    }
+
+    /// Removes every key of `action`.
+    pub fn clear(&mut self, action: Action) {
+        self.keys_mut(action).clear();
+    }
+
+    /// The keys of `action` for the manual, e.g. `↑,e`, or `-` when it has none.
+    pub fn label(&self, action: Action) -> String {
+        let keys = self.keys(action);
+        if keys.is_empty() {
+            "-".to_string()
+        } else {
+            keys.iter().map(Key::label).collect::<Vec<_>>().join(",")
+        }
+    }
+
+    /// Writes the keymap as a profile, one `action = key, key` line per action.
+    pub fn to_profile(&self) -> String {
+        self.bindings
+            .iter()
+            .map(|(action, keys)| {
+                let keys: Vec<String> = keys.iter().map(Key::name).collect();
+                format!("{} = {}\n", action.name(), keys.join(", "))
+            })
+            .collect()
+    }
+
+    /// Reads a profile written by `to_profile`. Actions the profile leaves out keep
+    /// those of their default keys that are still free.
+    pub fn from_profile(profile: &str) -> Result<Keymap, String> {
+        let mut keymap = Keymap::empty();
+        let mut listed = Vec::new();
+        for line in profile.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut parts = line.splitn(2, '=');
+            let (name, keys) = (parts.next().unwrap_or("").trim(), parts.next().unwrap_or(""));
+            let action = Action::from_name(name).ok_or_else(|| format!("unknown action {}", name))?;
+            for name in keys.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+                let key = Key::from_name(name).ok_or_else(|| format!("unknown key {}", name))?;
+                keymap.bind(action, key).map_err(|conflict| conflict.to_string())?;
+            }
+            listed.push(action);
+        }
+        let defaults = Keymap::default();
+        for action in Action::all().into_iter().filter(|action| !listed.contains(action)) {
+            for key in defaults.keys(action) {
+                keymap.bind(action, *key).ok();
+            }
+        }
+        Ok(keymap)
+    }
+
+    fn keys_mut(&mut self, action: Action) -> &mut Vec<Key> {
+        let index = self.bindings.iter().position(|(bound, _)| *bound == action).unwrap();
+        &mut self.bindings[index].1
+    }
+}
@@ -1,25 +1,66 @@
 mod utils;
-mod block;
-mod color_grid;
+pub mod block;
+pub mod color_grid;
+pub mod config;
+pub mod engine;
+pub mod fumen;
+pub mod garbage;
+mod action;
+pub mod ai;
 mod backend;
 mod board;
+mod bot;
 mod gameover;
-mod lrd;
+pub mod handling;
+mod hold;
+pub mod keymap;
+pub mod level;
+pub mod lock_delay;
+pub mod lrd;
 mod manual;
+mod menu;
+mod meter;
+pub mod mode;
+pub mod net;
 mod numbers;
+mod online;
+mod palette;
 mod pause;
-mod pos;
+pub mod piece_queue;
+pub mod pos;
 mod queue;
+pub mod randomizer;
+pub mod replay;
+pub mod rotation;
+pub mod save;
+pub mod scoring;
+pub mod search;
 mod tetris;
 mod timer;
+pub mod top_out;
+pub mod tspin;
+pub mod versus;
 mod score;
+mod seed;
+mod settings;
+pub mod socket;
+mod split_screen;
+mod storage;
+pub mod tbp;
 
 use cursive::{
     self,
     view::{Nameable, Selector},
 };
+use crate::config::Config;
+use crate::mode::GameModeKind;
+use crate::save::Save;
+use cursive::views::Dialog;
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
 use std::sync::Mutex;
+#[cfg(target_arch = "wasm32")]
 use web_sys::HtmlCanvasElement;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
@@ -28,44 +69,159 @@ use web_sys::HtmlCanvasElement;
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern {
     fn alert(s: &str);
 }
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub struct Cursive {
     backend: Mutex<cursive::Cursive>,
 }
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 impl Cursive {
     #[wasm_bindgen(js_name = "retris")]
-    pub async fn retris() -> Cursive {
+    pub async fn retris(options: Option<String>, mode: Option<String>) -> Cursive {
         utils::set_panic_hook();
         alert("Hello, wretris!");
-        let mut siv: cursive::Cursive = cursive::Cursive::new();
-        let tetris = crate::tetris::Tetris::new().with_name("retris");
-        siv.add_layer(tetris);
-        siv.focus(&Selector::Name("retris")).unwrap();
-        siv.set_fps(1000);
+        let siv = setup(options, mode);
         let siv: Mutex<cursive::Cursive> = std::sync::Mutex::new(siv);
         siv.lock().unwrap().run_with(|| backend::backend()).await;
         Cursive { backend: siv }
     }
 
     #[wasm_bindgen(js_name = "retris_with_canvas")]
-    pub async fn retris_with_canvas(canvas: HtmlCanvasElement) -> Cursive {
+    pub async fn retris_with_canvas(canvas: HtmlCanvasElement, options: Option<String>, mode: Option<String>) -> Cursive {
         utils::set_panic_hook();
         alert("Hello, wretris!");
-        let mut siv: cursive::Cursive = cursive::Cursive::new();
-        let tetris = crate::tetris::Tetris::new().with_name("retris");
-        siv.add_layer(tetris);
-        siv.focus(&Selector::Name("retris")).unwrap();
-        siv.set_fps(1000);
+        let siv = setup(options, mode);
         let siv: Mutex<cursive::Cursive> = std::sync::Mutex::new(siv);
         siv.lock().unwrap().run_with(|| backend::backend_with_canvas(canvas)).await;
         Cursive { backend: siv }
     }
 }
 
+/// Plays in the terminal until the player quits with Ctrl-C. Takes the same options
+/// as the page URL.
+#[cfg(feature = "native")]
+pub fn run(options: Option<String>, mode: Option<String>) {
+    let mut siv = setup(options, mode);
+    siv.run_with(backend::terminal);
+    siv.call_on_name("retris", |t: &mut crate::tetris::Tetris| t.save_game());
+}
+
+/// Builds the game from the page options. Without a valid `mode` the player picks one
+/// from the start menu first, and `versus` puts two players side by side instead, or
+/// against someone elsewhere with a `server` to meet on.
+fn setup(options: Option<String>, mode: Option<String>) -> cursive::Cursive {
+    let mut siv: cursive::Cursive = cursive::Cursive::new();
+    let mut config = Config::from_query(&options.unwrap_or_default());
+    let mode = mode.as_deref().and_then(GameModeKind::from_name);
+    config.mode = mode.unwrap_or(config.mode);
+    if config.mode == GameModeKind::Versus && config.server.is_some() {
+        match online::Online::connect(config.clone()) {
+            Ok(online) => {
+                siv.add_layer(online.with_name("online"));
+                #[cfg(target_arch = "wasm32")]
+                track_key_releases(&mut siv);
+                siv.focus(&Selector::Name("online")).unwrap();
+                siv.set_fps(60);
+                return siv;
+            }
+            Err(error) => siv.add_layer(Dialog::info(format!("{}\nPlaying on this keyboard instead.", error))),
+        }
+    }
+    if config.mode == GameModeKind::Versus {
+        siv.add_layer(split_screen::SplitScreen::new(config).with_name("versus"));
+        #[cfg(target_arch = "wasm32")]
+        track_key_releases(&mut siv);
+        siv.focus(&Selector::Name("versus")).unwrap();
+        siv.set_fps(60);
+        return siv;
+    }
+    let menu = menu::start_menu(config.mode, config.gravity_curve.levels(), config.start_level);
+    let bot = config.bot.clone();
+    let mut tetris = crate::tetris::Tetris::with_config(config);
+    let bot_error = bot.and_then(|bot| tetris.start_bot(&bot).err());
+    siv.add_layer(tetris.with_name("retris"));
+    // Terminals don't report key releases, so there every key press is one move.
+    #[cfg(target_arch = "wasm32")]
+    track_key_releases(&mut siv);
+    #[cfg(target_arch = "wasm32")]
+    save_when_hidden(&mut siv);
+    siv.focus(&Selector::Name("retris")).unwrap();
+    if mode.is_none() {
+        siv.add_layer(menu);
+    }
+    offer_saved_game(&mut siv, mode.is_none());
+    if let Some(error) = bot_error {
+        siv.add_layer(Dialog::info(error));
+    }
+    siv.set_fps(60);
+    siv
+}
+
+/// Asks whether to continue the game left unfinished last time, over the start menu
+/// when there is one. A save that can't be resumed any more is thrown away.
+fn offer_saved_game(siv: &mut cursive::Cursive, has_menu: bool) {
+    let save = match storage::load(tetris::SAVED_GAME).map(|text| Save::decode(&text)) {
+        Some(Ok(save)) => save,
+        Some(Err(error)) => {
+            storage::remove(tetris::SAVED_GAME);
+            siv.add_layer(Dialog::info(format!("The saved game was discarded: {}", error)));
+            return;
+        }
+        None => return,
+    };
+    let info = format!("{} game, {} lines, {} points", save.config.mode.title(), save.lines, save.points);
+    siv.add_layer(
+        Dialog::text(format!("Continue the unfinished game?\n{}", info))
+            .title("Saved game")
+            .button("Continue", move |s| {
+                s.pop_layer();
+                if has_menu {
+                    s.pop_layer();
+                }
+                let resumed = s.call_on_name("retris", |t: &mut crate::tetris::Tetris| t.resume_game(save.clone()));
+                if let Some(Err(error)) = resumed {
+                    storage::remove(tetris::SAVED_GAME);
+                    s.add_layer(Dialog::info(format!("The saved game was discarded: {}", error)));
+                }
+            })
+            .button("New game", |s| {
+                storage::remove(tetris::SAVED_GAME);
+                s.pop_layer();
+            }),
+    );
+}
+
+#[cfg(target_arch = "wasm32")]
+fn track_key_releases(siv: &mut cursive::Cursive) {
+    let cb_sink = siv.cb_sink().clone();
+    backend::forward_key_releases(move |event| {
+        cb_sink.send(Box::new(move |s| {
+            s.call_on_name("retris", |t: &mut crate::tetris::Tetris| t.on_key_release(event.clone()));
+            s.call_on_name("versus", |v: &mut split_screen::SplitScreen| v.on_key_release(event.clone()));
+            s.call_on_name("online", |o: &mut online::Online| o.on_key_release(event));
+        })).ok();
+    });
+    siv.call_on_name("retris", |t: &mut crate::tetris::Tetris| t.track_key_releases());
+    siv.call_on_name("versus", |v: &mut split_screen::SplitScreen| v.track_key_releases());
+    siv.call_on_name("online", |o: &mut online::Online| o.track_key_releases());
+}
+
+/// Saves the game in progress whenever the page is hidden, as when the player switches
+/// tabs or closes it.
+#[cfg(target_arch = "wasm32")]
+fn save_when_hidden(siv: &mut cursive::Cursive) {
+    let cb_sink = siv.cb_sink().clone();
+    backend::on_page_hidden(move || {
+        cb_sink.send(Box::new(|s| {
+            s.call_on_name("retris", |t: &mut crate::tetris::Tetris| t.save_game());
+        })).ok();
+    });
+}
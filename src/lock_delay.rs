@@ -0,0 +1,109 @@
+/// What gives a grounded piece more time before it locks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LockReset {
+    /// Only falling a row restarts the timer.
+    Step,
+    /// Every successful shift or turn restarts the timer, up to a limited number of times.
+    #[default]
+    Move,
+}
+
+impl LockReset {
+    pub fn name(&self) -> &'static str {
+        match self {
+            LockReset::Step => "step",
+            LockReset::Move => "move",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<LockReset> {
+        match name {
+            "step" => Some(LockReset::Step),
+            "move" => Some(LockReset::Move),
+            _ => None,
+        }
+    }
+}
+
+/// Counts down, in milliseconds, how long a piece may rest on the stack before it locks.
+#[derive(Clone, Debug)]
+pub struct LockDelay {
+    delay: u64,
+    mode: LockReset,
+    max_resets: usize,
+    elapsed: u64,
+    resets: usize,
+    lowest_y: i32,
+}
+
+impl LockDelay {
+    pub fn new(delay: u64, mode: LockReset, max_resets: usize) -> Self {
+        Self {
+            delay,
+            mode,
+            max_resets,
+            elapsed: 0,
+            resets: 0,
+            lowest_y: i32::MIN,
+        }
+    }
+
+    /// Starts over for a freshly spawned piece at row `y`.
+    pub fn renew(&mut self, y: i32) {
+        self.elapsed = 0;
+        self.resets = 0;
+        self.lowest_y = y;
+    }
+
+    /// The piece fell to row `y`. Reaching a new lowest row also gives back every reset.
+    pub fn on_step_down(&mut self, y: i32) {
+        self.elapsed = 0;
+        if y > self.lowest_y {
+            self.lowest_y = y;
+            self.resets = 0;
+        }
+    }
+
+    /// The grounded piece was shifted or turned. Once the resets run out it locks at once.
+    pub fn on_move(&mut self) {
+        if self.mode != LockReset::Move {
+            return;
+        }
+        if self.resets < self.max_resets {
+            self.resets += 1;
+            self.elapsed = 0;
+        } else {
+            self.elapsed = self.delay;
+        }
+    }
+
+    /// Runs the timer for a grounded piece and tells whether it has to lock now.
+    pub fn tick(&mut self, dt: u64) -> bool {
+        self.elapsed = std::cmp::min(self.elapsed + dt, self.delay);
+        self.elapsed >= self.delay
+    }
+
+    /// How far the timer has run, from 0 to 1.
+    pub fn progress(&self) -> f64 {
+        if self.delay == 0 {
+            return 1.0;
+        }
+        self.elapsed as f64 / self.delay as f64
+    }
+
+    pub fn resets_left(&self) -> usize {
+        self.max_resets - self.resets
+    }
+
+    /// The running timer: elapsed milliseconds, resets used and the lowest row reached.
+    pub fn state(&self) -> (u64, usize, i32) {
+        (self.elapsed, self.resets, self.lowest_y)
+    }
+
+    /// Continues a timer from `state`.
+    pub fn restore(&mut self, (elapsed, resets, lowest_y): (u64, usize, i32)) {
+        self.elapsed = elapsed.min(self.delay);
+        self.resets = resets.min(self.max_resets);
+        self.lowest_y = lowest_y;
+    }
+}
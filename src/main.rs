@@ -0,0 +1,15 @@
+//! Plays wretris in the terminal: `wretris [mode] [option=value ...]`, taking the options
+//! of the page URL, e.g. `wretris zen seed=42 previews=5`.
+
+fn main() {
+    let mut mode = None;
+    let mut options = Vec::new();
+    for arg in std::env::args().skip(1) {
+        if arg.contains('=') {
+            options.push(arg);
+        } else {
+            mode = Some(arg);
+        }
+    }
+    wretris::run(Some(options.join("&")), mode);
+}
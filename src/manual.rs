@@ -1,45 +1,72 @@
+use crate::keymap::{ Action, Keymap };
+use crate::replay::Playback;
+
 use cursive::{
     event::{Event, EventResult},
     theme::{ ColorStyle, Color, },
     Printer, View, Vec2,
 };
 
+/// Lists the key of every action in the active keymap.
 pub struct Manual {
+    lines: Vec<String>,
 }
 
 impl Default for Manual {
     fn default() -> Self {
-        Self::new()
+        Self::new(&Keymap::default())
     }
 }
 
 impl Manual {
-    pub fn new() -> Manual {
-        Manual {}
+    pub fn new(keymap: &Keymap) -> Manual {
+        let lines = Action::all()
+            .into_iter()
+            .map(|action| format!("{}: {}", keymap.label(action), action.description()))
+            .collect();
+        Manual { lines }
+    }
+
+    /// The state of a replay and the keys controlling it.
+    pub fn playback(playback: &Playback) -> Manual {
+        let state = if playback.is_finished() {
+            "finished"
+        } else if playback.is_paused() {
+            "paused"
+        } else {
+            "playing"
+        };
+        let lines = vec![
+            format!("Replay {} at {}x", state, playback.speed()),
+            format!("{} / {}", seconds(playback.time()), seconds(playback.duration())),
+            "space: pause and play".to_string(),
+            ".: next frame".to_string(),
+            "+,-: faster, slower".to_string(),
+            "←,→: 5s back, ahead".to_string(),
+            "0: from the start".to_string(),
+            "esc: back to the game".to_string(),
+        ];
+        Manual { lines }
     }
 }
 
+fn seconds(ms: u64) -> String {
+    format!("{}.{}s", ms / 1000, ms % 1000 / 100)
+}
+
 impl View for Manual {
     fn draw(&self, printer: &Printer) {
         let color_style = ColorStyle::new(Color::Rgb(50, 79, 54), Color::Rgb(255,255,255));
         printer.with_color(color_style, |printer| {
             printer.print((0, 0), &format!(" {:26} ", "Manual"));
-            printer.print((0, 1), &format!(" {:26} ", "↑,e: rotate clockwise"));
-            printer.print((0, 2), &format!(" {:26} ", "w: rotate counterclockwise"));
-            printer.print((0, 3), &format!(" {:26} ", "s: flip turn"));
-            printer.print((0, 4), &format!(" {:26} ", "↓: speed up"));
-            printer.print((0, 5), &format!(" {:26} ", "←: left"));
-            printer.print((0, 6), &format!(" {:26} ", "a: left most"));
-            printer.print((0, 7), &format!(" {:26} ", "→: right"));
-            printer.print((0, 8), &format!(" {:26} ", "d: right most"));
-            printer.print((0, 9), &format!(" {:26} ", "space: hard drop"));
-            printer.print((0, 10), &format!(" {:26} ", "m: stop and resume"));
-            printer.print((0, 11), &format!(" {:26} ", "n: new game"));
+            for (i, line) in self.lines.iter().enumerate() {
+                printer.print((0, i + 1), &format!(" {:26} ", line));
+            }
         });
     }
 
     fn required_size(&mut self, _constraints: Vec2) -> Vec2 {
-        Vec2::new(30, 16)
+        Vec2::new(30, self.lines.len() + 3)
     }
 
     fn on_event(&mut self, _: Event) -> EventResult {
@@ -0,0 +1,39 @@
+use crate::mode::GameModeKind;
+use crate::tetris::Tetris;
+
+use cursive::{
+    Cursive,
+    view::Scrollable,
+    views::{ Dialog, SelectView },
+};
+use std::ops::RangeInclusive;
+
+/// Asks for a game mode, then for the starting level, and starts a new game with both.
+pub fn start_menu(mode: GameModeKind, levels: RangeInclusive<usize>, level: usize) -> Dialog {
+    let modes = GameModeKind::all();
+    let mut select = SelectView::new();
+    for kind in &modes {
+        select.add_item(kind.title(), *kind);
+    }
+    let select = select
+        .selected(modes.iter().position(|kind| *kind == mode).unwrap_or(0))
+        .on_submit(move |s, mode: &GameModeKind| {
+            s.pop_layer();
+            s.add_layer(level_menu(*mode, levels.clone(), level));
+        });
+    Dialog::around(select).title("wretris")
+}
+
+fn level_menu(mode: GameModeKind, levels: RangeInclusive<usize>, level: usize) -> Dialog {
+    let mut select = SelectView::new();
+    for level in levels.clone() {
+        select.add_item(format!("Level {}", level), level);
+    }
+    let select = select
+        .selected(level.saturating_sub(*levels.start()))
+        .on_submit(move |s: &mut Cursive, level: &usize| {
+            s.pop_layer();
+            s.call_on_name("retris", |t: &mut Tetris| t.new_game(mode, *level));
+        });
+    Dialog::around(select.scrollable()).title("Start level")
+}
@@ -0,0 +1,44 @@
+use crate::engine::Snapshot;
+use cursive::{
+    Printer,
+    View,
+    theme::{Color, ColorStyle},
+};
+
+/// A bar beside the board that rises with the garbage waiting to come up. It turns red
+/// once the garbage would fill half the board.
+pub struct Meter {
+    height: usize,
+    garbage: usize,
+}
+
+impl Meter {
+    pub fn new(height: usize) -> Self {
+        Self {
+            height,
+            garbage: 0,
+        }
+    }
+
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.height = snapshot.height;
+        self.garbage = snapshot.garbage;
+    }
+}
+
+impl View for Meter {
+    fn draw(&self, printer: &Printer) {
+        let filled = self.garbage.min(self.height);
+        let color = if 2 * self.garbage >= self.height { Color::Rgb(224, 60, 60) } else { Color::Rgb(240, 180, 60) };
+        for j in 0..self.height {
+            let color = if j >= self.height - filled { color } else { Color::Rgb(20, 20, 20) };
+            printer.with_color(ColorStyle::new(color, color), |printer| {
+                printer.print((0, j), " ");
+            });
+        }
+    }
+
+    fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
+        cursive::Vec2::new(1, self.height)
+    }
+}
@@ -0,0 +1,219 @@
+use crate::numbers::padding;
+use crate::top_out::TopOutRules;
+
+/// How far the player has come, as far as a mode cares.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub points: u64,
+    pub lines: usize,
+    pub level: usize,
+    pub elapsed: u64,
+}
+
+/// Rules deciding when a game is over and what the HUD shows.
+pub trait GameMode {
+    /// Whether the player has reached the goal of the mode.
+    fn is_complete(&self, progress: &Progress) -> bool;
+
+    /// Which top-outs end the game. Any other block out clears the stack and play goes on.
+    fn top_out_rules(&self) -> TopOutRules {
+        TopOutRules::default()
+    }
+
+    /// Milliseconds to show on the clock.
+    fn clock(&self, progress: &Progress) -> u64 {
+        progress.elapsed
+    }
+
+    fn hud(&self, progress: &Progress) -> Vec<String>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameModeKind {
+    /// Clear a number of lines as fast as possible.
+    Sprint { lines: usize },
+    /// Play until the level after `max_level`, or forever.
+    Marathon { max_level: Option<usize> },
+    /// Score as much as possible in `duration` milliseconds.
+    Ultra { duration: u64 },
+    /// Play without pressure; topping out only clears the stack.
+    Zen,
+    /// Two players send each other garbage until one tops out.
+    Versus,
+}
+
+impl Default for GameModeKind {
+    fn default() -> Self {
+        GameModeKind::Sprint { lines: 40 }
+    }
+}
+
+impl GameModeKind {
+    /// The presets offered in the start menu.
+    pub fn all() -> Vec<GameModeKind> {
+        vec![
+            GameModeKind::Sprint { lines: 40 },
+            GameModeKind::Marathon { max_level: Some(15) },
+            GameModeKind::Marathon { max_level: None },
+            GameModeKind::Ultra { duration: 2 * 60 * 1000 },
+            GameModeKind::Ultra { duration: 3 * 60 * 1000 },
+            GameModeKind::Zen,
+        ]
+    }
+
+    pub fn create(&self) -> Box<dyn GameMode> {
+        match *self {
+            GameModeKind::Sprint { lines } => Box::new(Sprint { lines }),
+            GameModeKind::Marathon { max_level } => Box::new(Marathon { max_level }),
+            GameModeKind::Ultra { duration } => Box::new(Ultra { duration }),
+            GameModeKind::Zen => Box::new(Zen),
+            GameModeKind::Versus => Box::new(Versus),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            GameModeKind::Sprint { lines } => format!("sprint-{}", lines),
+            GameModeKind::Marathon { max_level: Some(level) } => format!("marathon-{}", level),
+            GameModeKind::Marathon { max_level: None } => "marathon-endless".to_string(),
+            GameModeKind::Ultra { duration } => format!("ultra-{}", duration / 60_000),
+            GameModeKind::Zen => "zen".to_string(),
+            GameModeKind::Versus => "versus".to_string(),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self {
+            GameModeKind::Sprint { lines } => format!("Sprint ({} lines)", lines),
+            GameModeKind::Marathon { max_level: Some(level) } => format!("Marathon (to level {})", level),
+            GameModeKind::Marathon { max_level: None } => "Marathon (endless)".to_string(),
+            GameModeKind::Ultra { duration } => format!("Ultra ({} minutes)", duration / 60_000),
+            GameModeKind::Zen => "Zen".to_string(),
+            GameModeKind::Versus => "Versus".to_string(),
+        }
+    }
+
+    /// Parses names like `sprint`, `sprint-20`, `marathon-endless`, `ultra-3`, `zen` or `versus`.
+    pub fn from_name(name: &str) -> Option<GameModeKind> {
+        let mut parts = name.splitn(2, '-');
+        let (mode, arg) = (parts.next().unwrap_or(""), parts.next());
+        match (mode, arg) {
+            ("sprint", None) => Some(GameModeKind::Sprint { lines: 40 }),
+            ("sprint", Some(lines)) => lines.parse().ok().map(|lines| GameModeKind::Sprint { lines }),
+            ("marathon", None) => Some(GameModeKind::Marathon { max_level: Some(15) }),
+            ("marathon", Some("endless")) => Some(GameModeKind::Marathon { max_level: None }),
+            ("marathon", Some(level)) => level.parse().ok().map(|level| GameModeKind::Marathon { max_level: Some(level) }),
+            ("ultra", None) => Some(GameModeKind::Ultra { duration: 2 * 60 * 1000 }),
+            ("ultra", Some(minutes)) => minutes.parse::<u64>().ok().map(|minutes| GameModeKind::Ultra { duration: minutes * 60 * 1000 }),
+            ("zen", None) => Some(GameModeKind::Zen),
+            ("versus", None) => Some(GameModeKind::Versus),
+            _ => None,
+        }
+    }
+}
+
+fn score2str(progress: &Progress) -> String {
+    format!("Score: {}", padding(progress.points as usize, 8))
+}
+
+fn level2str(progress: &Progress) -> String {
+    format!("Level: {}", padding(progress.level, 2))
+}
+
+pub struct Sprint {
+    lines: usize,
+}
+
+impl GameMode for Sprint {
+    fn is_complete(&self, progress: &Progress) -> bool {
+        progress.lines >= self.lines
+    }
+
+    fn hud(&self, progress: &Progress) -> Vec<String> {
+        vec![
+            format!("Lines: {} / {}", padding(progress.lines, 2), self.lines),
+            score2str(progress),
+            level2str(progress),
+        ]
+    }
+}
+
+pub struct Marathon {
+    max_level: Option<usize>,
+}
+
+impl GameMode for Marathon {
+    fn is_complete(&self, progress: &Progress) -> bool {
+        self.max_level.is_some_and(|max_level| progress.level > max_level)
+    }
+
+    fn hud(&self, progress: &Progress) -> Vec<String> {
+        let level = match self.max_level {
+            Some(max_level) => format!("Level: {} / {}", padding(progress.level.min(max_level), 2), max_level),
+            None => level2str(progress),
+        };
+        vec![
+            level,
+            format!("Lines: {}", padding(progress.lines, 2)),
+            score2str(progress),
+        ]
+    }
+}
+
+pub struct Ultra {
+    duration: u64,
+}
+
+impl GameMode for Ultra {
+    fn is_complete(&self, progress: &Progress) -> bool {
+        progress.elapsed >= self.duration
+    }
+
+    fn clock(&self, progress: &Progress) -> u64 {
+        self.duration.saturating_sub(progress.elapsed)
+    }
+
+    fn hud(&self, progress: &Progress) -> Vec<String> {
+        vec![
+            score2str(progress),
+            format!("Lines: {}", padding(progress.lines, 2)),
+            level2str(progress),
+        ]
+    }
+}
+
+pub struct Zen;
+
+impl GameMode for Zen {
+    fn is_complete(&self, _: &Progress) -> bool {
+        false
+    }
+
+    fn top_out_rules(&self) -> TopOutRules {
+        TopOutRules::none()
+    }
+
+    fn hud(&self, progress: &Progress) -> Vec<String> {
+        vec![
+            format!("Lines: {}", padding(progress.lines, 2)),
+            score2str(progress),
+            level2str(progress),
+        ]
+    }
+}
+
+pub struct Versus;
+
+impl GameMode for Versus {
+    fn is_complete(&self, _: &Progress) -> bool {
+        false
+    }
+
+    fn hud(&self, progress: &Progress) -> Vec<String> {
+        vec![
+            format!("Lines: {}", padding(progress.lines, 2)),
+            score2str(progress),
+            level2str(progress),
+        ]
+    }
+}
@@ -0,0 +1,306 @@
+//! Versus across machines: the JSON messages clients and the relay server exchange, and
+//! the match as one client plays it. Each client plays its own game and follows the
+//! other player's on a copy of it, which the inputs it syncs drive exactly as they went,
+//! since both games are dealt the same blocks from the seed the server shares.
+
+use crate::config::Config;
+use crate::engine::{ Engine, GameEvent, Input };
+use crate::mode::GameModeKind;
+use crate::versus::PLAYERS;
+
+use serde::{ Deserialize, Serialize };
+
+/// Something that happened to a game at a moment of it, besides time passing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    Input(Input),
+    /// Garbage from the other player arrived.
+    Garbage(usize),
+}
+
+/// Messages from a client to the server.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Enters the lobby of `room` as `name`. Whoever opens the room sets its rules, a
+    /// query as `Config::to_query` writes.
+    Join { room: String, name: String, config: String },
+    /// Ready for the next round.
+    Ready,
+    /// The steps of the round since the last sync, each at the elapsed milliseconds it
+    /// came at, and how far the game has run.
+    Sync { round: usize, elapsed: u64, steps: Vec<(u64, Step)> },
+    /// Garbage for the other player.
+    Attack { round: usize, lines: usize },
+    ToppedOut { round: usize },
+}
+
+/// Messages from the server to a client.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// Who is in the room by seat, who of them is ready, and the seat of the receiver.
+    Lobby { room: String, players: Vec<String>, ready: Vec<bool>, seat: usize },
+    /// A round starts: both players play the rules of the room with the same seed.
+    Start { round: usize, seed: u64, config: String },
+    /// The other player's game, as they synced it.
+    Sync { round: usize, elapsed: u64, steps: Vec<(u64, Step)> },
+    /// Garbage the other player sent.
+    Attack { round: usize, lines: usize },
+    /// Rounds each seat has won, after `winner` won one.
+    RoundOver { round: usize, winner: usize, wins: Vec<usize> },
+    MatchOver { winner: usize, wins: Vec<usize> },
+    /// The other player left, which ends the match.
+    Left { name: String },
+    Error { message: String },
+}
+
+impl ClientMessage {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn decode(text: &str) -> Result<ClientMessage, String> {
+        serde_json::from_str(text).map_err(|error| format!("Bad message from the client: {}", error))
+    }
+}
+
+impl ServerMessage {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn decode(text: &str) -> Result<ServerMessage, String> {
+        serde_json::from_str(text).map_err(|error| format!("Bad message from the server: {}", error))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    /// Waiting in the lobby for another player, or for both to be ready.
+    Lobby,
+    Playing,
+    RoundOver { winner: usize },
+    MatchOver { winner: usize },
+}
+
+/// One client's side of an online match: its own game, the other player's as synced,
+/// and the messages to send the server.
+pub struct Match {
+    config: Config,
+    state: State,
+    seat: usize,
+    players: Vec<String>,
+    ready: Vec<bool>,
+    round: usize,
+    wins: [usize; PLAYERS],
+    local: Engine,
+    remote: Engine,
+    /// Steps of the local game not synced yet.
+    steps: Vec<(u64, Step)>,
+    /// Elapsed milliseconds of the local game at the last sync.
+    synced: u64,
+    /// Whether the server has been told the local game topped out.
+    topped_out: bool,
+    outbox: Vec<ClientMessage>,
+}
+
+impl Match {
+    /// Joins `room` as `name`, offering the rules of `config` if the room is new.
+    pub fn new(config: Config, room: &str, name: &str) -> Self {
+        let config = Config { mode: GameModeKind::Versus, ..config };
+        let join = ClientMessage::Join { room: room.to_string(), name: name.to_string(), config: config.to_query() };
+        Self {
+            local: Engine::new(config.clone()),
+            remote: Engine::new(config.clone()),
+            config,
+            state: State::Lobby,
+            seat: 0,
+            players: Vec::new(),
+            ready: Vec::new(),
+            round: 0,
+            wins: [0; PLAYERS],
+            steps: Vec::new(),
+            synced: 0,
+            topped_out: false,
+            outbox: vec![join],
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Seat of this player: 0 for whoever opened the room, 1 for the other player.
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    /// Names of the players in the room by seat.
+    pub fn players(&self) -> &[String] {
+        &self.players
+    }
+
+    /// Whether the player in `seat` is ready for the next round.
+    pub fn is_ready(&self, seat: usize) -> bool {
+        self.ready.get(seat).copied().unwrap_or(false)
+    }
+
+    /// Rounds each seat has won.
+    pub fn wins(&self) -> [usize; PLAYERS] {
+        self.wins
+    }
+
+    /// Rounds it takes to win the match.
+    pub fn rounds_to_win(&self) -> usize {
+        self.config.best_of / 2 + 1
+    }
+
+    /// This player's game.
+    pub fn engine(&self) -> &Engine {
+        &self.local
+    }
+
+    /// The other player's game, as far as it has been synced.
+    pub fn opponent(&self) -> &Engine {
+        &self.remote
+    }
+
+    /// Tells the server this player is ready for the next round.
+    pub fn ready(&mut self) {
+        if self.state != State::Playing && !self.is_ready(self.seat) {
+            self.outbox.push(ClientMessage::Ready);
+        }
+    }
+
+    pub fn handle_input(&mut self, input: Input) -> Vec<GameEvent> {
+        if self.state != State::Playing || self.local.is_gameover() {
+            return Vec::new();
+        }
+        let events = self.local.handle_input(input);
+        self.steps.push((self.local.elapsed(), Step::Input(input)));
+        self.resolve(&events);
+        events
+    }
+
+    /// Advances the local game by `dt` milliseconds.
+    pub fn tick(&mut self, dt: u64) -> Vec<GameEvent> {
+        if self.state != State::Playing {
+            return Vec::new();
+        }
+        let events = self.local.tick(dt);
+        self.resolve(&events);
+        events
+    }
+
+    /// Handles a message of the server. Errors, and the other player leaving, come back
+    /// as a message to show.
+    pub fn on_message(&mut self, message: ServerMessage) -> Result<(), String> {
+        match message {
+            ServerMessage::Lobby { players, ready, seat, .. } => {
+                self.players = players;
+                self.ready = ready;
+                self.seat = seat;
+            }
+            ServerMessage::Start { round, seed, config } => self.start(round, seed, &config),
+            ServerMessage::Sync { round, elapsed, steps } if round == self.round => self.follow(elapsed, &steps),
+            ServerMessage::Attack { round, lines } if round == self.round && self.state == State::Playing => {
+                if !self.local.is_gameover() {
+                    self.local.receive_garbage(lines);
+                    self.steps.push((self.local.elapsed(), Step::Garbage(lines)));
+                }
+            }
+            ServerMessage::RoundOver { round, winner, wins } if round == self.round => {
+                self.set_wins(&wins);
+                self.state = State::RoundOver { winner };
+            }
+            ServerMessage::MatchOver { winner, wins } => {
+                self.set_wins(&wins);
+                self.state = State::MatchOver { winner };
+            }
+            ServerMessage::Left { name } => {
+                self.wins = [0; PLAYERS];
+                self.state = State::Lobby;
+                return Err(format!("{} left the match", name));
+            }
+            ServerMessage::Error { message } => return Err(message),
+            // Messages of a round that is over.
+            ServerMessage::Sync { .. } | ServerMessage::Attack { .. } | ServerMessage::RoundOver { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Messages to send to the server, oldest first, with the steps of the local game
+    /// since the last call.
+    pub fn outgoing(&mut self) -> Vec<ClientMessage> {
+        if self.state == State::Playing && (!self.steps.is_empty() || self.local.elapsed() > self.synced) {
+            self.synced = self.local.elapsed();
+            let steps = std::mem::take(&mut self.steps);
+            self.outbox.push(ClientMessage::Sync { round: self.round, elapsed: self.synced, steps });
+        }
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Starts round `round` on the rules of the room, keeping this player's own settings.
+    fn start(&mut self, round: usize, seed: u64, rules: &str) {
+        let rules = Config::from_query(rules);
+        self.config = Config {
+            mode: GameModeKind::Versus,
+            seed: Some(seed),
+            keymap: self.config.keymap.clone(),
+            server: self.config.server.clone(),
+            room: self.config.room.clone(),
+            name: self.config.name.clone(),
+            ..rules
+        };
+        if matches!(self.state, State::MatchOver { .. }) {
+            self.wins = [0; PLAYERS];
+        }
+        self.local = Engine::new(self.config.clone());
+        self.remote = Engine::new(self.config.clone());
+        self.round = round;
+        self.ready = vec![false; self.players.len()];
+        self.steps.clear();
+        self.synced = 0;
+        self.topped_out = false;
+        self.state = State::Playing;
+    }
+
+    /// Plays the other player's steps on their game, up to `elapsed`.
+    fn follow(&mut self, elapsed: u64, steps: &[(u64, Step)]) {
+        for (at, step) in steps {
+            self.remote.tick(at.saturating_sub(self.remote.elapsed()));
+            match step {
+                Step::Input(input) => {
+                    self.remote.handle_input(*input);
+                }
+                Step::Garbage(lines) => self.remote.receive_garbage(*lines),
+            }
+        }
+        self.remote.tick(elapsed.saturating_sub(self.remote.elapsed()));
+    }
+
+    /// Sends the garbage of the local game's attacks and tells the server when it tops out.
+    fn resolve(&mut self, events: &[GameEvent]) {
+        for event in events {
+            if let GameEvent::Attack(lines) = event {
+                self.outbox.push(ClientMessage::Attack { round: self.round, lines: *lines });
+            }
+        }
+        if self.local.is_gameover() && !self.topped_out {
+            self.topped_out = true;
+            self.outbox.push(ClientMessage::ToppedOut { round: self.round });
+        }
+    }
+
+    fn set_wins(&mut self, wins: &[usize]) {
+        for (seat, won) in wins.iter().take(PLAYERS).enumerate() {
+            self.wins[seat] = *won;
+        }
+    }
+}
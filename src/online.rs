@@ -0,0 +1,208 @@
+use crate::config::Config;
+use crate::engine::Input;
+use crate::net::{ Match, State };
+use crate::settings;
+use crate::socket::Socket;
+use crate::split_screen::Side;
+use crate::utils;
+use crate::versus::PLAYERS;
+
+use cursive::{
+    event::{ Callback, Event, EventResult },
+    theme::{ Color, ColorStyle },
+    views::Dialog,
+    Printer, Vec2, View,
+};
+
+/// Versus against a player elsewhere through the relay server: this player's board on
+/// the left, the other player's on the right.
+pub struct Online {
+    game: Match,
+    socket: Socket,
+    /// This player's side first.
+    sides: Vec<Side>,
+    /// Whether the server can still be reached.
+    connected: bool,
+    /// The state and the number of players last announced, so each is announced once.
+    announced: Option<(State, usize)>,
+    last_refresh: f64,
+}
+
+impl Online {
+    /// Connects to `config.server` and joins `config.room`.
+    pub fn connect(config: Config) -> Result<Self, String> {
+        let url = config.server.clone().ok_or("No server given")?;
+        let socket = Socket::connect(&url)?;
+        let game = Match::new(config.clone(), &config.room, &config.name);
+        let keymap = settings::load_profile(&config.keymap).unwrap_or_default();
+        let sides = vec![Side::new(game.config(), keymap), Side::new(game.config(), Default::default())];
+        let mut online = Online {
+            game,
+            socket,
+            sides,
+            connected: true,
+            announced: None,
+            last_refresh: utils::now(),
+        };
+        online.update_views();
+        Ok(online)
+    }
+
+    fn update_views(&mut self) {
+        self.sides[0].update(&self.game.engine().snapshot());
+        self.sides[1].update(&self.game.opponent().snapshot());
+    }
+
+    /// Sends what the game has to say and handles what the server said. Returns the
+    /// errors to show.
+    fn exchange(&mut self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !self.connected {
+            return errors;
+        }
+        for message in self.socket.receive() {
+            match message {
+                Ok(message) => errors.extend(self.game.on_message(message).err()),
+                Err(error) => {
+                    self.connected = false;
+                    errors.push(error);
+                    return errors;
+                }
+            }
+        }
+        for message in self.game.outgoing() {
+            if let Err(error) = self.socket.send(&message) {
+                self.connected = false;
+                errors.push(error);
+                break;
+            }
+        }
+        errors
+    }
+
+    /// Shows errors, and a dialog to get ready whenever a round can start.
+    fn announce(&mut self, errors: Vec<String>) -> EventResult {
+        let players = self.game.players().to_vec();
+        let key = (self.game.state(), players.len());
+        let dialog = if self.announced == Some(key) || !self.connected {
+            None
+        } else {
+            self.announced = Some(key);
+            self.ready_dialog(&players)
+        };
+        if errors.is_empty() && dialog.is_none() {
+            return EventResult::Consumed(None);
+        }
+        EventResult::Consumed(Some(Callback::from_fn(move |s| {
+            if let Some((message, button)) = dialog.clone() {
+                s.add_layer(Dialog::text(message).title("Online versus").button(button, |s| {
+                    s.pop_layer();
+                    s.call_on_name("online", |v: &mut Online| v.game.ready());
+                }));
+            }
+            if !errors.is_empty() {
+                s.add_layer(Dialog::info(errors.join("\n")));
+            }
+        })))
+    }
+
+    /// What to say when both players are here and the next round waits for them.
+    fn ready_dialog(&self, players: &[String]) -> Option<(String, &'static str)> {
+        let name = |seat: usize| if seat == self.game.seat() { "You".to_string() } else { players[seat].clone() };
+        let wins = self.game.wins();
+        let score = format!("{} - {}", wins[self.game.seat()], wins[1 - self.game.seat()]);
+        match self.game.state() {
+            _ if players.len() < PLAYERS => None,
+            State::Lobby => Some((format!("{} vs {}", players[0], players[1]), "Ready")),
+            State::Playing => None,
+            State::RoundOver { winner } => Some((format!("{} won the round\n{}", name(winner), score), "Next round")),
+            State::MatchOver { winner } => Some((format!("{} won the match\n{}", name(winner), score), "Rematch")),
+        }
+    }
+
+    fn on_refresh(&mut self) -> EventResult {
+        let dt = (utils::now() - self.last_refresh).max(0.0) as u64;
+        self.last_refresh += dt as f64;
+        self.game.tick(dt);
+        let repeated = self.sides[0].tick(dt);
+        self.apply(repeated);
+        let errors = self.exchange();
+        self.update_views();
+        self.announce(errors)
+    }
+
+    /// Switches this player to DAS and ARR handling of held keys. Only call this when
+    /// every key release reaches `on_key_release`.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn track_key_releases(&mut self) {
+        let config = self.game.config().clone();
+        self.sides[0].track_key_releases(&config);
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn on_key_release(&mut self, event: Event) {
+        if let Some(input) = self.input(&event) {
+            let inputs = self.sides[0].release(input);
+            self.apply(inputs);
+            self.update_views();
+        }
+    }
+
+    fn apply(&mut self, inputs: Vec<Input>) {
+        for input in inputs {
+            self.game.handle_input(input);
+        }
+        for input in self.sides[0].keep_soft_drop(self.game.engine()) {
+            self.game.handle_input(input);
+        }
+    }
+
+    fn input(&self, event: &Event) -> Option<Input> {
+        settings::key(event).and_then(|key| self.sides[0].keymap().action(key)).and_then(|action| action.input())
+    }
+
+    /// What the board of `side` is titled: the player's name and the rounds they won.
+    fn title(&self, side: usize) -> String {
+        let seat = if side == 0 { self.game.seat() } else { 1 - self.game.seat() };
+        let name = self.game.players().get(seat).cloned().unwrap_or_else(|| "Waiting for a player".to_string());
+        let wins = self.game.wins()[seat];
+        let stars = "*".repeat(wins) + &".".repeat(self.game.rounds_to_win().saturating_sub(wins));
+        let ready = if self.game.state() != State::Playing && self.game.is_ready(seat) { "  ready" } else { "" };
+        format!("{}  {}{}", name, stars, ready)
+    }
+}
+
+impl View for Online {
+    fn draw(&self, printer: &Printer) {
+        let (x_padding, y_padding) = (5, 2);
+        let mut x = x_padding;
+        for (side, view) in self.sides.iter().enumerate() {
+            printer.with_color(ColorStyle::new(Color::Rgb(255,255,255), Color::Rgb(50, 79, 54)), |printer| {
+                printer.print((x, 0), &self.title(side));
+            });
+            view.draw(&printer.offset((x, y_padding)));
+            x += view.width() + 2 * x_padding;
+        }
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if event == Event::Refresh {
+            return self.on_refresh();
+        }
+        match self.input(&event) {
+            Some(input) => {
+                let inputs = self.sides[0].press(input);
+                self.apply(inputs);
+                self.update_views();
+                EventResult::Consumed(None)
+            }
+            None => EventResult::Ignored,
+        }
+    }
+
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        let width: usize = self.sides.iter().map(|side| side.width() + 10).sum();
+        let height = self.sides.iter().map(Side::height).max().unwrap_or(0);
+        Vec2::new(width + 5, height + 4)
+    }
+}
@@ -0,0 +1,62 @@
+use crate::block::{ BColor, BlockWithPos, Shape };
+
+use cursive::theme::{
+    BaseColor, ColorStyle, self,
+};
+
+impl BlockWithPos {
+    pub fn to_cursive_color(&self) -> ColorStyle {
+        self.block.shape().to_cursive()
+    }
+}
+
+impl Shape {
+    pub fn to_cursive(&self) -> ColorStyle {
+        self.to_color().to_cursive()
+    }
+}
+
+impl BColor {
+    /// Fades the color towards black by `amount`, from 0 (unchanged) to 1.
+    pub fn dimmed(&self, amount: f64) -> ColorStyle {
+        let (r, g, b) = self.rgb();
+        let scale = |c: u8| (c as f64 * (1.0 - amount.clamp(0.0, 1.0))) as u8;
+        let color = theme::Color::Rgb(scale(r), scale(g), scale(b));
+        ColorStyle::new(color, color)
+    }
+
+    fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            BColor::I => (85, 85, 255),
+            BColor::O => (255, 255, 85),
+            BColor::T => (255, 85, 255),
+            BColor::S => (85, 255, 85),
+            BColor::Z => (255, 85, 85),
+            BColor::J => (85, 255, 255),
+            BColor::L => (255, 255, 255),
+            BColor::GRID1 => (20, 20, 20),
+            BColor::GRID2 => (80, 80, 80),
+            BColor::HINT => (120, 120, 120),
+            BColor::WARNING => (255, 255, 85),
+            BColor::GARBAGE => (150, 150, 150),
+        }
+    }
+
+    pub fn to_cursive(&self) -> ColorStyle {
+        match self {
+            BColor::I => ColorStyle::new(theme::Color::Light(BaseColor::Blue), theme::Color::Light(BaseColor::Blue)),
+            BColor::O => ColorStyle::new(theme::Color::Light(BaseColor::Yellow), theme::Color::Light(BaseColor::Yellow)),
+            BColor::T => ColorStyle::new(theme::Color::Light(BaseColor::Magenta), theme::Color::Light(BaseColor::Magenta)),
+            BColor::S => ColorStyle::new(theme::Color::Light(BaseColor::Green), theme::Color::Light(BaseColor::Green)),
+            BColor::Z => ColorStyle::new(theme::Color::Light(BaseColor::Red), theme::Color::Light(BaseColor::Red)),
+            BColor::J => ColorStyle::new(theme::Color::Light(BaseColor::Cyan), theme::Color::Light(BaseColor::Cyan)),
+            BColor::L => ColorStyle::new(theme::Color::Light(BaseColor::White), theme::Color::Light(BaseColor::White)),
+            BColor::GRID1 => ColorStyle::new(theme::Color::Rgb(20, 20, 20), theme::Color::Rgb(20, 20, 20)),
+            BColor::GRID2 => ColorStyle::new(theme::Color::Rgb(80, 80, 80), theme::Color::Rgb(80, 80, 80)),
+            BColor::HINT => ColorStyle::new(theme::Color::Rgb(120, 120, 120), theme::Color::Rgb(120, 120, 120)),
+            BColor::WARNING => ColorStyle::new(theme::Color::Light(BaseColor::Yellow), theme::Color::Light(BaseColor::Yellow)),
+            BColor::GARBAGE => ColorStyle::new(theme::Color::Rgb(150, 150, 150), theme::Color::Rgb(150, 150, 150)),
+
+        }
+    }
+}
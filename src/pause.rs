@@ -4,19 +4,19 @@ use cursive::{
     Printer,
     theme::{BaseColor, Color, ColorStyle},
 };
+use crate::keymap::Key;
+use crate::settings;
 use crate::tetris::Tetris;
 
-pub struct Pause {}
-
-impl Default for Pause {
-    fn default() -> Self {
-        Self::new()
-    }
+pub struct Pause {
+    /// Keys that resume the game; any key does when there are none.
+    keys: Vec<Key>,
 }
 
 impl Pause {
-    pub fn new() -> Self {
+    pub fn new(keys: Vec<Key>) -> Self {
         Self {
+            keys,
         }
     }
 }
@@ -30,8 +30,9 @@ impl View for Pause {
                 });
             }
         }
+        let key = self.keys.first().map_or("any key".to_string(), Key::label);
         printer.with_color(ColorStyle::new(Color::Dark(BaseColor::White), Color::Dark(BaseColor::Blue)), |printer| {
-            printer.print((10, 2), "paused, press m to resume");
+            printer.print((10, 2), &format!("paused, press {} to resume", key));
         });
     }
 
@@ -40,12 +41,13 @@ impl View for Pause {
     }
 
     fn on_event(&mut self, event: Event) -> EventResult {
-        if event != Event::Char('m') && event != Event::Char('M') {
+        let resumes = self.keys.is_empty() || settings::key(&event).is_some_and(|key| self.keys.contains(&key));
+        if !resumes {
             EventResult::Ignored
         } else {
             EventResult::Consumed(Some(Callback::from_fn(move |s| {
                 s.pop_layer();
-                s.call_on_name("tetris", |t: &mut Tetris| t.on_event(Event::Char('m')));
+                s.call_on_name("retris", |t: &mut Tetris| t.stop_and_resume());
             })))
         }
     }
@@ -0,0 +1,56 @@
+use crate::block::Block;
+use crate::randomizer::{ Randomizer, RandomizerKind };
+
+use rand::RngCore;
+use std::collections::VecDeque;
+
+/// The upcoming blocks shown to the player, drawn ahead from the randomizer. The
+/// sequence of blocks does not depend on how many are shown.
+pub struct PieceQueue {
+    blocks: VecDeque<Block>,
+    randomizer: Box<dyn Randomizer>,
+    /// Blocks dealt by the randomizer so far, the ones in the queue included.
+    drawn: usize,
+}
+
+impl PieceQueue {
+    pub fn new(kind: RandomizerKind, previews: usize, rng: &mut dyn RngCore) -> Self {
+        let mut randomizer = kind.create();
+        let mut blocks = VecDeque::new();
+        for _ in 0..previews {
+            blocks.push_back(Block::new(randomizer.next(rng)));
+        }
+        Self {
+            blocks,
+            randomizer,
+            drawn: previews,
+        }
+    }
+
+    /// The queue after `drawn` blocks were dealt from a randomizer seeded like `rng`.
+    pub fn resume(kind: RandomizerKind, previews: usize, drawn: usize, rng: &mut dyn RngCore) -> Self {
+        let mut queue = Self::new(kind, previews, rng);
+        while queue.drawn < drawn {
+            queue.pop_and_spawn_new_block(rng);
+        }
+        queue
+    }
+
+    pub fn drawn(&self) -> usize {
+        self.drawn
+    }
+
+    pub fn renew(&mut self, kind: RandomizerKind, previews: usize, rng: &mut dyn RngCore) {
+        *self = Self::new(kind, previews, rng);
+    }
+
+    pub fn pop_and_spawn_new_block(&mut self, rng: &mut dyn RngCore) -> Block {
+        self.blocks.push_back(Block::new(self.randomizer.next(rng)));
+        self.drawn += 1;
+        self.blocks.pop_front().unwrap()
+    }
+
+    pub fn preview(&self) -> Vec<Block> {
+        self.blocks.iter().cloned().collect()
+    }
+}
@@ -1,80 +1,120 @@
-use crate::block::{ Block, Shape };
+use crate::block::Block;
+use crate::engine::Snapshot;
 use cursive:: {
     Printer,
     View,
     theme::{Color, ColorStyle},
 };
-use std::collections::VecDeque;
+
+/// Rows each preview takes, and the first one when drawn large.
+const PREVIEW_HEIGHT: usize = 5;
+const BIG_PREVIEW_HEIGHT: usize = 6;
 
 pub struct Queue {
-    pub blocks: VecDeque<Block>,
-    shapes: Vec<Shape>,
+    blocks: Vec<Block>,
+    previews: usize,
+    /// Draw the next block at twice the size of the others.
+    big_first: bool,
 }
 
 impl Default for Queue {
     fn default() -> Self {
-        Self::new()
+        Self::new(3, false)
     }
 }
 
 impl Queue {
-    pub fn new() -> Self {
-        let mut blocks = VecDeque::new();
-        for _ in 0..3 {
-            blocks.push_back(Block::default());
-        }
+    pub fn new(previews: usize, big_first: bool) -> Self {
         Self {
-            blocks,
-            shapes: Shape::all(),
+            blocks: Vec::new(),
+            previews,
+            big_first,
         }
     }
 
-    pub fn renew(&mut self) {
-        *self = Self::new();
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.blocks = snapshot.queue.clone();
+    }
+
+    fn is_big(&self) -> bool {
+        self.big_first && self.previews > 0
+    }
+
+    fn width(&self) -> usize {
+        if self.is_big() { 22 } else { 12 }
     }
 
-    pub fn pop_and_spawn_new_block(&mut self) -> Block {
-        let block = self.blocks.pop_front().unwrap();
-        if self.shapes.is_empty() {
-            self.shapes = Shape::all();
+    fn height(&self) -> usize {
+        if self.is_big() {
+            BIG_PREVIEW_HEIGHT + (self.previews - 1) * PREVIEW_HEIGHT
+        } else {
+            self.previews * PREVIEW_HEIGHT
         }
-        self.blocks.push_back(Block::new(self.shapes.pop().unwrap()));
-        block
     }
 
     fn draw_blocks(&self, printer: &Printer) {
-        let mut y_padding = 2;
-        for block in &self.blocks {
-            for vector in &block.cells() {
-                printer.with_color(block.color().to_cursive(), |printer| {
-                    printer.print((5 + 2*vector.0, y_padding + vector.1), "  ");
-                });
+        // Small blocks are centered in the wider container of a big preview.
+        let x_padding = (self.width() - 12) / 2;
+        let mut top = 0;
+        for (i, block) in self.blocks.iter().enumerate() {
+            let color = block.color().to_cursive();
+            if i == 0 && self.is_big() {
+                draw_big_block(printer, block, color, top + 3);
+                top += BIG_PREVIEW_HEIGHT;
+            } else {
+                draw_block(&printer.offset((x_padding, 0)), block, color, top + 2);
+                top += PREVIEW_HEIGHT;
             }
-            y_padding += 5;
         }
     }
 
     fn draw_container(&self, printer: &Printer) {
         let color_style = ColorStyle::new(Color::Rgb(255,255,255), Color::Rgb(183, 85, 224));
-        for j in 0..15 {
+        let inside = self.width() - 2;
+        for j in 0..self.height() {
                 printer.with_color(color_style, |printer| {
-                    printer.print((0, j), "|          |");
+                    printer.print((0, j), &format!("|{}|", " ".repeat(inside)));
                 });
         }
         printer.with_color(color_style, |printer| {
-            printer.print((0, 15), "|__________|");
+            printer.print((0, self.height()), &format!("|{}|", "_".repeat(inside)));
+        });
+    }
+}
+
+pub fn draw_block(printer: &Printer, block: &Block, color_style: ColorStyle, y_padding: usize) {
+    for vector in &block.cells() {
+        printer.with_color(color_style, |printer| {
+            printer.print((5 + 2*vector.0, y_padding as i32 + vector.1), "  ");
+        });
+    }
+}
+
+/// Draws every cell of `block` four columns wide and two rows high.
+fn draw_big_block(printer: &Printer, block: &Block, color_style: ColorStyle, y_padding: usize) {
+    for vector in &block.cells() {
+        printer.with_color(color_style, |printer| {
+            for row in 0..2 {
+                printer.print((9 + 4*vector.0, y_padding as i32 + 2*vector.1 + row), "    ");
+            }
         });
     }
 }
 
 impl View for Queue {
     fn draw(&self, printer: &Printer) {
+        if self.previews == 0 {
+            return;
+        }
         self.draw_container(printer);
         self.draw_blocks(printer);
     }
 
     fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
-        cursive::Vec2::new(12, 16)
+        if self.previews == 0 {
+            cursive::Vec2::new(0, 0)
+        } else {
+            cursive::Vec2::new(self.width(), self.height() + 1)
+        }
     }
 }
-
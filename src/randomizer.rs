@@ -0,0 +1,124 @@
+use crate::block::Shape;
+
+use rand::{ Rng, RngCore };
+use rand::seq::SliceRandom;
+
+const SHAPES: [Shape; 7] = [Shape::I, Shape::O, Shape::T, Shape::S, Shape::Z, Shape::J, Shape::L];
+
+/// A strategy deciding which shape comes next in the queue.
+pub trait Randomizer {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RandomizerKind {
+    #[default]
+    SevenBag,
+    FourteenBag,
+    Memoryless,
+    Tgm,
+}
+
+impl RandomizerKind {
+    pub fn all() -> Vec<RandomizerKind> {
+        vec![RandomizerKind::SevenBag, RandomizerKind::FourteenBag, RandomizerKind::Memoryless, RandomizerKind::Tgm]
+    }
+
+    pub fn create(&self) -> Box<dyn Randomizer> {
+        match self {
+            RandomizerKind::SevenBag => Box::new(Bag::new(1)),
+            RandomizerKind::FourteenBag => Box::new(Bag::new(2)),
+            RandomizerKind::Memoryless => Box::new(Memoryless),
+            RandomizerKind::Tgm => Box::new(Tgm::new(6)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RandomizerKind::SevenBag => "7-bag",
+            RandomizerKind::FourteenBag => "14-bag",
+            RandomizerKind::Memoryless => "memoryless",
+            RandomizerKind::Tgm => "tgm",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RandomizerKind> {
+        Self::all().into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// Deals every shape `copies` times in a shuffled bag before refilling it.
+pub struct Bag {
+    copies: usize,
+    shapes: Vec<Shape>,
+}
+
+impl Bag {
+    pub fn new(copies: usize) -> Self {
+        Self {
+            copies,
+            shapes: Vec::new(),
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        if self.shapes.is_empty() {
+            for _ in 0..self.copies {
+                self.shapes.extend_from_slice(&SHAPES);
+            }
+            self.shapes.shuffle(rng);
+        }
+        self.shapes.pop().unwrap()
+    }
+}
+
+/// Picks every shape independently with equal probability.
+pub struct Memoryless;
+
+impl Randomizer for Memoryless {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        SHAPES[rng.gen_range(0..SHAPES.len())]
+    }
+}
+
+/// Remembers the last four shapes and rerolls up to `rolls` times to avoid them.
+/// The first shape is never S, Z or O.
+pub struct Tgm {
+    rolls: usize,
+    history: [Shape; 4],
+    is_first: bool,
+}
+
+impl Tgm {
+    pub fn new(rolls: usize) -> Self {
+        Self {
+            rolls,
+            history: [Shape::Z, Shape::S, Shape::S, Shape::Z],
+            is_first: true,
+        }
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next(&mut self, rng: &mut dyn RngCore) -> Shape {
+        let shape = if self.is_first {
+            self.is_first = false;
+            let first = [Shape::I, Shape::T, Shape::J, Shape::L];
+            first[rng.gen_range(0..first.len())]
+        } else {
+            let mut shape = SHAPES[rng.gen_range(0..SHAPES.len())];
+            for _ in 1..self.rolls {
+                if !self.history.contains(&shape) {
+                    break;
+                }
+                shape = SHAPES[rng.gen_range(0..SHAPES.len())];
+            }
+            shape
+        };
+        self.history.rotate_left(1);
+        self.history[3] = shape;
+        shape
+    }
+}
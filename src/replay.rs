@@ -0,0 +1,229 @@
+use crate::config::Config;
+use crate::engine::{ Engine, GameEvent, Input };
+
+const HEADER: &str = "wretris-replay";
+/// Bumped whenever the engine changes in a way that plays old inputs differently.
+pub const VERSION: u32 = 1;
+
+pub const MIN_SPEED: f64 = 0.25;
+pub const MAX_SPEED: f64 = 4.0;
+
+/// Everything needed to play a game again: its rules with the seed, and every input
+/// with the elapsed milliseconds it came at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub config: Config,
+    pub inputs: Vec<(u64, Input)>,
+    /// Milliseconds the game lasted.
+    pub duration: u64,
+}
+
+impl Replay {
+    /// Records the current game of `engine` so far.
+    pub fn of(engine: &Engine) -> Replay {
+        Replay {
+            config: Config { seed: Some(engine.seed()), ..engine.config().clone() },
+            inputs: engine.inputs().to_vec(),
+            duration: engine.elapsed(),
+        }
+    }
+
+    /// A new engine at the start of the game.
+    pub fn engine(&self) -> Engine {
+        Engine::new(self.config.clone())
+    }
+
+    /// Writes the replay as text: a header with the version, the config as a query, the
+    /// duration and the inputs.
+    pub fn encode(&self) -> String {
+        format!("{} {}\n{}\n{}\n{}\n", HEADER, VERSION, self.config.to_query(), self.duration, encode_inputs(&self.inputs))
+    }
+
+    pub fn decode(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines();
+        match lines.next().map(|line| line.split_whitespace().collect::<Vec<_>>()).as_deref() {
+            Some([HEADER, version]) if *version == VERSION.to_string() => {}
+            Some([HEADER, version]) => return Err(format!("Replays of version {} can't be played", version)),
+            _ => return Err("Not a replay".to_string()),
+        }
+        let config = Config::from_query(lines.next().unwrap_or(""));
+        if config.seed.is_none() {
+            return Err("The replay has no seed".to_string());
+        }
+        let duration = lines.next().and_then(|line| line.trim().parse().ok()).ok_or("The replay has no duration")?;
+        let inputs = decode_inputs(lines.next().unwrap_or(""))?;
+        Ok(Replay { config, inputs, duration })
+    }
+}
+
+/// Writes timed inputs as the milliseconds since the previous input and a letter, e.g. `120l 33h`.
+pub fn encode_inputs(inputs: &[(u64, Input)]) -> String {
+    let mut last = 0;
+    let tokens: Vec<String> = inputs
+        .iter()
+        .map(|(at, input)| {
+            let token = format!("{}{}", at - last, code(*input));
+            last = *at;
+            token
+        })
+        .collect();
+    tokens.join(" ")
+}
+
+pub fn decode_inputs(text: &str) -> Result<Vec<(u64, Input)>, String> {
+    let mut inputs = Vec::new();
+    let mut at = 0;
+    for token in text.split_whitespace() {
+        let (delta, letter) = token.split_at(token.len() - token.chars().last().map_or(0, char::len_utf8));
+        let delta: u64 = delta.parse().map_err(|_| format!("Bad input {}", token))?;
+        let input = letter.chars().next().and_then(input).ok_or_else(|| format!("Bad input {}", token))?;
+        at += delta;
+        inputs.push((at, input));
+    }
+    Ok(inputs)
+}
+
+fn code(input: Input) -> char {
+    match input {
+        Input::Left => 'l',
+        Input::Right => 'r',
+        Input::LeftMost => 'L',
+        Input::RightMost => 'R',
+        Input::RotateClockwise => 'c',
+        Input::RotateCounterClockwise => 'w',
+        Input::FlipTurn => 'f',
+        Input::SoftDrop => 's',
+        Input::SoftDropRelease => 'u',
+        Input::HardDrop => 'h',
+        Input::SonicDrop => 'd',
+        Input::Hold => 'o',
+    }
+}
+
+fn input(code: char) -> Option<Input> {
+    match code {
+        'l' => Some(Input::Left),
+        'r' => Some(Input::Right),
+        'L' => Some(Input::LeftMost),
+        'R' => Some(Input::RightMost),
+        'c' => Some(Input::RotateClockwise),
+        'w' => Some(Input::RotateCounterClockwise),
+        'f' => Some(Input::FlipTurn),
+        's' => Some(Input::SoftDrop),
+        'u' => Some(Input::SoftDropRelease),
+        'h' => Some(Input::HardDrop),
+        'd' => Some(Input::SonicDrop),
+        'o' => Some(Input::Hold),
+        _ => None,
+    }
+}
+
+/// Plays a replay back on its own engine, which it drives through the recorded
+/// inputs exactly as the game went.
+pub struct Playback {
+    replay: Replay,
+    engine: Engine,
+    /// Index of the next input to apply.
+    next: usize,
+    time: u64,
+    speed: f64,
+    paused: bool,
+    /// Replay milliseconds owed by speeds other than 1x.
+    carry: f64,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        let engine = replay.engine();
+        Self {
+            replay,
+            engine,
+            next: 0,
+            time: 0,
+            speed: 1.0,
+            paused: false,
+            carry: 0.0,
+        }
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Milliseconds of the game played back so far.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.replay.duration
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.time >= self.replay.duration && self.next == self.replay.inputs.len()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) -> bool {
+        self.paused = !self.paused;
+        self.paused
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f64) {
+        self.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+    }
+
+    /// Plays `dt` milliseconds of real time at the current speed, unless paused.
+    pub fn advance(&mut self, dt: u64) -> Vec<GameEvent> {
+        if self.paused {
+            return Vec::new();
+        }
+        self.carry += dt as f64 * self.speed;
+        let steps = self.carry as u64;
+        self.carry -= steps as f64;
+        self.run_to(self.time + steps)
+    }
+
+    /// Plays up to the start of the next frame at 60 frames per second, even when paused.
+    pub fn step_frame(&mut self) -> Vec<GameEvent> {
+        let frame = self.time * 60 / 1000 + 1;
+        self.run_to((frame * 1000).div_ceil(60))
+    }
+
+    /// Jumps to `time` milliseconds into the game. Going back plays again from the start.
+    pub fn seek(&mut self, time: u64) {
+        if time < self.time {
+            self.engine = self.replay.engine();
+            self.next = 0;
+            self.time = 0;
+        }
+        self.carry = 0.0;
+        self.run_to(time);
+    }
+
+    fn run_to(&mut self, time: u64) -> Vec<GameEvent> {
+        let time = time.min(self.replay.duration);
+        let mut events = Vec::new();
+        while let Some((at, input)) = self.replay.inputs.get(self.next).copied() {
+            if at > time {
+                break;
+            }
+            events.extend(self.engine.tick(at.saturating_sub(self.time)));
+            self.time = self.time.max(at);
+            events.extend(self.engine.handle_input(input));
+            self.next += 1;
+        }
+        if time > self.time {
+            events.extend(self.engine.tick(time - self.time));
+            self.time = time;
+        }
+        events
+    }
+}
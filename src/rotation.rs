@@ -0,0 +1,145 @@
+use crate::block::{ BlockWithPos, Rotation, Shape };
+use crate::color_grid::ColorGrid;
+use crate::pos::Pos;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Turn {
+    Clockwise,
+    CounterClockwise,
+    Flip,
+}
+
+impl Turn {
+    pub fn apply(&self, block: &BlockWithPos) -> BlockWithPos {
+        match self {
+            Turn::Clockwise => block.rotate(true),
+            Turn::CounterClockwise => block.rotate(false),
+            Turn::Flip => block.flip_turn(),
+        }
+    }
+}
+
+/// Decides where a piece ends up after a turn, or whether it can turn at all.
+pub trait RotationSystem {
+    /// Returns the turned piece together with the index of the kick that made it fit.
+    fn rotate(&self, grid: &ColorGrid, block: &BlockWithPos, turn: Turn) -> Option<(BlockWithPos, usize)>;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RotationSystemKind {
+    #[default]
+    Classic,
+    Srs,
+}
+
+impl RotationSystemKind {
+    pub fn all() -> Vec<RotationSystemKind> {
+        vec![RotationSystemKind::Classic, RotationSystemKind::Srs]
+    }
+
+    pub fn system(&self) -> &'static dyn RotationSystem {
+        match self {
+            RotationSystemKind::Classic => &Classic,
+            RotationSystemKind::Srs => &Srs,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RotationSystemKind::Classic => "classic",
+            RotationSystemKind::Srs => "srs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RotationSystemKind> {
+        Self::all().into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// The original wretris rotation: turn around the pivot cell without kicks.
+pub struct Classic;
+
+impl RotationSystem for Classic {
+    fn rotate(&self, grid: &ColorGrid, block: &BlockWithPos, turn: Turn) -> Option<(BlockWithPos, usize)> {
+        let next = turn.apply(block);
+        if grid.fits(&next) {
+            Some((next, 0))
+        } else {
+            None
+        }
+    }
+}
+
+// Kick tables as published for the guideline, with y pointing up.
+const JLSTZ_KICKS: [[Pos; 5]; 8] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+const I_KICKS: [[Pos; 5]; 8] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // 0 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // R -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // R -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // 2 -> R
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // 2 -> L
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // L -> 2
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // L -> 0
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // 0 -> L
+];
+
+// The guideline has no 180° turn; these are the widely used TETR.IO SRS+ kicks.
+const FLIP_KICKS: [[Pos; 6]; 4] = [
+    [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)],   // 0 -> 2
+    [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],     // R -> L
+    [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)], // 2 -> 0
+    [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)],  // L -> R
+];
+
+// The I piece turns around the centre of its 4x4 box rather than a cell,
+// so its pivot drifts by these amounts in each state.
+const I_OFFSETS: [Pos; 4] = [(0, 0), (0, 1), (-1, 1), (-1, 0)];
+
+/// Guideline Super Rotation System.
+pub struct Srs;
+
+impl Srs {
+    pub fn kicks(shape: Shape, from: Rotation, turn: Turn) -> Vec<Pos> {
+        let from = from as usize;
+        let kicks: &[Pos] = match (turn, shape) {
+            (_, Shape::O) => &[(0, 0)],
+            (Turn::Flip, _) => &FLIP_KICKS[from],
+            (Turn::Clockwise, Shape::I) => &I_KICKS[2 * from],
+            (Turn::CounterClockwise, Shape::I) => &I_KICKS[(2 * from + 7) % 8],
+            (Turn::Clockwise, _) => &JLSTZ_KICKS[2 * from],
+            (Turn::CounterClockwise, _) => &JLSTZ_KICKS[(2 * from + 7) % 8],
+        };
+        kicks.iter().map(|(x, y)| (*x, -*y)).collect()
+    }
+}
+
+impl RotationSystem for Srs {
+    fn rotate(&self, grid: &ColorGrid, block: &BlockWithPos, turn: Turn) -> Option<(BlockWithPos, usize)> {
+        let shape = block.block.shape();
+        let from = block.block.rotation();
+        let mut next = turn.apply(block);
+        if shape == Shape::I {
+            let before = I_OFFSETS[from as usize];
+            let after = I_OFFSETS[next.block.rotation() as usize];
+            next.pos = (next.pos.0 + after.0 - before.0, next.pos.1 + after.1 - before.1);
+        }
+        Self::kicks(shape, from, turn).into_iter().enumerate().find_map(|(idx, (dx, dy))| {
+            let kicked = BlockWithPos::from(next.block.clone(), (next.pos.0 + dx, next.pos.1 + dy));
+            if grid.fits(&kicked) {
+                Some((kicked, idx))
+            } else {
+                None
+            }
+        })
+    }
+}
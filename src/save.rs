@@ -0,0 +1,187 @@
+use crate::block::{ BColor, Rotation, Shape };
+use crate::config::Config;
+use crate::engine::Input;
+use crate::pos::Pos;
+use crate::replay::{ decode_inputs, encode_inputs };
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const HEADER: &str = "wretris-save";
+/// Bumped whenever the format or the engine changes so that old saves would resume wrongly.
+pub const VERSION: u32 = 1;
+
+const SHAPES: [Shape; 7] = [Shape::I, Shape::O, Shape::T, Shape::S, Shape::Z, Shape::J, Shape::L];
+const ROTATIONS: [Rotation; 4] = [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270];
+
+/// A game in progress, detailed enough to go on exactly where it was left. The queue
+/// and the randomizer aren't stored: they are dealt again from the seed up to `drawn`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Save {
+    /// The rules of the game, with its seed.
+    pub config: Config,
+    /// Locked cells of the whole grid row by row, buffer rows first.
+    pub cells: Vec<Option<BColor>>,
+    pub shape: Shape,
+    pub rotation: Rotation,
+    pub pos: Pos,
+    pub hold: Option<Shape>,
+    pub can_hold: bool,
+    /// Blocks dealt from the randomizer, the queue included.
+    pub drawn: usize,
+    pub points: u64,
+    pub combo: Option<usize>,
+    pub back_to_back: bool,
+    pub lines: usize,
+    pub elapsed: u64,
+    /// The lock delay timer: elapsed milliseconds, resets used and the lowest row reached.
+    pub lock_delay: (u64, usize, i32),
+    /// Part of a row the block has fallen towards the next.
+    pub fall: f64,
+    pub soft_drop: bool,
+    /// Inputs so far, so the replay of the game still covers all of it.
+    pub inputs: Vec<(u64, Input)>,
+}
+
+fn letter(shape: Shape) -> char {
+    ['I', 'O', 'T', 'S', 'Z', 'J', 'L'][shape as usize]
+}
+
+fn shape(letter: char) -> Option<Shape> {
+    SHAPES.iter().copied().find(|shape| self::letter(*shape) == letter)
+}
+
+/// Locked cells are written as the letter of their piece, or `G` for garbage.
+fn cell_letter(color: BColor) -> char {
+    SHAPES.iter().find(|shape| shape.to_color() == color).map_or('G', |shape| letter(*shape))
+}
+
+fn cell(letter: char) -> Option<BColor> {
+    match letter {
+        'G' => Some(BColor::GARBAGE),
+        letter => shape(letter).map(|shape| shape.to_color()),
+    }
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on { "on" } else { "off" }
+}
+
+fn field<T: FromStr>(fields: &HashMap<&str, &str>, key: &str) -> Result<T, String> {
+    fields.get(key).and_then(|value| value.parse().ok()).ok_or_else(|| format!("The save has no valid {}", key))
+}
+
+fn flag(fields: &HashMap<&str, &str>, key: &str) -> Result<bool, String> {
+    match fields.get(key) {
+        Some(&"on") => Ok(true),
+        Some(&"off") => Ok(false),
+        _ => Err(format!("The save has no valid {}", key)),
+    }
+}
+
+fn optional_shape(fields: &HashMap<&str, &str>, key: &str) -> Result<Option<Shape>, String> {
+    match fields.get(key) {
+        Some(&"-") => Ok(None),
+        Some(value) if value.chars().count() == 1 => shape(value.chars().next().unwrap()).map(Some).ok_or_else(|| format!("Bad {} {}", key, value)),
+        _ => Err(format!("The save has no valid {}", key)),
+    }
+}
+
+impl Save {
+    /// Writes the save as text: a header with the version, the config as a query, the
+    /// state as a query, one line of cells per row and the inputs.
+    pub fn encode(&self) -> String {
+        let (lock_elapsed, resets, lowest) = self.lock_delay;
+        let state = [
+            format!("shape={}", letter(self.shape)),
+            format!("rotation={}", self.rotation as u8),
+            format!("x={}", self.pos.0),
+            format!("y={}", self.pos.1),
+            format!("hold={}", self.hold.map_or('-', letter)),
+            format!("can_hold={}", on_off(self.can_hold)),
+            format!("drawn={}", self.drawn),
+            format!("points={}", self.points),
+            format!("combo={}", self.combo.map_or("-".to_string(), |combo| combo.to_string())),
+            format!("back_to_back={}", on_off(self.back_to_back)),
+            format!("lines={}", self.lines),
+            format!("elapsed={}", self.elapsed),
+            format!("lock_elapsed={}", lock_elapsed),
+            format!("lock_resets={}", resets),
+            format!("lowest={}", lowest),
+            format!("fall={}", self.fall),
+            format!("soft_drop={}", on_off(self.soft_drop)),
+        ];
+        let rows: Vec<String> = self
+            .cells
+            .chunks(self.config.width.max(1))
+            .map(|row| row.iter().map(|cell| cell.map_or('.', cell_letter)).collect())
+            .collect();
+        format!(
+            "{} {}\n{}\n{}\n{}\n{}\n",
+            HEADER,
+            VERSION,
+            self.config.to_query(),
+            state.join("&"),
+            rows.join("\n"),
+            encode_inputs(&self.inputs),
+        )
+    }
+
+    pub fn decode(text: &str) -> Result<Save, String> {
+        let mut lines = text.lines();
+        match lines.next().map(|line| line.split_whitespace().collect::<Vec<_>>()).as_deref() {
+            Some([HEADER, version]) if *version == VERSION.to_string() => {}
+            Some([HEADER, version]) => return Err(format!("Saves of version {} can't be resumed", version)),
+            _ => return Err("Not a saved game".to_string()),
+        }
+        let config = Config::from_query(lines.next().unwrap_or(""));
+        if config.seed.is_none() {
+            return Err("The save has no seed".to_string());
+        }
+        let fields: HashMap<&str, &str> = lines
+            .next()
+            .unwrap_or("")
+            .split('&')
+            .filter_map(|pair| {
+                let mut kv = pair.splitn(2, '=');
+                Some((kv.next()?, kv.next()?))
+            })
+            .collect();
+        let mut cells = Vec::with_capacity(config.width * (config.height + config.buffer));
+        for _ in 0..config.height + config.buffer {
+            let row = lines.next().ok_or("The save misses rows")?;
+            if row.chars().count() != config.width {
+                return Err(format!("Bad row {}", row));
+            }
+            for c in row.chars() {
+                cells.push(match c {
+                    '.' => None,
+                    c => Some(cell(c).ok_or_else(|| format!("Bad row {}", row))?),
+                });
+            }
+        }
+        let rotation: usize = field(&fields, "rotation")?;
+        Ok(Save {
+            cells,
+            shape: optional_shape(&fields, "shape")?.ok_or("The save has no block")?,
+            rotation: *ROTATIONS.get(rotation).ok_or("Bad rotation")?,
+            pos: (field(&fields, "x")?, field(&fields, "y")?),
+            hold: optional_shape(&fields, "hold")?,
+            can_hold: flag(&fields, "can_hold")?,
+            drawn: field(&fields, "drawn")?,
+            points: field(&fields, "points")?,
+            combo: match fields.get("combo") {
+                Some(&"-") => None,
+                _ => Some(field(&fields, "combo")?),
+            },
+            back_to_back: flag(&fields, "back_to_back")?,
+            lines: field(&fields, "lines")?,
+            elapsed: field(&fields, "elapsed")?,
+            lock_delay: (field(&fields, "lock_elapsed")?, field(&fields, "lock_resets")?, field(&fields, "lowest")?),
+            fall: field(&fields, "fall")?,
+            soft_drop: flag(&fields, "soft_drop")?,
+            inputs: decode_inputs(lines.next().unwrap_or(""))?,
+            config,
+        })
+    }
+}
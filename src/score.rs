@@ -1,4 +1,4 @@
-use crate::numbers::padding;
+use crate::engine::Snapshot;
 
 use cursive::{
     theme::{ Color, ColorStyle,},
@@ -6,18 +6,13 @@ use cursive::{
     Printer,
 };
 
-pub struct Score {
-    score: usize,
-    perfect: usize,
-}
+const WIDTH: usize = 20;
+const HEIGHT: usize = 3;
 
-impl Default for Score {
-    fn default() -> Self {
-        Self {
-            score: 0,
-            perfect: 40,
-        }
-    }
+/// Shows the progress the game mode reports, such as lines, level and points.
+#[derive(Default)]
+pub struct Score {
+    hud: Vec<String>,
 }
 
 impl Score {
@@ -25,20 +20,8 @@ impl Score {
         Self::default()
     }
 
-    pub fn add(&mut self, s: usize) {
-        self.score += s
-    }
-
-    pub fn is_gameover(&self) -> bool {
-        self.score >= self.perfect
-    }
-
-    pub fn renew(&mut self) {
-        self.score = 0;
-    }
-
-    fn num2str(&self) -> String {
-        format!(" Lines: {} / {} ", padding(self.score, 2), self.perfect)
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.hud = snapshot.hud.clone();
     }
 }
 
@@ -46,12 +29,13 @@ impl View for Score {
     fn draw(&self, printer: &Printer) {
         let color_style = ColorStyle::new(Color::Rgb(50, 79, 54), Color::Rgb(255,255,255));
         printer.with_color(color_style, |printer| {
-            printer.print((0, 0), &self.num2str());
+            for (y, line) in self.hud.iter().take(HEIGHT).enumerate() {
+                printer.print((0, y), &format!(" {:<width$}", line, width = WIDTH - 1));
+            }
         });
     }
 
     fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
-        let line = self.num2str();
-        cursive::Vec2::new(line.len() + 3, 1)
+        cursive::Vec2::new(WIDTH + 3, HEIGHT)
     }
 }
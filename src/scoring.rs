@@ -0,0 +1,137 @@
+use crate::tspin::TSpin;
+
+const LINE_POINTS: [u64; 5] = [0, 100, 300, 500, 800];
+const MINI_POINTS: [u64; 3] = [100, 200, 400];
+const TSPIN_POINTS: [u64; 4] = [400, 800, 1200, 1600];
+const LINE_NAMES: [&str; 5] = ["", "Single", "Double", "Triple", "Tetris"];
+const PERFECT_CLEAR_POINTS: [u64; 5] = [0, 800, 1200, 1800, 2000];
+const BACK_TO_BACK_PERFECT_TETRIS: u64 = 3200;
+const COMBO_POINTS: u64 = 50;
+
+/// Points awarded for locking one piece.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Award {
+    pub lines: usize,
+    pub spin: TSpin,
+    pub points: u64,
+    /// Line clears in a row before this one; 0 when it starts a new combo.
+    pub combo: usize,
+    pub back_to_back: bool,
+    pub perfect_clear: bool,
+}
+
+impl Award {
+    /// Describes the clear for the player, e.g. "Back-to-Back", "T-Spin Double", "Combo 2".
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = Vec::new();
+        if self.back_to_back {
+            labels.push("Back-to-Back".to_string());
+        }
+        let spin = match self.spin {
+            TSpin::None => "",
+            TSpin::Mini => "T-Spin Mini",
+            TSpin::Full => "T-Spin",
+        };
+        let clear = format!("{} {}", spin, LINE_NAMES[self.lines]).trim().to_string();
+        if !clear.is_empty() {
+            labels.push(clear);
+        }
+        if self.combo > 0 {
+            labels.push(format!("Combo {}", self.combo));
+        }
+        if self.perfect_clear {
+            labels.push("Perfect Clear".to_string());
+        }
+        labels
+    }
+}
+
+/// Guideline scoring: line clears and T-spins scaled by level, plus drops,
+/// combos, back-to-back bonuses and perfect clears.
+#[derive(Clone, Debug, Default)]
+pub struct Scoring {
+    points: u64,
+    combo: Option<usize>,
+    back_to_back: bool,
+}
+
+impl Scoring {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continues scoring a game from its points, combo and back-to-back chain.
+    pub fn resume(points: u64, combo: Option<usize>, back_to_back: bool) -> Self {
+        Self {
+            points,
+            combo,
+            back_to_back,
+        }
+    }
+
+    pub fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// Consecutive line clears so far, if the last piece cleared any.
+    pub fn combo(&self) -> Option<usize> {
+        self.combo
+    }
+
+    /// Whether the next difficult clear gets the back-to-back bonus.
+    pub fn is_back_to_back(&self) -> bool {
+        self.back_to_back
+    }
+
+    pub fn soft_drop(&mut self, cells: usize) {
+        self.points += cells as u64;
+    }
+
+    pub fn hard_drop(&mut self, cells: usize) {
+        self.points += 2 * cells as u64;
+    }
+
+    /// Scores a locked piece that cleared `lines` at `level`, possibly with a T-spin.
+    pub fn lock(&mut self, lines: usize, spin: TSpin, level: usize, perfect_clear: bool) -> Award {
+        let level = level.max(1) as u64;
+        let lines = lines.min(4);
+        let base = match spin {
+            TSpin::None => LINE_POINTS[lines],
+            TSpin::Mini => MINI_POINTS[lines.min(2)],
+            TSpin::Full => TSPIN_POINTS[lines.min(3)],
+        };
+        if lines == 0 {
+            // Spins without lines neither extend nor break a back-to-back chain.
+            self.combo = None;
+            self.points += base * level;
+            return Award { spin, points: base * level, ..Award::default() };
+        }
+        // Tetrises and spins that clear lines are the difficult clears chaining back-to-back.
+        let difficult = lines == 4 || spin != TSpin::None;
+        let back_to_back = difficult && self.back_to_back;
+        let mut points = base * level;
+        if back_to_back {
+            points += points / 2;
+        }
+        let combo = self.combo.map_or(0, |combo| combo + 1);
+        points += COMBO_POINTS * combo as u64 * level;
+        if perfect_clear {
+            points += if back_to_back && lines == 4 {
+                BACK_TO_BACK_PERFECT_TETRIS
+            } else {
+                PERFECT_CLEAR_POINTS[lines]
+            } * level;
+        }
+        self.points += points;
+        self.combo = Some(combo);
+        self.back_to_back = difficult;
+        Award {
+            lines,
+            spin,
+            points,
+            combo,
+            back_to_back,
+            perfect_clear,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::block::{ Block, BlockWithPos };
+use crate::color_grid::{ ColorGrid, Move };
+use crate::engine::Input;
+use crate::lrd::LR;
+use crate::pos::Pos;
+
+use std::collections::{ HashSet, VecDeque };
+
+/// Moves tried from every position, cheapest first.
+const MOVES: [Input; 6] = [
+    Input::Left,
+    Input::Right,
+    Input::RotateClockwise,
+    Input::RotateCounterClockwise,
+    Input::FlipTurn,
+    Input::SonicDrop,
+];
+
+/// A place the active block can lock at, and how to get it there.
+#[derive(Clone, Debug)]
+pub struct Placement {
+    pub block: BlockWithPos,
+    /// Moves from where the block is now. A hard drop then locks it.
+    pub inputs: Vec<Input>,
+    /// Whether the block turned last, as a T-spin needs.
+    pub spin: bool,
+}
+
+impl Placement {
+    /// Sorted cells the block takes, the same for every rotation that covers them.
+    pub fn cells(&self) -> Vec<Pos> {
+        let mut cells = self.block.cells();
+        cells.sort();
+        cells
+    }
+}
+
+fn apply(grid: &mut ColorGrid, input: Input) -> bool {
+    match input {
+        Input::Left => grid.handle_lr(LR::Left, false),
+        Input::Right => grid.handle_lr(LR::Right, false),
+        Input::RotateClockwise => grid.rotate(true),
+        Input::RotateCounterClockwise => grid.rotate(false),
+        Input::FlipTurn => grid.flip_turn(),
+        Input::SonicDrop => grid.hard_drop() > 0,
+        _ => false,
+    }
+}
+
+/// Every place the active block of `grid` can lock at, each by the fewest moves. A place
+/// reached both with and without a final turn is listed for each. With `sonic` the block
+/// drops to the stack after every move, as it does at 20 G.
+pub fn placements(grid: &ColorGrid, sonic: bool) -> Vec<Placement> {
+    let mut grid = grid.clone();
+    let start = grid.block.clone();
+    let mut seen = HashSet::new();
+    let mut found = HashSet::new();
+    let mut placements = Vec::new();
+    let mut queue = VecDeque::new();
+    seen.insert((start.pos, start.block.rotation() as u8, false));
+    queue.push_back((start, Vec::new(), false));
+    while let Some((block, inputs, spin)) = queue.pop_front() {
+        grid.block = block.clone();
+        grid.hard_drop();
+        let landed = Placement {
+            block: grid.block.clone(),
+            inputs: inputs.clone(),
+            spin: spin && grid.block.pos == block.pos,
+        };
+        if found.insert((landed.cells(), landed.spin)) {
+            placements.push(landed);
+        }
+        for input in MOVES {
+            grid.block = block.clone();
+            if !apply(&mut grid, input) {
+                continue;
+            }
+            let turned = matches!(grid.last_move(), Some(Move::Turn { .. }));
+            let fell = sonic && grid.hard_drop() > 0;
+            let spin = turned && !fell;
+            let moved = grid.block.clone();
+            if seen.insert((moved.pos, moved.block.rotation() as u8, spin)) {
+                let mut next = inputs.clone();
+                next.push(input);
+                queue.push_back((moved, next, spin));
+            }
+        }
+    }
+    placements
+}
+
+/// The fewest moves that take the active block of `grid` onto `target`, preferring a final
+/// turn when `spin` asks for one.
+pub fn path(grid: &ColorGrid, target: &BlockWithPos, spin: bool, sonic: bool) -> Option<Vec<Input>> {
+    let mut cells = target.cells();
+    cells.sort();
+    let placements: Vec<Placement> = placements(grid, sonic).into_iter().filter(|placement| placement.cells() == cells).collect();
+    placements
+        .iter()
+        .find(|placement| placement.spin == spin)
+        .or_else(|| placements.first())
+        .map(|placement| placement.inputs.clone())
+}
+
+/// `grid` with `block` spawned in place of the active one, as after a hold.
+pub fn respawned(grid: &ColorGrid, block: Block) -> ColorGrid {
+    let mut grid = grid.clone();
+    grid.insert(block);
+    grid
+}
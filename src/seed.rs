@@ -0,0 +1,46 @@
+use crate::engine::Snapshot;
+use cursive::{
+    theme::{ Color, ColorStyle,},
+    View,
+    Printer,
+};
+
+pub struct Seed {
+    seed: u64,
+}
+
+impl Default for Seed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Seed {
+    pub fn new() -> Self {
+        Self {
+            seed: 0,
+        }
+    }
+
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.seed = snapshot.seed;
+    }
+
+    fn seed2str(&self) -> String {
+        format!(" Seed {:020} ", self.seed)
+    }
+}
+
+impl View for Seed {
+    fn draw(&self, printer: &Printer) {
+        let color_style = ColorStyle::new(Color::Rgb(50, 79, 54), Color::Rgb(255,255,255));
+        printer.with_color(color_style, |printer| {
+            printer.print((0, 0), &self.seed2str());
+        });
+    }
+
+    fn required_size(&mut self, _: cursive::Vec2) -> cursive::Vec2 {
+        let line = self.seed2str();
+        cursive::Vec2::new(line.len(), 1)
+    }
+}
@@ -0,0 +1,174 @@
+use crate::keymap::{ Action, Key, Keymap };
+use crate::storage;
+use crate::tetris::Tetris;
+
+use cursive::{
+    Cursive,
+    event::{ Callback, Event, EventResult, Key as CursiveKey },
+    theme::{ BaseColor, Color, ColorStyle },
+    view::{ Nameable, Resizable },
+    views::{ Dialog, EditView, SelectView },
+    Printer, View, Vec2,
+};
+
+/// The bindable key of a cursive event.
+pub fn key(event: &Event) -> Option<Key> {
+    match event {
+        Event::Char(c) => Some(Key::char(*c)),
+        Event::Key(CursiveKey::Left) => Some(Key::Left),
+        Event::Key(CursiveKey::Right) => Some(Key::Right),
+        Event::Key(CursiveKey::Up) => Some(Key::Up),
+        Event::Key(CursiveKey::Down) => Some(Key::Down),
+        Event::Key(CursiveKey::Enter) => Some(Key::Enter),
+        _ => None,
+    }
+}
+
+fn record(profile: &str) -> String {
+    format!("keymap.{}", profile)
+}
+
+/// Reads a saved key binding profile.
+pub fn load_profile(profile: &str) -> Result<Keymap, String> {
+    match storage::load(&record(profile)) {
+        Some(text) => Keymap::from_profile(&text),
+        None => Err(format!("There is no profile {}", profile)),
+    }
+}
+
+pub fn save_profile(profile: &str, keymap: &Keymap) -> Result<(), String> {
+    storage::save(&record(profile), &keymap.to_profile())
+}
+
+/// Lists every action with its keys. Submitting an action waits for a key to add to it.
+/// Closing resumes the game when `resume` is set.
+pub fn settings(keymap: &Keymap, resume: bool) -> Dialog {
+    let mut select = SelectView::new();
+    fill(&mut select, keymap);
+    let select = select.on_submit(|s, action: &Action| s.add_layer(Capture { action: *action }));
+    Dialog::around(select.with_name("bindings"))
+        .title("Key bindings")
+        .button("Defaults", |s| change(s, |keymap| *keymap = Keymap::default()))
+        .button("Save", |s| {
+            let profile = s.call_on_name("retris", |t: &mut Tetris| t.profile().to_string()).unwrap_or_default();
+            s.add_layer(profile_dialog("Save profile", profile, |s, profile| {
+                let keymap = s.call_on_name("retris", |t: &mut Tetris| t.keymap().clone()).unwrap_or_default();
+                match save_profile(profile, &keymap) {
+                    Ok(()) => {
+                        s.call_on_name("retris", |t: &mut Tetris| t.set_profile(profile));
+                    }
+                    Err(error) => s.add_layer(Dialog::info(error)),
+                }
+            }));
+        })
+        .button("Load", |s| {
+            let profile = s.call_on_name("retris", |t: &mut Tetris| t.profile().to_string()).unwrap_or_default();
+            s.add_layer(profile_dialog("Load profile", profile, |s, profile| match load_profile(profile) {
+                Ok(keymap) => {
+                    s.call_on_name("retris", |t: &mut Tetris| t.set_profile(profile));
+                    change(s, |current| *current = keymap);
+                }
+                Err(error) => s.add_layer(Dialog::info(error)),
+            }));
+        })
+        .button("Close", move |s| {
+            s.pop_layer();
+            s.call_on_name("retris", |t: &mut Tetris| t.close_dialog(resume));
+        })
+}
+
+fn fill(select: &mut SelectView<Action>, keymap: &Keymap) {
+    let selected = select.selected_id();
+    select.clear();
+    for action in Action::all() {
+        select.add_item(format!("{:24} {}", action.description(), keymap.label(action)), action);
+    }
+    if let Some(selected) = selected {
+        select.set_selection(selected);
+    }
+}
+
+/// Applies `update` to the keymap of the game and shows the result. Changes that leave
+/// no key to open this screen again are refused.
+fn change(s: &mut Cursive, update: impl FnOnce(&mut Keymap)) {
+    let mut keymap = match s.call_on_name("retris", |t: &mut Tetris| t.keymap().clone()) {
+        Some(keymap) => keymap,
+        None => return,
+    };
+    update(&mut keymap);
+    if keymap.keys(Action::Settings).is_empty() {
+        s.add_layer(Dialog::info("Keep a key for the key bindings."));
+        return;
+    }
+    s.call_on_name("retris", |t: &mut Tetris| t.set_keymap(keymap.clone()));
+    s.call_on_name("bindings", |select: &mut SelectView<Action>| fill(select, &keymap));
+}
+
+/// Binds `key` to `action`, asking first when another action has the key.
+fn bind(s: &mut Cursive, action: Action, key: Key) {
+    let mut bound = match s.call_on_name("retris", |t: &mut Tetris| t.keymap().clone()) {
+        Some(keymap) => keymap,
+        None => return,
+    };
+    match bound.bind(action, key) {
+        Ok(()) => change(s, |keymap| *keymap = bound),
+        Err(conflict) => s.add_layer(
+            Dialog::text(format!("{}.\nMove it to {}?", conflict, action.description()))
+                .title("Conflict")
+                .button("Move", move |s| {
+                    s.pop_layer();
+                    change(s, |keymap| {
+                        keymap.rebind(action, key);
+                    });
+                })
+                .dismiss_button("Cancel"),
+        ),
+    }
+}
+
+fn profile_dialog(title: &str, profile: String, on_submit: fn(&mut Cursive, &str)) -> Dialog {
+    let edit = EditView::new().content(profile).on_submit(move |s, profile| {
+        s.pop_layer();
+        on_submit(s, profile);
+    });
+    Dialog::around(edit.fixed_width(20)).title(title).dismiss_button("Cancel")
+}
+
+/// Waits for the key to bind to an action.
+struct Capture {
+    action: Action,
+}
+
+impl View for Capture {
+    fn draw(&self, printer: &Printer) {
+        printer.with_color(ColorStyle::new(Color::Dark(BaseColor::White), Color::Dark(BaseColor::Blue)), |printer| {
+            printer.print((0, 0), &format!(" {:38} ", format!("Press a key for {}", self.action.description())));
+            printer.print((0, 1), &format!(" {:38} ", "backspace: clear keys, esc: cancel"));
+        });
+    }
+
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        Vec2::new(40, 2)
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        let action = self.action;
+        let callback: Box<dyn Fn(&mut Cursive)> = match event {
+            Event::Key(CursiveKey::Esc) => Box::new(|s| {
+                s.pop_layer();
+            }),
+            Event::Key(CursiveKey::Backspace) => Box::new(move |s| {
+                s.pop_layer();
+                change(s, |keymap| keymap.clear(action));
+            }),
+            _ => match key(&event) {
+                Some(key) => Box::new(move |s| {
+                    s.pop_layer();
+                    bind(s, action, key);
+                }),
+                None => return EventResult::Ignored,
+            },
+        };
+        EventResult::Consumed(Some(Callback::from_fn(callback)))
+    }
+}
@@ -0,0 +1,171 @@
+//! Carries online versus messages to the relay server over a WebSocket: a tungstenite
+//! connection natively, and the browser's `WebSocket` on wasm.
+
+use crate::net::{ ClientMessage, ServerMessage };
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{ net::TcpStream, thread, time::Duration };
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::{ self, Receiver, Sender, TryRecvError };
+#[cfg(not(target_arch = "wasm32"))]
+use tungstenite::{ stream::MaybeTlsStream, Message, WebSocket };
+
+#[cfg(target_arch = "wasm32")]
+use std::{ cell::RefCell, collections::VecDeque, rc::Rc };
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{ closure::Closure, JsCast };
+#[cfg(target_arch = "wasm32")]
+use web_sys::{ CloseEvent, MessageEvent, WebSocket as BrowserSocket };
+
+/// How long the connection thread waits for a message before it sends what is queued.
+#[cfg(not(target_arch = "wasm32"))]
+const POLL: Duration = Duration::from_millis(5);
+
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Socket {
+    outgoing: Sender<String>,
+    /// Messages from the server, read on a thread of their own so the game never waits.
+    incoming: Receiver<Result<String, String>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Socket {
+    /// Connects to the server at `url`, e.g. `ws://localhost:9000`.
+    pub fn connect(url: &str) -> Result<Socket, String> {
+        let (mut socket, _) = tungstenite::connect(url).map_err(|error| format!("Cannot reach the server {}: {}", url, error))?;
+        if let MaybeTlsStream::Plain(stream) = socket.get_mut() {
+            stream.set_read_timeout(Some(POLL)).map_err(|error| format!("Cannot reach the server {}: {}", url, error))?;
+        }
+        let (outgoing, queued) = mpsc::channel();
+        let (arrived, incoming) = mpsc::channel();
+        thread::spawn(move || run(socket, queued, arrived));
+        Ok(Socket { outgoing, incoming })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> Result<(), String> {
+        self.outgoing.send(message.encode()).map_err(|_| "Lost the connection to the server".to_string())
+    }
+
+    /// Messages that arrived since the last call, oldest first.
+    pub fn receive(&mut self) -> Vec<Result<ServerMessage, String>> {
+        let mut messages = Vec::new();
+        loop {
+            match self.incoming.try_recv() {
+                Ok(Ok(text)) => messages.push(ServerMessage::decode(&text)),
+                Ok(Err(error)) => messages.push(Err(error)),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    messages.push(Err("Lost the connection to the server".to_string()));
+                    break;
+                }
+            }
+        }
+        messages
+    }
+}
+
+/// Writes what the game queued and reads what the server sent until either side hangs up.
+#[cfg(not(target_arch = "wasm32"))]
+fn run(mut socket: WebSocket<MaybeTlsStream<TcpStream>>, queued: Receiver<String>, arrived: Sender<Result<String, String>>) {
+    loop {
+        loop {
+            match queued.try_recv() {
+                Ok(text) => {
+                    if socket.send(Message::Text(text)).is_err() {
+                        return;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    socket.close(None).ok();
+                    socket.flush().ok();
+                    return;
+                }
+            }
+        }
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                if arrived.send(Ok(text)).is_err() {
+                    return;
+                }
+            }
+            Ok(Message::Close(_)) => {
+                arrived.send(Err("The server closed the connection".to_string())).ok();
+                return;
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(error)) if matches!(error.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(error) => {
+                arrived.send(Err(format!("Lost the connection to the server: {}", error))).ok();
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub struct Socket {
+    socket: BrowserSocket,
+    /// Messages the server sent, and connection errors.
+    inbox: Rc<RefCell<VecDeque<Result<String, String>>>>,
+    /// Messages queued while the connection opens.
+    pending: Vec<String>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut(CloseEvent)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Socket {
+    /// Connects to the server at `url`, e.g. `ws://localhost:9000`.
+    pub fn connect(url: &str) -> Result<Socket, String> {
+        let socket = BrowserSocket::new(url).map_err(|_| format!("Cannot reach the server {}", url))?;
+        let inbox = Rc::new(RefCell::new(VecDeque::new()));
+        let queued = inbox.clone();
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            if let Some(text) = event.data().as_string() {
+                queued.borrow_mut().push_back(Ok(text));
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+        let queued = inbox.clone();
+        let on_close = Closure::wrap(Box::new(move |_: CloseEvent| {
+            queued.borrow_mut().push_back(Err("Lost the connection to the server".to_string()));
+        }) as Box<dyn FnMut(CloseEvent)>);
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+        Ok(Socket { socket, inbox, pending: Vec::new(), _on_message: on_message, _on_close: on_close })
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) -> Result<(), String> {
+        self.pending.push(message.encode());
+        self.flush()
+    }
+
+    /// Messages that arrived since the last call, oldest first.
+    pub fn receive(&mut self) -> Vec<Result<ServerMessage, String>> {
+        let mut messages = Vec::new();
+        if let Err(error) = self.flush() {
+            messages.push(Err(error));
+        }
+        messages.extend(self.inbox.borrow_mut().drain(..).map(|text| text.and_then(|text| ServerMessage::decode(&text))));
+        messages
+    }
+
+    /// Sends the queued messages once the connection is open.
+    fn flush(&mut self) -> Result<(), String> {
+        if self.socket.ready_state() != BrowserSocket::OPEN {
+            return Ok(());
+        }
+        for text in self.pending.drain(..) {
+            self.socket.send_with_str(&text).map_err(|_| "Lost the connection to the server".to_string())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Socket {
+    fn drop(&mut self) {
+        self.socket.set_onclose(None);
+        self.socket.close().ok();
+    }
+}
@@ -0,0 +1,299 @@
+use crate::board::Board;
+use crate::config::Config;
+use crate::engine::{ Engine, Input, Snapshot };
+use crate::handling::Handling;
+use crate::hold::Hold;
+use crate::keymap::{ Action as KeyAction, Keymap };
+use crate::meter::Meter;
+use crate::queue::Queue;
+use crate::score::Score;
+use crate::settings;
+use crate::utils;
+use crate::versus::{ State, Versus, PLAYERS };
+
+use cursive::{
+    event::{ Callback, Event, EventResult },
+    theme::{ Color, ColorStyle },
+    views::Dialog,
+    Printer, Vec2, View,
+};
+use std::cmp::max;
+
+/// Keys of player `player`, from the profile `versus-1` or `versus-2` if there is one.
+fn keymap(player: usize) -> Keymap {
+    settings::load_profile(&format!("versus-{}", player + 1)).unwrap_or_else(|_| Keymap::versus(player))
+}
+
+/// The board of one player with the views around it.
+pub(crate) struct Side {
+    keymap: Keymap,
+    /// DAS and ARR handling of the held keys, once key releases are tracked.
+    handling: Option<Handling>,
+    board: Board,
+    meter: Meter,
+    queue: Queue,
+    score: Score,
+    hold: Hold,
+    board_size: Vec2,
+    meter_size: Vec2,
+    queue_size: Vec2,
+    score_size: Vec2,
+    hold_size: Vec2,
+}
+
+impl Side {
+    pub(crate) fn new(config: &Config, keymap: Keymap) -> Self {
+        let mut board = Board::new(config.width, config.height);
+        let mut meter = Meter::new(config.height);
+        let mut queue = Queue::new(config.previews, config.big_preview);
+        let mut score = Score::new();
+        let mut hold = Hold::new();
+        Side {
+            keymap,
+            handling: None,
+            board_size: board.required_size(Vec2::new(0,0)),
+            meter_size: meter.required_size(Vec2::new(0,0)),
+            queue_size: queue.required_size(Vec2::new(0,0)),
+            score_size: score.required_size(Vec2::new(0,0)),
+            hold_size: hold.required_size(Vec2::new(0,0)),
+            board,
+            meter,
+            queue,
+            score,
+            hold,
+        }
+    }
+
+    pub(crate) fn update(&mut self, snapshot: &Snapshot) {
+        self.board.update(snapshot);
+        self.meter.update(snapshot);
+        self.queue.update(snapshot);
+        self.score.update(snapshot);
+        self.hold.update(snapshot);
+    }
+
+    pub(crate) fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub(crate) fn track_key_releases(&mut self, config: &Config) {
+        self.handling = Some(Handling::new(config.das, config.arr));
+    }
+
+    pub(crate) fn press(&mut self, input: Input) -> Vec<Input> {
+        match &mut self.handling {
+            Some(handling) => handling.press(input),
+            None => vec![input],
+        }
+    }
+
+    pub(crate) fn release(&mut self, input: Input) -> Vec<Input> {
+        match &mut self.handling {
+            Some(handling) => handling.release(input),
+            None => Vec::new(),
+        }
+    }
+
+    /// The moves repeated by held keys over `dt` milliseconds.
+    pub(crate) fn tick(&mut self, dt: u64) -> Vec<Input> {
+        match &mut self.handling {
+            Some(handling) => handling.tick(dt),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn keep_soft_drop(&self, engine: &Engine) -> Vec<Input> {
+        match &self.handling {
+            Some(handling) => handling.keep_soft_drop(engine),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        max(self.score_size.x, self.hold_size.x) + 2 + self.meter_size.x + 1 + self.board_size.x + 2 + self.queue_size.x
+    }
+
+    pub(crate) fn height(&self) -> usize {
+        max(self.board_size.y, self.queue_size.y)
+    }
+
+    pub(crate) fn draw(&self, printer: &Printer) {
+        let meter = max(self.score_size.x, self.hold_size.x) + 2;
+        let column = meter + self.meter_size.x + 1;
+        self.score.draw(&printer.offset((0, 0)));
+        self.hold.draw(&printer.offset((0, self.score_size.y + 2)));
+        self.meter.draw(&printer.offset((meter, 0)));
+        self.board.draw(&printer.offset((column, 0)));
+        self.queue.draw(&printer.offset((column + self.board_size.x + 2, 0)));
+    }
+}
+
+/// Two players side by side at the same keyboard, each with their own keys.
+pub struct SplitScreen {
+    versus: Versus,
+    sides: Vec<Side>,
+    /// Whether the end of the round has been announced.
+    announced: bool,
+    last_refresh: f64,
+}
+
+impl SplitScreen {
+    pub fn new(config: Config) -> Self {
+        let versus = Versus::new(config);
+        let sides = (0..PLAYERS).map(|player| Side::new(versus.config(), keymap(player))).collect();
+        let mut split_screen = SplitScreen {
+            versus,
+            sides,
+            announced: false,
+            last_refresh: utils::now(),
+        };
+        split_screen.update_views();
+        split_screen
+    }
+
+    fn update_views(&mut self) {
+        for (player, side) in self.sides.iter_mut().enumerate() {
+            side.update(&self.versus.engine(player).snapshot());
+        }
+    }
+
+    /// Shows who won once a round is over, with a button to go on.
+    fn announce(&mut self) -> EventResult {
+        self.update_views();
+        let wins = self.versus.wins();
+        let (message, button) = match self.versus.state() {
+            State::Playing => return EventResult::Consumed(None),
+            _ if self.announced => return EventResult::Consumed(None),
+            State::RoundOver { winner: Some(winner) } => {
+                (format!("KO! Player {} wins the round\n{} - {}", winner + 1, wins[0], wins[1]), "Next round")
+            }
+            State::RoundOver { winner: None } => (format!("Double KO, nobody wins the round\n{} - {}", wins[0], wins[1]), "Next round"),
+            State::MatchOver { winner } => {
+                (format!("Player {} wins the match\n{} - {}", winner + 1, wins[0], wins[1]), "Rematch")
+            }
+        };
+        self.announced = true;
+        EventResult::Consumed(Some(Callback::from_fn(move |s| {
+            s.add_layer(Dialog::text(message.clone()).title("Versus").button(button, |s| {
+                s.pop_layer();
+                s.call_on_name("versus", |v: &mut SplitScreen| v.next_round());
+            }));
+        })))
+    }
+
+    pub fn next_round(&mut self) {
+        self.versus.next_round();
+        self.announced = false;
+        self.last_refresh = utils::now();
+        self.update_views();
+    }
+
+    fn on_refresh(&mut self) -> EventResult {
+        let dt = (utils::now() - self.last_refresh).max(0.0) as u64;
+        self.last_refresh += dt as f64;
+        self.versus.tick(dt);
+        for player in 0..PLAYERS {
+            let repeated = self.sides[player].tick(dt);
+            self.apply(player, repeated);
+        }
+        self.announce()
+    }
+
+    /// Switches both players to DAS and ARR handling of held keys. Only call this when
+    /// every key release reaches `on_key_release`.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn track_key_releases(&mut self) {
+        let config = self.versus.config().clone();
+        for side in &mut self.sides {
+            side.track_key_releases(&config);
+        }
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn on_key_release(&mut self, event: Event) {
+        if let Some((player, action)) = self.action(&event) {
+            if let Some(input) = action.input() {
+                let inputs = self.sides[player].release(input);
+                self.apply(player, inputs);
+                self.update_views();
+            }
+        }
+    }
+
+    fn apply(&mut self, player: usize, inputs: Vec<Input>) {
+        for input in inputs {
+            self.versus.handle_input(player, input);
+        }
+        for input in self.sides[player].keep_soft_drop(self.versus.engine(player)) {
+            self.versus.handle_input(player, input);
+        }
+    }
+
+    /// Pauses both games until the dialog is closed.
+    fn pause(&mut self) -> EventResult {
+        if self.versus.state() != State::Playing || self.versus.is_paused() {
+            return EventResult::Ignored;
+        }
+        self.versus.toggle_pause();
+        EventResult::Consumed(Some(Callback::from_fn(|s| {
+            s.add_layer(Dialog::text("Paused").title("Versus").button("Resume", |s| {
+                s.pop_layer();
+                s.call_on_name("versus", |v: &mut SplitScreen| v.resume());
+            }));
+        })))
+    }
+
+    fn resume(&mut self) {
+        if self.versus.is_paused() {
+            self.versus.toggle_pause();
+        }
+        self.last_refresh = utils::now();
+    }
+
+    /// The player whose key `event` is, and what it does.
+    fn action(&self, event: &Event) -> Option<(usize, KeyAction)> {
+        let key = settings::key(event)?;
+        self.sides.iter().enumerate().find_map(|(player, side)| side.keymap.action(key).map(|action| (player, action)))
+    }
+}
+
+impl View for SplitScreen {
+    fn draw(&self, printer: &Printer) {
+        let (x_padding, y_padding) = (5, 2);
+        let wins = self.versus.wins();
+        let mut x = x_padding;
+        for (player, side) in self.sides.iter().enumerate() {
+            let stars = "*".repeat(wins[player]) + &".".repeat(self.versus.rounds_to_win().saturating_sub(wins[player]));
+            let title = format!("Player {}  {}", player + 1, stars);
+            printer.with_color(ColorStyle::new(Color::Rgb(255,255,255), Color::Rgb(50, 79, 54)), |printer| {
+                printer.print((x, 0), &title);
+            });
+            side.draw(&printer.offset((x, y_padding)));
+            x += side.width() + 2 * x_padding;
+        }
+    }
+
+    fn on_event(&mut self, event: Event) -> EventResult {
+        if event == Event::Refresh {
+            return self.on_refresh();
+        }
+        match self.action(&event) {
+            Some((_, KeyAction::Pause)) => self.pause(),
+            Some((player, action)) => match action.input() {
+                Some(input) => {
+                    let inputs = self.sides[player].press(input);
+                    self.apply(player, inputs);
+                    self.announce()
+                }
+                None => EventResult::Ignored,
+            },
+            None => EventResult::Ignored,
+        }
+    }
+
+    fn required_size(&mut self, _constraint: Vec2) -> Vec2 {
+        let width: usize = self.sides.iter().map(|side| side.width() + 10).sum();
+        let height = self.sides.iter().map(Side::height).max().unwrap_or(0);
+        Vec2::new(width + 5, height + 4)
+    }
+}
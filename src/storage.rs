@@ -0,0 +1,65 @@
+//! Small named text records kept between sessions: browser localStorage on wasm and
+//! files in the user's config directory natively.
+
+#[cfg(target_arch = "wasm32")]
+const PREFIX: &str = "wretris.";
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, String> {
+    web_sys::window()
+        .ok_or("no window")?
+        .local_storage()
+        .ok()
+        .flatten()
+        .ok_or_else(|| "localStorage is not available".to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn load(name: &str) -> Option<String> {
+    local_storage().ok()?.get_item(&format!("{}{}", PREFIX, name)).ok().flatten()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, value: &str) -> Result<(), String> {
+    local_storage()?
+        .set_item(&format!("{}{}", PREFIX, name), value)
+        .map_err(|_| format!("cannot save {}", name))
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(name: &str) {
+    if let Ok(storage) = local_storage() {
+        storage.remove_item(&format!("{}{}", PREFIX, name)).ok();
+    }
+}
+
+/// `$XDG_CONFIG_HOME/wretris`, falling back to `~/.config/wretris`.
+#[cfg(not(target_arch = "wasm32"))]
+fn dir() -> Option<std::path::PathBuf> {
+    use std::env::var_os;
+    use std::path::PathBuf;
+    let config = var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("wretris"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(name: &str) -> Option<String> {
+    std::fs::read_to_string(dir()?.join(name)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, value: &str) -> Result<(), String> {
+    let dir = dir().ok_or("no config directory")?;
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(dir.join(name), value))
+        .map_err(|error| format!("cannot save {}: {}", name, error))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(name: &str) {
+    if let Some(dir) = dir() {
+        std::fs::remove_file(dir.join(name)).ok();
+    }
+}
@@ -0,0 +1,389 @@
+//! The Tetris Bot Protocol: JSON messages between a game and a bot such as Cold Clear.
+//! The game describes the board and the queue, asks for suggestions and plays them.
+
+use crate::block::{ BColor, Block, BlockWithPos, Rotation, Shape };
+use crate::engine::{ Engine, GameEvent, Input };
+use crate::level::MAX_GRAVITY;
+use crate::search;
+
+use serde::{ Deserialize, Serialize };
+
+/// Columns of the board bots play on.
+pub const WIDTH: usize = 10;
+/// Rows of the board sent to bots, the bottom row first.
+pub const ROWS: usize = 40;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Piece {
+    I,
+    O,
+    T,
+    L,
+    J,
+    S,
+    Z,
+}
+
+impl Piece {
+    pub fn of(shape: Shape) -> Piece {
+        match shape {
+            Shape::I => Piece::I,
+            Shape::O => Piece::O,
+            Shape::T => Piece::T,
+            Shape::L => Piece::L,
+            Shape::J => Piece::J,
+            Shape::S => Piece::S,
+            Shape::Z => Piece::Z,
+        }
+    }
+
+    pub fn shape(&self) -> Shape {
+        match self {
+            Piece::I => Shape::I,
+            Piece::O => Shape::O,
+            Piece::T => Shape::T,
+            Piece::L => Shape::L,
+            Piece::J => Shape::J,
+            Piece::S => Shape::S,
+            Piece::Z => Shape::Z,
+        }
+    }
+}
+
+/// A taken cell of the board: the piece it came from, or `G` for garbage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cell {
+    I,
+    O,
+    T,
+    L,
+    J,
+    S,
+    Z,
+    G,
+}
+
+impl Cell {
+    fn of(color: BColor) -> Cell {
+        match color {
+            BColor::I => Cell::I,
+            BColor::O => Cell::O,
+            BColor::T => Cell::T,
+            BColor::L => Cell::L,
+            BColor::J => Cell::J,
+            BColor::S => Cell::S,
+            BColor::Z => Cell::Z,
+            _ => Cell::G,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Orientation {
+    fn of(rotation: Rotation) -> Orientation {
+        match rotation {
+            Rotation::R0 => Orientation::North,
+            Rotation::R90 => Orientation::East,
+            Rotation::R180 => Orientation::South,
+            Rotation::R270 => Orientation::West,
+        }
+    }
+
+    fn rotation(&self) -> Rotation {
+        match self {
+            Orientation::North => Rotation::R0,
+            Orientation::East => Rotation::R90,
+            Orientation::South => Rotation::R180,
+            Orientation::West => Rotation::R270,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Spin {
+    None,
+    Mini,
+    Full,
+}
+
+/// Where a piece locks: its rotation center counted from the bottom left cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    #[serde(rename = "type")]
+    pub piece: Piece,
+    pub orientation: Orientation,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Location {
+    /// The location of `block` on a board of `rows` rows in all.
+    pub fn of(block: &BlockWithPos, rows: usize) -> Location {
+        let (x, y) = block.guideline_center();
+        Location {
+            piece: Piece::of(block.block.shape()),
+            orientation: Orientation::of(block.block.rotation()),
+            x,
+            y: rows as i32 - 1 - y,
+        }
+    }
+
+    pub fn block(&self, rows: usize) -> BlockWithPos {
+        let block = Block::with_rotation(self.piece.shape(), self.orientation.rotation());
+        BlockWithPos::from_guideline(block, (self.x, rows as i32 - 1 - self.y))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    pub location: Location,
+    pub spin: Spin,
+}
+
+/// Messages from the game to the bot.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    /// Answers `info` with the standard rules, which the bot accepts with `ready`.
+    Rules,
+    /// Sets up the board to think about. The queue starts with the falling piece.
+    Start {
+        hold: Option<Piece>,
+        queue: Vec<Piece>,
+        combo: u32,
+        back_to_back: bool,
+        board: Vec<Vec<Option<Cell>>>,
+    },
+    Stop,
+    Suggest,
+    Play {
+        #[serde(rename = "move")]
+        mv: Move,
+    },
+    NewPiece { piece: Piece },
+    Quit,
+}
+
+/// Messages from the bot to the game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Info {
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        version: String,
+        #[serde(default)]
+        author: String,
+        #[serde(default)]
+        features: Vec<String>,
+    },
+    Ready,
+    Error { reason: String },
+    /// Moves the bot would play, the best first.
+    Suggestion { moves: Vec<Move> },
+}
+
+impl BotMessage {
+    pub fn decode(line: &str) -> Result<BotMessage, String> {
+        serde_json::from_str(line).map_err(|error| format!("Bad message from the bot: {}", error))
+    }
+}
+
+impl FrontendMessage {
+    pub fn encode(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// The game as a `start` message.
+    pub fn start(engine: &Engine) -> Result<FrontendMessage, String> {
+        let grid = engine.grid();
+        if grid.width != WIDTH {
+            return Err(format!("Bots play on boards {} cells wide", WIDTH));
+        }
+        let cells = engine.locked_cells();
+        let rows: Vec<&[Option<BColor>]> = cells.chunks(WIDTH).rev().collect();
+        let board = (0..ROWS)
+            .map(|y| match rows.get(y) {
+                Some(row) => row.iter().map(|cell| cell.map(Cell::of)).collect(),
+                None => vec![None; WIDTH],
+            })
+            .collect();
+        let snapshot = engine.snapshot();
+        let mut queue = vec![Piece::of(snapshot.block.block.shape())];
+        queue.extend(snapshot.queue.iter().map(|block| Piece::of(block.shape())));
+        Ok(FrontendMessage::Start {
+            hold: snapshot.hold.map(|block| Piece::of(block.shape())),
+            queue,
+            combo: snapshot.combo.unwrap_or(0) as u32,
+            back_to_back: snapshot.back_to_back,
+            board,
+        })
+    }
+}
+
+/// The inputs that play `mv` on `engine`, holding first when the move is for the held
+/// or the next piece. `None` if the move can't be played.
+pub fn inputs(engine: &Engine, mv: &Move) -> Option<Vec<Input>> {
+    let snapshot = engine.snapshot();
+    let shape = mv.location.piece.shape();
+    let grid = engine.grid();
+    let mut inputs = Vec::new();
+    let held = if shape == snapshot.block.block.shape() {
+        grid.clone()
+    } else {
+        let swapped = snapshot.hold.as_ref().or_else(|| snapshot.queue.first())?;
+        if !snapshot.hold_enabled || !snapshot.can_hold || swapped.shape() != shape {
+            return None;
+        }
+        inputs.push(Input::Hold);
+        search::respawned(grid, Block::new(shape))
+    };
+    let target = mv.location.block(grid.height);
+    let sonic = engine.gravity() >= MAX_GRAVITY;
+    inputs.extend(search::path(&held, &target, mv.spin != Spin::None, sonic)?);
+    inputs.push(Input::HardDrop);
+    Some(inputs)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Waiting for the bot to introduce itself.
+    Info,
+    /// Waiting for the bot to accept the rules.
+    Rules,
+    /// Ready to be asked for a move.
+    Idle,
+    /// Waiting for a suggestion.
+    Thinking,
+    Stopped,
+}
+
+/// The game's side of a conversation with a bot: it answers the bot's messages, plays
+/// its suggestions on the engine and keeps it told of every new piece.
+pub struct Session {
+    state: State,
+    name: Option<String>,
+    /// Blocks dealt when the bot was last told about the queue.
+    revealed: usize,
+    outbox: Vec<FrontendMessage>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self {
+            state: State::Info,
+            name: None,
+            revealed: 0,
+            outbox: Vec::new(),
+        }
+    }
+
+    /// The name the bot gave, once it has.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Whether the bot is ready to be asked for a move.
+    pub fn is_idle(&self) -> bool {
+        self.state == State::Idle
+    }
+
+    /// Messages to send to the bot, oldest first.
+    pub fn outgoing(&mut self) -> Vec<FrontendMessage> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Asks the bot for its next move.
+    pub fn suggest(&mut self) {
+        if self.state == State::Idle {
+            self.outbox.push(FrontendMessage::Suggest);
+            self.state = State::Thinking;
+        }
+    }
+
+    /// Tells the bot the game is over.
+    pub fn stop(&mut self) {
+        if matches!(self.state, State::Idle | State::Thinking) {
+            self.outbox.push(FrontendMessage::Stop);
+            self.state = State::Stopped;
+        }
+    }
+
+    /// Sets the bot up for the game now on `engine`, e.g. after a new game started.
+    pub fn start(&mut self, engine: &Engine) -> Result<(), String> {
+        if self.state == State::Info || self.state == State::Rules {
+            return Ok(());
+        }
+        self.stop();
+        self.outbox.push(FrontendMessage::start(engine)?);
+        self.revealed = engine.drawn();
+        self.state = State::Idle;
+        Ok(())
+    }
+
+    /// Handles a message of the bot, playing its suggestion on `engine`.
+    pub fn on_message(&mut self, engine: &mut Engine, message: BotMessage) -> Result<Vec<GameEvent>, String> {
+        match (self.state, message) {
+            (State::Info, BotMessage::Info { name, .. }) => {
+                self.name = Some(name);
+                self.outbox.push(FrontendMessage::Rules);
+                self.state = State::Rules;
+            }
+            (State::Rules, BotMessage::Ready) => {
+                self.state = State::Stopped;
+                self.start(engine)?;
+            }
+            (_, BotMessage::Error { reason }) => {
+                self.state = State::Stopped;
+                return Err(format!("The bot refused to play: {}", reason));
+            }
+            // Gravity locked a block while the bot was thinking, so its suggestion is for a
+            // board that is gone: start it over on the one in play.
+            (State::Thinking, BotMessage::Suggestion { .. }) if engine.drawn() != self.revealed => self.start(engine)?,
+            (State::Thinking, BotMessage::Suggestion { moves }) => return self.play(engine, &moves),
+            // A suggestion asked for before the game was stopped.
+            (State::Stopped, BotMessage::Suggestion { .. }) => {}
+            (_, message) => return Err(format!("Unexpected message from the bot: {:?}", message)),
+        }
+        Ok(Vec::new())
+    }
+
+    /// Plays the first suggested move that fits and tells the bot about it.
+    fn play(&mut self, engine: &mut Engine, moves: &[Move]) -> Result<Vec<GameEvent>, String> {
+        let (mv, inputs) = moves
+            .iter()
+            .find_map(|mv| inputs(engine, mv).map(|inputs| (*mv, inputs)))
+            .ok_or("The bot suggested no move that can be played")?;
+        let events: Vec<GameEvent> = inputs.into_iter().flat_map(|input| engine.handle_input(input)).collect();
+        self.outbox.push(FrontendMessage::Play { mv });
+        let snapshot = engine.snapshot();
+        let mut queue = vec![snapshot.block.block.shape()];
+        queue.extend(snapshot.queue.iter().map(Block::shape));
+        let new = engine.drawn() - self.revealed;
+        for shape in &queue[queue.len().saturating_sub(new)..] {
+            self.outbox.push(FrontendMessage::NewPiece { piece: Piece::of(*shape) });
+        }
+        self.revealed = engine.drawn();
+        self.state = State::Idle;
+        if engine.is_gameover() {
+            self.stop();
+        }
+        Ok(events)
+    }
+}
@@ -1,9 +1,27 @@
+use crate::action::Action;
+use crate::ai::Ai;
+use crate::block::BlockWithPos;
 use crate::board::Board;
+use crate::bot::Bot;
+use crate::config::Config;
+use crate::engine::{ Engine, GameEvent, Input, Outcome, Snapshot };
+use crate::handling::Handling;
+use crate::hold::Hold;
+use crate::keymap::{ Action as KeyAction, Keymap };
 use crate::manual::Manual;
+use crate::menu;
+use crate::mode::GameModeKind;
 use crate::pause::Pause;
 use crate::queue::Queue;
+use crate::replay::{ Playback, Replay };
+use crate::save::Save;
 use crate::score::Score;
+use crate::seed::Seed;
+use crate::settings;
+use crate::storage;
+use crate::tbp::Session;
 use crate::timer::Timer;
+use crate::utils;
 
 use cursive::{
     event::{Callback, Event, EventResult, Key},
@@ -13,28 +31,53 @@ use cursive::{
 };
 use std::cmp::max;
 use wasm_bindgen::prelude::*;
-use web_sys::console;
 
-const SLOW_SPEED: usize = 10;
-const NORMAL_SPEED: usize = 5;
-const FAST_SPEED: usize = 1;
+/// Where the replay of the last finished game is kept.
+const LAST_REPLAY: &str = "replay";
+/// Where the game in progress is kept so it can be continued after a reload.
+pub const SAVED_GAME: &str = "save";
+/// Milliseconds a replay jumps back or ahead.
+const SEEK: u64 = 5000;
+/// Milliseconds the built-in bot waits before each move, so it can be watched.
+const AUTOPLAY_DELAY: u64 = 200;
 
 #[wasm_bindgen]
 pub struct Tetris {
+    engine: Engine,
+    /// Repeats held keys once key releases are tracked; until then every key event is one input.
+    handling: Option<Handling>,
+    keymap: Keymap,
+    /// Name the keymap is saved under.
+    profile: String,
+    /// A replay shown instead of the game.
+    playback: Option<Playback>,
+    /// Whether the game goes on once the replay is closed.
+    resume_after_replay: bool,
+    /// A bot playing instead of the player, and where its conversation stands.
+    bot: Option<(Bot, Session)>,
+    ai: Ai,
+    /// Whether the built-in bot plays, and for how many milliseconds it has waited.
+    autoplay: Option<u64>,
+    /// Whether the board shows where the built-in bot would put the block.
+    hints: bool,
+    /// The last hint, for the number of blocks dealt and whether holding was allowed then.
+    suggestion: Option<(usize, bool, BlockWithPos)>,
     board: Board,
     queue: Queue,
     score: Score,
+    action: Action,
     timer: Timer,
+    seed: Seed,
+    hold: Hold,
     manual: Manual,
     board_size: Vec2,
     score_size: Vec2,
+    action_size: Vec2,
     timer_size: Vec2,
+    seed_size: Vec2,
+    hold_size: Vec2,
     manual_size: Vec2,
-    is_paused: bool,
-    hit_bottom: bool,
-    frame_idx: usize,
-    max_frame_idx: usize,
-    gameover: bool,
+    last_refresh: f64,
 }
 
 impl Default for Tetris {
@@ -45,132 +88,483 @@ impl Default for Tetris {
 
 impl Tetris {
     pub fn new() -> Self {
-        let mut board = Board::new(10, 20);
+        Self::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let mut board = Board::new(config.width, config.height);
+        let queue = Queue::new(config.previews, config.big_preview);
+        let keymap = settings::load_profile(&config.keymap).unwrap_or_default();
+        let profile = config.keymap.clone();
+        let autoplay = if config.autoplay { Some(0) } else { None };
+        let engine = Engine::new(config);
+        let mut hold = Hold::new();
+        hold.update(&engine.snapshot());
+        let hold_size = hold.required_size(Vec2::new(0,0));
         let board_size = board.required_size(Vec2::new(0,0));
         let mut score = Score::new();
         let score_size = score.required_size(Vec2::new(0,0));
+        let mut action = Action::new();
+        let action_size = action.required_size(Vec2::new(0,0));
         let mut timer = Timer::new();
         let timer_size = timer.required_size(Vec2::new(0,0));
-        let mut manual = Manual::new();
+        let mut seed = Seed::new();
+        let seed_size = seed.required_size(Vec2::new(0,0));
+        let mut manual = Manual::new(&keymap);
         let manual_size = manual.required_size(Vec2::new(0,0));
 
-        Tetris {
+        let mut tetris = Tetris {
+            engine,
+            handling: None,
+            keymap,
+            profile,
+            playback: None,
+            resume_after_replay: false,
+            bot: None,
+            ai: Ai::default(),
+            autoplay,
+            hints: false,
+            suggestion: None,
             board,
-            queue: Queue::new(),
+            queue,
             score,
+            action,
             timer,
+            seed,
+            hold,
             manual,
             board_size,
             score_size,
+            action_size,
             timer_size,
+            seed_size,
+            hold_size,
             manual_size,
-            is_paused: false,
-            hit_bottom: false,
-            frame_idx: 0,
-            max_frame_idx: SLOW_SPEED,
-            gameover: false,
+            last_refresh: utils::now(),
+        };
+        tetris.update_views();
+        tetris
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        match &self.playback {
+            Some(playback) => playback.engine().snapshot(),
+            None => self.engine.snapshot(),
         }
     }
 
-    fn handle_merge_and_pass(&mut self, event: Event) -> EventResult {
-        if self.gameover && event != Event::Char('n') && event != Event::Char('N') {
-            return EventResult::Consumed(None);
+    fn update_views(&mut self) {
+        let snapshot = self.snapshot();
+        if let Some(playback) = &self.playback {
+            self.manual = Manual::playback(playback);
         }
-        let is_begin = self.hit_bottom;
-        if self.hit_bottom {
-            self.merge_block();
+        self.board.update(&snapshot);
+        let suggestion = if self.hints && self.playback.is_none() { self.suggestion() } else { None };
+        self.board.suggest(suggestion);
+        self.queue.update(&snapshot);
+        self.score.update(&snapshot);
+        self.action.update(&snapshot);
+        self.timer.update(&snapshot);
+        self.seed.update(&snapshot);
+        self.hold.update(&snapshot);
+    }
+
+    fn handle_game_events(&mut self, events: Vec<GameEvent>) -> EventResult {
+        self.update_views();
+        if events.contains(&GameEvent::GameOver) {
+            utils::log("gameover");
+            if let Some((_, session)) = &mut self.bot {
+                session.stop();
+            }
+            if self.is_played_by_hand() {
+                storage::remove(SAVED_GAME);
+                if let Err(error) = storage::save(LAST_REPLAY, &Replay::of(&self.engine).encode()) {
+                    utils::log(&error);
+                }
+            }
+            if self.autoplay.is_some() && self.bot.is_none() {
+                // The demo goes on with the next game.
+                self.engine.new_game();
+                self.suggestion = None;
+                self.update_views();
+                return EventResult::Consumed(None);
+            }
+            let message = match self.engine.outcome() {
+                Some(Outcome::Complete) => {
+                    let mut lines = vec!["Complete!".to_string()];
+                    lines.extend(self.engine.snapshot().hud);
+                    lines.push(self.timer.time2str().trim().to_string());
+                    lines.join("\n")
+                }
+                Some(Outcome::ToppedOut(top_out)) => format!("Game Over!\n{}", top_out.description()),
+                None => "Game Over!".to_string(),
+            };
+            return EventResult::Consumed(Some(Callback::from_fn(move |s| {
+                s.add_layer(Dialog::info(message.clone()));
+            })));
         }
-        match event {
-            Event::Key(Key::Down) => self.speed_up(),
-            Event::Char('N') | Event::Char('n') => self.new_game(),
-            Event::Char('M') | Event::Char('m') => self.stop_and_resume(),
-            Event::Refresh => self.on_down(false, is_begin),
-            Event::Char(' ') => self.on_down(true, is_begin),
-            _ => EventResult::Ignored,
+        EventResult::Consumed(None)
+    }
+
+    /// Switches to DAS and ARR handling of held keys. Only call this when every key
+    /// release reaches `on_key_release`.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn track_key_releases(&mut self) {
+        let config = self.engine.config();
+        self.handling = Some(Handling::new(config.das, config.arr));
+    }
+
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    pub fn on_key_release(&mut self, event: Event) {
+        if self.playback.is_some() || self.bot.is_some() {
+            return;
         }
+        let input = self.input(&event);
+        let inputs = match (&mut self.handling, input) {
+            (Some(handling), Some(input)) => handling.release(input),
+            _ => return,
+        };
+        self.apply(inputs);
+        self.update_views();
     }
 
-    fn speed_up(&mut self) -> EventResult {
-        self.max_frame_idx = FAST_SPEED;
-        self.frame_idx = 0;
+    fn on_key_press(&mut self, input: Input) -> EventResult {
+        if self.bot.is_some() {
+            return EventResult::Ignored;
+        }
+        // Any move takes over from the built-in bot.
+        self.autoplay = None;
+        let inputs = match &mut self.handling {
+            Some(handling) => handling.press(input),
+            None => vec![input],
+        };
+        let events = self.apply(inputs);
+        self.handle_game_events(events)
+    }
+
+    fn apply(&mut self, inputs: Vec<Input>) -> Vec<GameEvent> {
+        let mut events: Vec<GameEvent> = inputs.into_iter().flat_map(|input| self.engine.handle_input(input)).collect();
+        if let Some(handling) = &self.handling {
+            for input in handling.keep_soft_drop(&self.engine) {
+                events.extend(self.engine.handle_input(input));
+            }
+        }
+        events
+    }
+
+    /// Milliseconds since the last refresh.
+    fn refresh_clock(&mut self) -> u64 {
+        let dt = (utils::now() - self.last_refresh).max(0.0) as u64;
+        self.last_refresh += dt as f64;
+        dt
+    }
+
+    fn on_refresh(&mut self) -> EventResult {
+        let dt = self.refresh_clock();
+        let mut events = self.engine.tick(dt);
+        let repeated = match &mut self.handling {
+            Some(handling) => handling.tick(dt),
+            None => Vec::new(),
+        };
+        events.extend(self.apply(repeated));
+        events.extend(self.autoplay(dt));
+        let error = match self.drive_bot() {
+            Ok(played) => {
+                events.extend(played);
+                None
+            }
+            Err(error) => {
+                self.bot = None;
+                Some(error)
+            }
+        };
+        let result = self.handle_game_events(events);
+        match error {
+            Some(error) => EventResult::Consumed(Some(Callback::from_fn(move |s| {
+                s.add_layer(Dialog::info(error.clone()));
+            }))),
+            None => result,
+        }
+    }
+
+    /// Lets the built-in bot make its next move once it has waited long enough.
+    fn autoplay(&mut self, dt: u64) -> Vec<GameEvent> {
+        let waited = match &mut self.autoplay {
+            Some(waited) if self.bot.is_none() && !self.engine.is_paused() => waited,
+            _ => return Vec::new(),
+        };
+        *waited += dt;
+        if *waited < AUTOPLAY_DELAY {
+            return Vec::new();
+        }
+        *waited = 0;
+        self.ai.play(&mut self.engine).unwrap_or_default()
+    }
+
+    fn toggle_autoplay(&mut self) -> EventResult {
+        self.autoplay = match self.autoplay {
+            Some(_) => None,
+            None => Some(0),
+        };
         EventResult::Consumed(None)
     }
 
-    fn new_game(&mut self) -> EventResult {
-        self.board.renew();
-        self.score.renew();
-        self.timer.renew();
-        self.queue.renew();
-        self.is_paused = false;
-        self.hit_bottom = false;
-        self.frame_idx = 0;
-        self.max_frame_idx = SLOW_SPEED;
-        self.gameover = false;
+    fn toggle_hints(&mut self) -> EventResult {
+        self.hints = !self.hints;
+        self.update_views();
         EventResult::Consumed(None)
     }
 
-    fn stop_and_resume(&mut self) -> EventResult {
-        self.toggle_pause();
-        if self.is_paused {
-            EventResult::Consumed(Some(Callback::from_fn(move |s| {
-                s.add_layer(Pause::new());
-            })))
-        } else {
-            EventResult::Consumed(None)
+    /// Where the built-in bot would put the block, worked out once per block.
+    fn suggestion(&mut self) -> Option<BlockWithPos> {
+        let key = (self.engine.drawn(), self.engine.snapshot().can_hold);
+        match &self.suggestion {
+            Some((drawn, can_hold, block)) if (*drawn, *can_hold) == key => Some(block.clone()),
+            _ => {
+                let block = self.ai.plan(&self.engine)?.block;
+                self.suggestion = Some((key.0, key.1, block.clone()));
+                Some(block)
+            }
         }
     }
 
-    fn toggle_pause(&mut self) {
-        self.is_paused = !self.is_paused;
-        self.timer.toggle_pause();
+    /// Hands the game over to a Tetris Bot Protocol bot: a command natively, the URL of
+    /// a Web Worker script on wasm. It plays once it has accepted the rules.
+    pub fn start_bot(&mut self, bot: &str) -> Result<(), String> {
+        self.bot = Some((Bot::start(bot)?, Session::new()));
+        Ok(())
     }
 
-    fn on_down(&mut self, is_drop: bool, is_begin: bool) -> EventResult {
-        if self.is_paused {
-            return EventResult::Consumed(None);
+    /// Plays the moves the bot suggested, asks it for the next one and sends it what
+    /// happened. Its messages wait while the game is paused.
+    fn drive_bot(&mut self) -> Result<Vec<GameEvent>, String> {
+        let (bot, session) = match &mut self.bot {
+            Some(bot) if !self.engine.is_paused() => bot,
+            _ => return Ok(Vec::new()),
+        };
+        let mut events = Vec::new();
+        for message in bot.receive() {
+            events.extend(session.on_message(&mut self.engine, message?)?);
         }
-        let (gameover, hit_bottom) = self.board.on_down(is_drop, is_begin);
-        let gameover = gameover || self.score.is_gameover();
-        if gameover {
-            self.gameover = true;
-            self.toggle_pause();
-            console::log_1(&"gameover".to_string().into());
-            return EventResult::Consumed(Some(Callback::from_fn(move |s| {
-                s.add_layer(Dialog::info("Game Over!"));
-            })));
+        if !self.engine.is_gameover() {
+            session.suggest();
+        }
+        for message in session.outgoing() {
+            bot.send(&message)?;
         }
-        if hit_bottom {
-            if is_drop {
-                self.merge_block();
-            } else {
-                self.hit_bottom = hit_bottom;
-                self.frame_idx = 0;
-                self.max_frame_idx = NORMAL_SPEED;
+        Ok(events)
+    }
+
+    /// Tells the bot about a game that replaced the one it was playing.
+    fn restart_bot(&mut self) {
+        if let Some((_, session)) = &mut self.bot {
+            if let Err(error) = session.start(&self.engine) {
+                utils::log(&error);
+                self.bot = None;
             }
         }
+    }
+
+    fn choose_new_game(&mut self) -> EventResult {
+        let config = self.engine.config();
+        let (mode, levels, level) = (config.mode, config.gravity_curve.levels(), config.start_level);
+        EventResult::Consumed(Some(Callback::from_fn(move |s| {
+            s.add_layer(menu::start_menu(mode, levels.clone(), level));
+        })))
+    }
+
+    pub fn new_game(&mut self, mode: GameModeKind, start_level: usize) -> EventResult {
+        if self.playback.take().is_some() {
+            self.manual = Manual::new(&self.keymap);
+        }
+        self.engine.set_mode(mode);
+        self.engine.set_start_level(start_level);
+        self.engine.new_game();
+        self.suggestion = None;
+        self.restart_bot();
+        storage::remove(SAVED_GAME);
+        self.last_refresh = utils::now();
+        self.update_views();
         EventResult::Consumed(None)
     }
 
-    fn merge_block(&mut self) {
-        let score = self.board.merge_block();
-        self.score.add(score);
-        let block = self.queue.pop_and_spawn_new_block();
-        self.board.insert(block);
-        self.hit_bottom = false;
-        self.max_frame_idx = SLOW_SPEED;
-        self.frame_idx = 0;
+    /// Whether the player is at the keys rather than the demo or a bot. Only their games
+    /// are saved and recorded, so watching doesn't overwrite them.
+    fn is_played_by_hand(&self) -> bool {
+        self.autoplay.is_none() && self.bot.is_none()
+    }
+
+    /// Keeps the game in progress so it can be continued next time. Each save copies every
+    /// input so far, so this runs on pause, when the page is hidden and when the terminal
+    /// game quits rather than as the game goes.
+    pub fn save_game(&self) {
+        if !self.is_played_by_hand() || self.engine.is_gameover() || self.engine.inputs().is_empty() {
+            return;
+        }
+        if let Err(error) = storage::save(SAVED_GAME, &self.engine.save().encode()) {
+            utils::log(&error);
+        }
     }
 
-    fn pass_event_to_board(&mut self, event: Event) -> EventResult {
-        if self.is_paused || self.gameover {
-            return EventResult::Consumed(None)
+    /// Replaces the current game with a saved one, with its own board size and previews.
+    pub fn resume_game(&mut self, save: Save) -> Result<(), String> {
+        self.engine = Engine::resume(save)?;
+        let config = self.engine.config();
+        self.board = Board::new(config.width, config.height);
+        self.board_size = self.board.required_size(Vec2::new(0,0));
+        self.queue = Queue::new(config.previews, config.big_preview);
+        if self.handling.is_some() {
+            self.handling = Some(Handling::new(config.das, config.arr));
         }
-        let moved = self.board.handle_event(event, self.hit_bottom);
-        if self.hit_bottom && moved {
-            self.max_frame_idx = std::cmp::min(3 + self.max_frame_idx, 2 * NORMAL_SPEED);
+        if self.playback.take().is_some() {
+            self.manual = Manual::new(&self.keymap);
         }
+        self.suggestion = None;
+        self.restart_bot();
+        self.last_refresh = utils::now();
+        self.update_views();
+        Ok(())
+    }
+
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.manual = Manual::new(&keymap);
+        self.manual_size = self.manual.required_size(Vec2::new(0,0));
+        self.keymap = keymap;
+    }
+
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    pub fn set_profile(&mut self, profile: &str) {
+        self.profile = profile.to_string();
+    }
+
+    /// Pauses the game while the player changes key bindings.
+    fn open_settings(&mut self) -> EventResult {
+        let resume = !self.engine.snapshot().is_paused;
+        if resume {
+            self.engine.toggle_pause();
+        }
+        let keymap = self.keymap.clone();
+        EventResult::Consumed(Some(Callback::from_fn(move |s| {
+            s.add_layer(settings::settings(&keymap, resume));
+        })))
+    }
+
+    /// Goes on with the game once the dialog that paused it is closed, if it was running.
+    pub fn close_dialog(&mut self, resume: bool) {
+        if resume {
+            self.engine.toggle_pause();
+            self.last_refresh = utils::now();
+        }
+    }
+
+    /// Pauses the game and plays back the last finished one.
+    fn watch_replay(&mut self) -> EventResult {
+        let replay = storage::load(LAST_REPLAY)
+            .ok_or_else(|| "No game has been played to the end yet".to_string())
+            .and_then(|text| Replay::decode(&text));
+        let replay = match replay {
+            Ok(replay) => replay,
+            Err(error) => {
+                return EventResult::Consumed(Some(Callback::from_fn(move |s| {
+                    s.add_layer(Dialog::info(error.clone()));
+                })))
+            }
+        };
+        self.resume_after_replay = !self.engine.is_paused();
+        if self.resume_after_replay {
+            self.engine.toggle_pause();
+        }
+        self.playback = Some(Playback::new(replay));
+        self.last_refresh = utils::now();
+        self.update_views();
+        EventResult::Consumed(None)
+    }
+
+    fn stop_replay(&mut self) {
+        self.playback = None;
+        self.manual = Manual::new(&self.keymap);
+        if self.resume_after_replay {
+            self.engine.toggle_pause();
+        }
+        self.last_refresh = utils::now();
+        self.update_views();
+    }
+
+    fn on_playback_event(&mut self, event: Event) -> EventResult {
+        let dt = if event == Event::Refresh { self.refresh_clock() } else { 0 };
+        let playback = match &mut self.playback {
+            Some(playback) => playback,
+            None => return EventResult::Ignored,
+        };
+        match event {
+            Event::Refresh => {
+                playback.advance(dt);
+            }
+            Event::Char(' ') => {
+                playback.toggle_pause();
+            }
+            Event::Char('.') => {
+                playback.step_frame();
+            }
+            Event::Char('+') | Event::Char('=') => playback.set_speed(playback.speed() * 2.0),
+            Event::Char('-') => playback.set_speed(playback.speed() / 2.0),
+            Event::Key(Key::Left) => playback.seek(playback.time().saturating_sub(SEEK)),
+            Event::Key(Key::Right) => playback.seek(playback.time() + SEEK),
+            Event::Char('0') => playback.seek(0),
+            Event::Key(Key::Esc) => self.stop_replay(),
+            _ => return EventResult::Ignored,
+        }
+        self.update_views();
         EventResult::Consumed(None)
     }
+
+    /// Copies the board as a fumen, and shows it for terminals, which have no clipboard.
+    fn copy_fumen(&mut self) -> EventResult {
+        let message = match self.engine.fumen() {
+            Ok(fumen) if utils::copy(&fumen) => format!("Copied the board as fumen:\n{}", fumen),
+            Ok(fumen) => fumen,
+            Err(error) => error,
+        };
+        let resume = !self.engine.is_paused();
+        if resume {
+            self.engine.toggle_pause();
+        }
+        EventResult::Consumed(Some(Callback::from_fn(move |s| {
+            s.add_layer(Dialog::text(message.clone()).title("Fumen").button("Ok", move |s| {
+                s.pop_layer();
+                s.call_on_name("retris", |t: &mut Tetris| t.close_dialog(resume));
+            }));
+        })))
+    }
+
+    pub fn stop_and_resume(&mut self) -> EventResult {
+        let is_paused = self.engine.toggle_pause();
+        self.last_refresh = utils::now();
+        if is_paused {
+            self.save_game();
+            let keys = self.keymap.keys(KeyAction::Pause).to_vec();
+            EventResult::Consumed(Some(Callback::from_fn(move |s| {
+                s.add_layer(Pause::new(keys.clone()));
+            })))
+        } else {
+            EventResult::Consumed(None)
+        }
+    }
+
+    fn action(&self, event: &Event) -> Option<KeyAction> {
+        settings::key(event).and_then(|key| self.keymap.action(key))
+    }
+
+    fn input(&self, event: &Event) -> Option<Input> {
+        self.action(event).and_then(|action| action.input())
+    }
 }
 
 impl View for Tetris {
@@ -181,13 +575,26 @@ impl View for Tetris {
         let score_padding = Vec2::new(x_padding, y_padding);
         let score_printer = printer.offset(score_padding);
 
-        let timer_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding);
+        let action_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding);
+        let action_printer = printer.offset(action_padding);
+
+        let timer_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding + self.action_size.y + y_padding);
         let timer_printer = printer.offset(timer_padding);
 
-        let manual_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding + self.timer_size.y + y_padding);
+        let seed_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding + self.action_size.y + y_padding + self.timer_size.y + y_padding);
+        let seed_printer = printer.offset(seed_padding);
+
+        let hold_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding + self.action_size.y + y_padding + self.timer_size.y + y_padding + self.seed_size.y + y_padding);
+        let hold_printer = printer.offset(hold_padding);
+
+        let manual_padding = Vec2::new(x_padding, y_padding + self.score_size.y + y_padding + self.action_size.y + y_padding + self.timer_size.y + y_padding + self.seed_size.y + y_padding + self.hold_size.y + y_padding);
         let manual_printer = printer.offset(manual_padding);
         
-        let first_column_x_padding = max(max(self.manual_size.x, self.score_size.x), self.timer_size.x);
+        let first_column_x_padding = [self.manual_size, self.score_size, self.timer_size, self.seed_size, self.hold_size, self.action_size]
+            .iter()
+            .map(|size| size.x)
+            .max()
+            .unwrap_or(0);
 
         let board_padding = Vec2::new(x_padding + first_column_x_padding + x_padding, y_padding);
         let board_printer = printer.offset(board_padding);
@@ -196,7 +603,10 @@ impl View for Tetris {
         let queue_printer = printer.offset(queue_padding);
 
         self.score.draw(&score_printer);
+        self.action.draw(&action_printer);
         self.timer.draw(&timer_printer);
+        self.seed.draw(&seed_printer);
+        self.hold.draw(&hold_printer);
         self.manual.draw(&manual_printer);
         self.board.draw(&board_printer);
         self.queue.draw(&queue_printer);
@@ -204,23 +614,30 @@ impl View for Tetris {
     }
 
     fn on_event(&mut self, event: Event) -> EventResult {
+        if self.playback.is_some() {
+            return self.on_playback_event(event);
+        }
         if event == Event::Refresh {
-            self.frame_idx += 1;
-            if self.frame_idx == self.max_frame_idx {
-                self.frame_idx = 0;
-            } else {
-                return EventResult::Ignored;
-            }
+            return self.on_refresh();
         }
-
-        match event {
-            Event::Refresh | Event::Key(Key::Down) | Event::Char(' ') | Event::Char('N') | Event::Char('n') | Event::Char('M') | Event::Char('m') => self.handle_merge_and_pass(event),
-            _ => self.pass_event_to_board(event),
+        match self.action(&event) {
+            Some(KeyAction::Replay) => self.watch_replay(),
+            Some(KeyAction::NewGame) => self.choose_new_game(),
+            Some(KeyAction::Pause) => self.stop_and_resume(),
+            Some(KeyAction::Settings) => self.open_settings(),
+            Some(KeyAction::Fumen) => self.copy_fumen(),
+            Some(KeyAction::Hint) => self.toggle_hints(),
+            Some(KeyAction::Autoplay) => self.toggle_autoplay(),
+            Some(action) => match action.input() {
+                Some(input) => self.on_key_press(input),
+                None => EventResult::Ignored,
+            },
+            None => EventResult::Ignored,
         }
     }
 
     fn required_size(&mut self, _constraint: cursive::Vec2) -> cursive::Vec2 {
-        Vec2::new(100, 100)
-
+        // Leave room for wide and tall boards next to the side columns.
+        Vec2::new(max(100, self.board_size.x + 60), max(100, self.board_size.y + 4))
     }
-}
\ No newline at end of file
+}
@@ -1,16 +1,14 @@
+use crate::engine::Snapshot;
 use crate::numbers::padding;
 use cursive::{
     theme::{ Color, ColorStyle,},
     View,
     Printer,
 };
-use js_sys::Date;
 
 
 pub struct Timer {
-    start: f64,
-    is_paused: bool,
-    pause_start: f64,
+    elapsed: u64,
 }
 
 impl Default for Timer {
@@ -21,28 +19,13 @@ impl Default for Timer {
 
 impl Timer {
     pub fn new() -> Self {
-        let now = Date::now();
         Self {
-            start: now,
-            is_paused: false,
-            pause_start: now,
+            elapsed: 0,
         }
     }
 
-    pub fn renew(&mut self) {
-        let now = Date::now();
-        self.start = now;
-        self.pause_start = now;
-        self.is_paused = false;
-    }
-
-    pub fn toggle_pause(&mut self) {
-        if self.is_paused {
-            self.start += Date::now() - self.pause_start;
-        } else {
-            self.pause_start = Date::now();
-        }
-        self.is_paused = !self.is_paused;
+    pub fn update(&mut self, snapshot: &Snapshot) {
+        self.elapsed = snapshot.clock;
     }
 
     pub fn time2str(&self) -> String {
@@ -53,10 +36,8 @@ impl Timer {
         format!(" Time {}:{}:{} ", mins, secs, mills)
     }
 
-    fn elapsed(&self) -> (u128, u128, u128) {
-        let mills = if self.is_paused {
-            (self.pause_start - self.start) as u128
-        } else { (Date::now() - self.start) as u128 };
+    fn elapsed(&self) -> (u64, u64, u64) {
+        let mills = self.elapsed;
         let mins = mills / 60000;
         let secs = (mills % 60000) / 1000;
         let mills = mills % 1000;
@@ -76,4 +57,4 @@ impl View for Timer {
         let line = self.time2str();
         cursive::Vec2::new(line.len(), 1)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,93 @@
+/// Ways a game can be lost by running out of room.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TopOut {
+    /// A new block spawned overlapping the stack.
+    BlockOut,
+    /// A block locked entirely above the visible field.
+    LockOut,
+    /// A block locked partly above the visible field without clearing a line.
+    PartialLockOut,
+    /// Garbage pushed the stack over the top.
+    GarbageOut,
+}
+
+impl TopOut {
+    pub fn description(&self) -> &'static str {
+        match self {
+            TopOut::BlockOut => "Block out: no room for the next block",
+            TopOut::LockOut => "Lock out: a block locked above the field",
+            TopOut::PartialLockOut => "Partial lock out: a block locked sticking out of the field",
+            TopOut::GarbageOut => "Garbage out: garbage pushed the stack over the top",
+        }
+    }
+}
+
+/// Which top-out conditions end the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TopOutRules {
+    pub block_out: bool,
+    pub lock_out: bool,
+    pub partial_lock_out: bool,
+}
+
+impl Default for TopOutRules {
+    /// The guideline rules: block out and lock out.
+    fn default() -> Self {
+        Self {
+            block_out: true,
+            lock_out: true,
+            partial_lock_out: false,
+        }
+    }
+}
+
+impl TopOutRules {
+    /// Never top out.
+    pub fn none() -> Self {
+        Self {
+            block_out: false,
+            lock_out: false,
+            partial_lock_out: false,
+        }
+    }
+
+    /// The rules as `from_name` reads them, e.g. `block,lock`.
+    pub fn name(&self) -> String {
+        let rules: Vec<&str> = [(self.block_out, "block"), (self.lock_out, "lock"), (self.partial_lock_out, "partial")]
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, rule)| *rule)
+            .collect();
+        if rules.is_empty() {
+            "none".to_string()
+        } else {
+            rules.join(",")
+        }
+    }
+
+    /// Parses a comma separated list of `block`, `lock` and `partial`, or `none`.
+    pub fn from_name(name: &str) -> Option<TopOutRules> {
+        let mut rules = Self::none();
+        for rule in name.split(',') {
+            match rule {
+                "block" => rules.block_out = true,
+                "lock" => rules.lock_out = true,
+                "partial" => rules.partial_lock_out = true,
+                "none" => {}
+                _ => return None,
+            }
+        }
+        Some(rules)
+    }
+
+    /// Checks a block about to lock with `cells_above` of its cells above the visible field.
+    pub fn on_lock(&self, cells_above: usize, cells: usize, lines: usize) -> Option<TopOut> {
+        if self.lock_out && cells_above == cells {
+            Some(TopOut::LockOut)
+        } else if self.partial_lock_out && cells_above > 0 && lines == 0 {
+            Some(TopOut::PartialLockOut)
+        } else {
+            None
+        }
+    }
+}
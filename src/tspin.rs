@@ -0,0 +1,51 @@
+use crate::block::{ Block, Rotation, Shape };
+use crate::color_grid::{ ColorGrid, Move };
+use crate::rotation::Turn;
+
+/// The SRS kick that lifts a T out of a T-spin triple slot; it always counts as a full spin.
+const LAST_KICK: usize = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TSpin {
+    #[default]
+    None,
+    Mini,
+    Full,
+}
+
+/// Classifies the active block of `grid` as it is about to lock, using the 3-corner rule:
+/// a T whose last move was a turn is a spin when three of the four cells diagonal to its
+/// centre are blocked. It is a full spin if both corners beside its nub are blocked, or if
+/// the turn needed the last SRS kick, and a mini otherwise.
+pub fn detect(grid: &ColorGrid) -> TSpin {
+    let block = &grid.block;
+    if block.block.shape() != Shape::T {
+        return TSpin::None;
+    }
+    let (turn, kick) = match grid.last_move() {
+        Some(Move::Turn { turn, kick }) => (turn, kick),
+        _ => return TSpin::None,
+    };
+    let (x, y) = block.pos;
+    let (dx, dy) = nub(&block.block);
+    let blocked = |cx: i32, cy: i32| grid.is_blocked(x + cx, y + cy);
+    let front = [(dx - dy, dy - dx), (dx + dy, dy + dx)].iter().filter(|(cx, cy)| blocked(*cx, *cy)).count();
+    let back = [(-dx - dy, -dy - dx), (-dx + dy, -dy + dx)].iter().filter(|(cx, cy)| blocked(*cx, *cy)).count();
+    if front + back < 3 {
+        TSpin::None
+    } else if front == 2 || (turn != Turn::Flip && kick == LAST_KICK) {
+        TSpin::Full
+    } else {
+        TSpin::Mini
+    }
+}
+
+/// Where the middle cell of the T sticks out from its centre.
+fn nub(block: &Block) -> (i32, i32) {
+    match block.rotation() {
+        Rotation::R0 => (0, -1),
+        Rotation::R90 => (1, 0),
+        Rotation::R180 => (0, 1),
+        Rotation::R270 => (-1, 0),
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(target_arch = "wasm32")]
 pub fn set_panic_hook() {
     // When the `console_error_panic_hook` feature is enabled, we can call the
     // `set_panic_hook` function at least once during initialization, and then
@@ -8,3 +9,44 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
     console_error_panic_hook::set_once();
 }
+
+/// Milliseconds of wall-clock time.
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> f64 {
+    use std::time::{ SystemTime, UNIX_EPOCH };
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |time| time.as_secs_f64() * 1000.0)
+}
+
+/// Logs to the browser console. A terminal has no room for it.
+#[cfg(target_arch = "wasm32")]
+pub fn log(message: &str) {
+    web_sys::console::log_1(&message.into());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn log(_message: &str) {}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = writeText)]
+    fn write_text(text: &str) -> js_sys::Promise;
+}
+
+/// Puts `text` on the clipboard, and tells whether there is one to put it on.
+#[cfg(target_arch = "wasm32")]
+pub fn copy(text: &str) -> bool {
+    // The promise only fails when the page isn't focused, which a key press rules out.
+    let _ = write_text(text);
+    true
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn copy(_text: &str) -> bool {
+    false
+}
@@ -0,0 +1,136 @@
+//! Two players dealt the same blocks send each other garbage until one tops out, over
+//! rounds until one of them has won the match.
+
+use crate::config::Config;
+use crate::engine::{ Engine, GameEvent, Input };
+use crate::mode::GameModeKind;
+
+pub const PLAYERS: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Playing,
+    /// A player topped out. `None` when both did at once.
+    RoundOver { winner: Option<usize> },
+    MatchOver { winner: usize },
+}
+
+pub struct Versus {
+    config: Config,
+    engines: Vec<Engine>,
+    wins: [usize; PLAYERS],
+    state: State,
+}
+
+impl Versus {
+    pub fn new(config: Config) -> Self {
+        let config = Config { mode: GameModeKind::Versus, ..config };
+        let seed = config.seed.unwrap_or_else(rand::random);
+        let engines = (0..PLAYERS).map(|_| Engine::new(Config { seed: Some(seed), ..config.clone() })).collect();
+        Self {
+            config,
+            engines,
+            wins: [0; PLAYERS],
+            state: State::Playing,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn engine(&self, player: usize) -> &Engine {
+        &self.engines[player]
+    }
+
+    /// Rounds each player has won.
+    pub fn wins(&self) -> [usize; PLAYERS] {
+        self.wins
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Rounds it takes to win the match.
+    pub fn rounds_to_win(&self) -> usize {
+        self.config.best_of / 2 + 1
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.engines[0].is_paused()
+    }
+
+    /// Pauses or resumes both games together.
+    pub fn toggle_pause(&mut self) -> bool {
+        let paused = !self.is_paused();
+        for engine in &mut self.engines {
+            if engine.is_paused() != paused {
+                engine.toggle_pause();
+            }
+        }
+        self.is_paused()
+    }
+
+    pub fn handle_input(&mut self, player: usize, input: Input) -> Vec<GameEvent> {
+        if self.state != State::Playing {
+            return Vec::new();
+        }
+        let events = self.engines[player].handle_input(input);
+        self.resolve(player, &events);
+        events
+    }
+
+    /// Advances both games by `dt` milliseconds. Returns what happened to each player.
+    pub fn tick(&mut self, dt: u64) -> Vec<(usize, GameEvent)> {
+        let mut events = Vec::new();
+        for player in 0..PLAYERS {
+            if self.state != State::Playing {
+                break;
+            }
+            let happened = self.engines[player].tick(dt);
+            self.resolve(player, &happened);
+            events.extend(happened.into_iter().map(|event| (player, event)));
+        }
+        events
+    }
+
+    /// Starts the next round with a new shared seed, or a new match once this one is won.
+    pub fn next_round(&mut self) {
+        if let State::MatchOver { .. } = self.state {
+            self.wins = [0; PLAYERS];
+        }
+        let seed = self.config.seed.unwrap_or_else(rand::random);
+        for engine in &mut self.engines {
+            engine.new_game_with_seed(seed);
+        }
+        self.state = State::Playing;
+    }
+
+    /// Sends the garbage of `player`'s attacks to the other player and ends the round on a KO.
+    fn resolve(&mut self, player: usize, events: &[GameEvent]) {
+        for event in events {
+            if let GameEvent::Attack(lines) = event {
+                self.engines[1 - player].receive_garbage(*lines);
+            }
+        }
+        let out: Vec<bool> = self.engines.iter().map(Engine::is_gameover).collect();
+        let winner = match (out[0], out[1]) {
+            (false, false) => return,
+            (true, true) => None,
+            (true, false) => Some(1),
+            (false, true) => Some(0),
+        };
+        self.state = match winner {
+            Some(winner) => {
+                self.wins[winner] += 1;
+                if self.wins[winner] >= self.rounds_to_win() {
+                    State::MatchOver { winner }
+                } else {
+                    State::RoundOver { winner: Some(winner) }
+                }
+            }
+            None => State::RoundOver { winner: None },
+        };
+    }
+}
@@ -0,0 +1,103 @@
+use wretris::ai::{ Ai, Features, Weights };
+use wretris::block::{ BColor, Rotation, Shape };
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::fumen::{ self, Page, Piece, HEIGHT, WIDTH };
+use wretris::level::GravityCurve;
+use wretris::mode::GameModeKind;
+use wretris::randomizer::RandomizerKind;
+use wretris::replay::{ Playback, Replay };
+use wretris::rotation::RotationSystemKind;
+
+/// Plays up to `pieces` blocks with the default bot, letting `dt` milliseconds pass before each.
+fn autoplay(config: Config, pieces: usize, dt: u64) -> Engine {
+    let ai = Ai::default();
+    let mut engine = Engine::new(config);
+    for _ in 0..pieces {
+        engine.tick(dt);
+        if ai.play(&mut engine).is_none() {
+            break;
+        }
+    }
+    engine
+}
+
+/// Checks that the replay of `engine` ends the same way.
+fn assert_replays(engine: &Engine) {
+    let mut playback = Playback::new(Replay::decode(&Replay::of(engine).encode()).unwrap());
+    while !playback.is_finished() {
+        playback.advance(1000);
+    }
+    let (played, replayed) = (engine.snapshot(), playback.engine().snapshot());
+    assert_eq!(replayed.cells, played.cells);
+    assert_eq!(replayed.points, played.points);
+    assert_eq!(replayed.outcome, played.outcome);
+}
+
+#[test]
+fn features_of_a_board() {
+    let mut page = Page::default();
+    for (x, y) in [(0, 1), (1, 0), (3, 0), (3, 1), (3, 2)] {
+        page.field[(HEIGHT - 1 - y) * WIDTH + x] = Some(BColor::GARBAGE);
+    }
+    page.piece = Some(Piece { shape: Shape::T, rotation: Rotation::R0, pos: (4, 4) });
+    let config = Config { fumen: Some(fumen::encode(&[page]).unwrap()), ..Config::default() };
+    let engine = Engine::new(config);
+    let features = Features::of(engine.grid(), 2);
+    assert_eq!(features, Features { height: 6, lines: 2, holes: 1, bumpiness: 1 + 1 + 3 + 3, wells: 1 });
+    let weights = Weights { height: -1.0, lines: 10.0, holes: -5.0, bumpiness: 0.0, wells: -2.0 };
+    assert_eq!(features.score(&weights), -6.0 + 20.0 - 5.0 - 2.0);
+}
+
+#[test]
+fn the_bot_clears_lines() {
+    let config = Config { mode: GameModeKind::Marathon { max_level: None }, seed: Some(1), ..Config::default() };
+    let engine = autoplay(config, 200, 0);
+    let snapshot = engine.snapshot();
+    assert!(!snapshot.gameover);
+    assert!(snapshot.lines >= 60, "{} lines", snapshot.lines);
+    assert_replays(&engine);
+}
+
+#[test]
+fn the_bot_holds_and_plans_for_every_shape() {
+    let ai = Ai::default();
+    let mut engine = Engine::new(Config { seed: Some(2), ..Config::default() });
+    for _ in 0..50 {
+        let plan = ai.plan(&engine).unwrap();
+        assert_eq!(plan.inputs.last(), Some(&Input::HardDrop));
+        ai.play(&mut engine);
+    }
+    assert!(engine.snapshot().hold.is_some());
+    assert!(engine.inputs().iter().any(|(_, input)| *input == Input::Hold));
+}
+
+/// Games with every randomizer and rotation system, slow and at 20 G, each checked against its replay.
+fn soak(games: u64, pieces: usize) {
+    for seed in 0..games {
+        let config = Config {
+            mode: GameModeKind::Marathon { max_level: None },
+            seed: Some(seed),
+            randomizer: RandomizerKind::all()[seed as usize % RandomizerKind::all().len()],
+            rotation_system: RotationSystemKind::all()[seed as usize / 2 % 2],
+            gravity_curve: if seed % 3 == 0 { GravityCurve::Fixed(20.0) } else { GravityCurve::default() },
+            width: 6 + seed as usize % 6,
+            ..Config::default()
+        };
+        let engine = autoplay(config, pieces, seed % 200);
+        assert!(engine.snapshot().lines > 0 || engine.is_gameover());
+        assert_replays(&engine);
+    }
+}
+
+#[test]
+fn soak_test() {
+    soak(8, 100);
+}
+
+/// Thousands of games; run with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn long_soak_test() {
+    soak(2000, 500);
+}
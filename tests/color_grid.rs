@@ -0,0 +1,36 @@
+use wretris::block::{ Block, BlockWithPos, BColor, Shape };
+use wretris::color_grid::ColorGrid;
+use wretris::rotation::RotationSystemKind;
+
+/// A board four wide without buffer rows, with an O block in the top left corner.
+fn grid(filled: &[(usize, usize)]) -> ColorGrid {
+    let mut grid = ColorGrid::new(4, 4, 0, (BColor::GRID1, BColor::GRID2), BColor::HINT, RotationSystemKind::Srs, Block::new(Shape::O));
+    for (x, y) in filled {
+        grid.set(*x, *y, BColor::I);
+    }
+    grid.block = BlockWithPos::from(Block::new(Shape::O), (1, 1));
+    grid
+}
+
+fn occupied(grid: &ColorGrid, y: usize) -> Vec<usize> {
+    (0..4).filter(|x| grid.is_occupied(*x, y)).collect()
+}
+
+#[test]
+fn clearing_the_top_row_keeps_the_rows_below() {
+    let mut grid = grid(&[(2, 0), (3, 0), (3, 3)]);
+    assert_eq!(grid.merge_block(), 1);
+    assert_eq!(occupied(&grid, 0), Vec::<usize>::new());
+    assert_eq!(occupied(&grid, 1), vec![0, 1]);
+    assert_eq!(occupied(&grid, 2), Vec::<usize>::new());
+    assert_eq!(occupied(&grid, 3), vec![3]);
+}
+
+#[test]
+fn rows_above_a_clear_move_down() {
+    let mut grid = grid(&[(2, 1), (3, 1), (3, 3)]);
+    assert_eq!(grid.merge_block(), 1);
+    assert_eq!(occupied(&grid, 0), Vec::<usize>::new());
+    assert_eq!(occupied(&grid, 1), vec![0, 1]);
+    assert_eq!(occupied(&grid, 3), vec![3]);
+}
@@ -0,0 +1,271 @@
+use wretris::block::Shape;
+use wretris::config::Config;
+use wretris::engine::{ Engine, GameEvent, Input };
+use wretris::level::GravityCurve;
+use wretris::randomizer::RandomizerKind;
+use wretris::rotation::RotationSystemKind;
+
+#[test]
+fn new_engine_has_a_piece_and_previews() {
+    let engine = Engine::default();
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.width, 10);
+    assert_eq!(snapshot.height, 20);
+    assert_eq!(snapshot.buffer, 20);
+    assert_eq!(snapshot.cells.len(), 400);
+    assert_eq!(snapshot.queue.len(), 3);
+    assert_eq!(snapshot.block.cells().len(), 4);
+    assert!(!snapshot.gameover);
+}
+
+#[test]
+fn hard_drop_locks_the_piece() {
+    let mut engine = Engine::default();
+    let next = engine.snapshot().queue[0].shape();
+    let events = engine.handle_input(Input::HardDrop);
+    assert_eq!(events, vec![GameEvent::Locked { lines: 0 }]);
+    assert_eq!(engine.snapshot().block.block.shape(), next);
+}
+
+#[test]
+fn paused_engine_ignores_ticks_and_inputs() {
+    let mut engine = Engine::default();
+    engine.tick(16);
+    assert!(engine.toggle_pause());
+    let before = engine.snapshot();
+    assert!(engine.tick(16).is_empty());
+    assert!(engine.handle_input(Input::HardDrop).is_empty());
+    let after = engine.snapshot();
+    assert_eq!(before.elapsed, after.elapsed);
+    assert_eq!(before.block.pos, after.block.pos);
+    assert!(!engine.toggle_pause());
+}
+
+#[test]
+fn gravity_eventually_locks_the_piece() {
+    let mut engine = Engine::default();
+    let mut locked = false;
+    for _ in 0..2000 {
+        if engine.tick(16).iter().any(|e| matches!(e, GameEvent::Locked { .. })) {
+            locked = true;
+            break;
+        }
+    }
+    assert!(locked);
+}
+
+#[test]
+fn stacking_pieces_tops_out() {
+    let mut engine = Engine::default();
+    for _ in 0..100_000 {
+        engine.tick(16);
+        if engine.is_gameover() {
+            break;
+        }
+    }
+    assert!(engine.is_gameover());
+    engine.new_game();
+    assert!(!engine.is_gameover());
+    assert!(engine.snapshot().cells.len() == 400);
+}
+
+#[test]
+fn same_seed_gives_same_pieces() {
+    let config = Config { seed: Some(42), ..Config::default() };
+    let mut a = Engine::new(config.clone());
+    let mut b = Engine::new(config);
+    assert_eq!(a.seed(), 42);
+    for _ in 0..20 {
+        assert_eq!(a.snapshot().block.block.shape(), b.snapshot().block.block.shape());
+        a.handle_input(Input::HardDrop);
+        b.handle_input(Input::HardDrop);
+    }
+    a.new_game_with_seed(7);
+    b.new_game_with_seed(7);
+    assert_eq!(a.snapshot().block.block.shape(), b.snapshot().block.block.shape());
+    assert_eq!(a.snapshot().seed, 7);
+}
+
+#[test]
+fn opening_pieces_come_from_the_bag() {
+    for seed in 0..50 {
+        let mut engine = Engine::new(Config { seed: Some(seed), randomizer: RandomizerKind::SevenBag, ..Config::default() });
+        let mut shapes = Vec::new();
+        for i in 0..14 {
+            shapes.push(engine.snapshot().block.block.shape() as u8);
+            engine.handle_input([Input::LeftMost, Input::RightMost, Input::Right][i % 3]);
+            engine.handle_input(Input::HardDrop);
+        }
+        assert!(!engine.is_gameover());
+        for bag in shapes.chunks(7) {
+            let mut bag = bag.to_vec();
+            bag.sort_unstable();
+            assert_eq!(bag, vec![0, 1, 2, 3, 4, 5, 6]);
+        }
+    }
+}
+
+#[test]
+fn config_from_query() {
+    let config = Config::from_query("?seed=12&randomizer=tgm&rotation=srs&unknown=1");
+    assert_eq!(config.seed, Some(12));
+    assert_eq!(config.randomizer, RandomizerKind::Tgm);
+    assert_eq!(config.rotation_system, RotationSystemKind::Srs);
+    let config = Config::from_query("seed=abc&rotation=nope");
+    assert_eq!(config.seed, None);
+    assert_eq!(config.rotation_system, RotationSystemKind::Classic);
+}
+
+#[test]
+fn hold_swaps_once_per_piece() {
+    let mut engine = Engine::new(Config { seed: Some(3), ..Config::default() });
+    let first = engine.snapshot().block.block.shape();
+    let next = engine.snapshot().queue[0].shape();
+    engine.handle_input(Input::Hold);
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.hold.map(|b| b.shape()), Some(first));
+    assert_eq!(snapshot.block.block.shape(), next);
+    assert!(!snapshot.can_hold);
+
+    engine.handle_input(Input::Hold);
+    assert_eq!(engine.snapshot().block.block.shape(), next);
+
+    engine.handle_input(Input::HardDrop);
+    assert!(engine.snapshot().can_hold);
+    engine.handle_input(Input::Hold);
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.block.block.shape(), first);
+    assert_eq!(snapshot.block.block.rotation(), wretris::block::Rotation::R0);
+}
+
+#[test]
+fn hold_can_be_turned_off() {
+    let mut engine = Engine::new(Config { hold: false, ..Config::default() });
+    let first = engine.snapshot().block.block.shape();
+    engine.handle_input(Input::Hold);
+    let snapshot = engine.snapshot();
+    assert!(snapshot.hold.is_none());
+    assert_eq!(snapshot.block.block.shape(), first);
+}
+
+#[test]
+fn gravity_does_not_depend_on_the_refresh_rate() {
+    let config = Config { seed: Some(5), gravity_curve: GravityCurve::Fixed(0.7), ..Config::default() };
+    let mut fast = Engine::new(config.clone());
+    let mut slow = Engine::new(config);
+    for _ in 0..3000 {
+        fast.tick(1);
+    }
+    for _ in 0..30 {
+        slow.tick(100);
+    }
+    let (fast, slow) = (fast.snapshot(), slow.snapshot());
+    assert_eq!(fast.block.pos, slow.block.pos);
+    assert_eq!(fast.cells, slow.cells);
+    assert_eq!(fast.elapsed, 3000);
+    assert_eq!(slow.elapsed, 3000);
+}
+
+#[test]
+fn gravity_is_measured_in_cells_per_frame() {
+    let mut engine = Engine::new(Config { gravity_curve: GravityCurve::Fixed(1.0 / 60.0), ..Config::default() });
+    let y = engine.snapshot().block.pos.1;
+    engine.tick(999);
+    assert_eq!(engine.snapshot().block.pos.1, y);
+    engine.tick(1);
+    assert_eq!(engine.snapshot().block.pos.1, y + 1);
+}
+
+#[test]
+fn twenty_g_puts_blocks_on_the_stack_at_once() {
+    let mut engine = Engine::new(Config { gravity_curve: GravityCurve::Fixed(20.0), ..Config::default() });
+    engine.tick(1);
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.block.pos, snapshot.hint.pos);
+    engine.handle_input(Input::RightMost);
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.block.pos, snapshot.hint.pos);
+}
+
+#[test]
+fn blocks_spawn_at_the_top_of_the_visible_field() {
+    let engine = Engine::new(Config { width: 6, height: 30, buffer: 4, ..Config::default() });
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.cells.len(), 6 * 34);
+    assert_eq!(snapshot.block.pos, (3, 4));
+    assert!(snapshot.block.cells().iter().all(|(x, y)| (0..6).contains(x) && (3..=4).contains(y)));
+
+    let engine = Engine::new(Config { buffer: 0, ..Config::default() });
+    assert_eq!(engine.snapshot().block.pos, (5, 1));
+}
+
+#[test]
+fn narrow_boards_still_clear_lines() {
+    // A flat I block fills a whole row of a board four wide.
+    let mut engine = Engine::new(Config { seed: Some(3), width: 4, height: 10, buffer: 2, ..Config::default() });
+    assert_eq!(engine.snapshot().cells.len(), 4 * 12);
+    while engine.snapshot().block.block.shape() != Shape::I {
+        engine.handle_input(Input::HardDrop);
+        assert!(!engine.is_gameover());
+    }
+    engine.handle_input(Input::HardDrop);
+    assert!(engine.snapshot().lines > 0);
+}
+
+#[test]
+fn board_size_from_query() {
+    let config = Config::from_query("width=6&height=30&buffer=2");
+    assert_eq!((config.width, config.height, config.buffer), (6, 30, 2));
+    let config = Config::from_query("width=2&height=x");
+    assert_eq!((config.width, config.height), (10, 20));
+}
+
+#[test]
+fn releasing_soft_drop_restores_gravity() {
+    let mut engine = Engine::new(Config { seed: Some(4), gravity_curve: GravityCurve::Fixed(1.0 / 60.0), ..Config::default() });
+    let y = engine.snapshot().block.pos.1;
+    engine.handle_input(Input::SoftDrop);
+    assert!(engine.is_soft_dropping());
+    engine.tick(100);
+    assert_eq!(engine.snapshot().block.pos.1, y + 1);
+    engine.handle_input(Input::SoftDropRelease);
+    assert!(!engine.is_soft_dropping());
+    engine.tick(899);
+    assert_eq!(engine.snapshot().block.pos.1, y + 1);
+    // Each new block starts at normal speed.
+    engine.handle_input(Input::SoftDrop);
+    engine.handle_input(Input::HardDrop);
+    assert!(!engine.is_soft_dropping());
+}
+
+#[test]
+fn preview_count_does_not_change_the_sequence() {
+    let shapes = |previews| {
+        let mut engine = Engine::new(Config { seed: Some(9), previews, ..Config::default() });
+        assert_eq!(engine.snapshot().queue.len(), previews);
+        let mut shapes = Vec::new();
+        for _ in 0..10 {
+            shapes.push(engine.snapshot().block.block.shape() as u8);
+            engine.handle_input(Input::HardDrop);
+        }
+        shapes
+    };
+    let three = shapes(3);
+    assert_eq!(shapes(0), three);
+    assert_eq!(shapes(7), three);
+
+    let mut engine = Engine::new(Config { seed: Some(9), previews: 1, ..Config::default() });
+    let next = engine.snapshot().queue[0].shape();
+    engine.handle_input(Input::HardDrop);
+    assert_eq!(engine.snapshot().block.block.shape(), next);
+}
+
+#[test]
+fn previews_from_query() {
+    let config = Config::from_query("previews=0&big_preview=on");
+    assert_eq!(config.previews, 0);
+    assert!(config.big_preview);
+    let config = Config::from_query("previews=8");
+    assert_eq!(config.previews, 3);
+    assert!(!config.big_preview);
+}
@@ -0,0 +1,132 @@
+use wretris::block::{ BColor, Rotation, Shape };
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::fumen::{ self, Page, Piece, HEIGHT, WIDTH };
+
+const SHAPES: [Shape; 7] = [Shape::I, Shape::O, Shape::T, Shape::S, Shape::Z, Shape::J, Shape::L];
+const ROTATIONS: [Rotation; 4] = [Rotation::R0, Rotation::R90, Rotation::R180, Rotation::R270];
+
+fn sorted_cells(piece: &Piece) -> Vec<(i32, i32)> {
+    let mut cells = piece.block().cells();
+    cells.sort();
+    cells
+}
+
+#[test]
+fn empty_field() {
+    let pages = fumen::decode("v115@vhAAgH").unwrap();
+    assert_eq!(pages, vec![Page::default()]);
+    assert_eq!(fumen::encode(&pages).unwrap(), "v115@vhAAgH");
+    assert_eq!(fumen::decode("https://fumen.zui.jp/?v115@vhAAgH").unwrap(), pages);
+}
+
+#[test]
+fn piece_on_the_floor() {
+    let pages = fumen::decode("v115@vhAVQJ").unwrap();
+    let piece = pages[0].piece.unwrap();
+    assert_eq!((piece.shape, piece.rotation), (Shape::T, Rotation::R0));
+    let floor = HEIGHT as i32 - 1;
+    assert_eq!(sorted_cells(&piece), vec![(3, floor), (4, floor - 1), (4, floor), (5, floor)]);
+    assert_eq!(fumen::encode(&pages).unwrap(), "v115@vhAVQJ");
+}
+
+#[test]
+fn pages_round_trip() {
+    let mut pages = Vec::new();
+    let mut state: u64 = 3;
+    let mut field = vec![None; WIDTH * (HEIGHT + 1)];
+    for i in 0..150 {
+        let unchanged = (20..=100).contains(&i);
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        // Change the field on some pages only, so that unchanged ones repeat past a count digit.
+        if !unchanged {
+            for (j, cell) in field.iter_mut().enumerate() {
+                if j >= 150 && ((state >> (j % 60)) % 7 == 0) {
+                    *cell = match (state >> 3) % 8 {
+                        7 => Some(BColor::GARBAGE),
+                        n => Some(SHAPES[n as usize].to_color()),
+                    };
+                }
+            }
+        }
+        let shape = SHAPES[(state >> 20) as usize % SHAPES.len()];
+        let rotation = ROTATIONS[(state >> 30) as usize % ROTATIONS.len()];
+        let piece = Piece { shape, rotation, pos: (4, 2 + (state >> 40) as i32 % 15) };
+        let comment = match i % 3 {
+            0 => format!("page {} T-spin ダブル 100%", i),
+            1 => String::new(),
+            _ => pages.last().map_or(String::new(), |page: &Page| page.comment.clone()),
+        };
+        pages.push(Page {
+            field: field.clone(),
+            piece: Some(piece).filter(|_| i % 5 != 0),
+            comment,
+            lock: i % 4 != 0,
+        });
+    }
+    let encoded = fumen::encode(&pages).unwrap();
+    assert!(encoded.starts_with("v115@"));
+    assert!(encoded.split('?').skip(1).all(|chunk| chunk.len() <= 47));
+    assert_eq!(fumen::decode(&encoded).unwrap(), pages);
+}
+
+#[test]
+fn every_piece_keeps_its_place() {
+    for shape in SHAPES {
+        for rotation in ROTATIONS {
+            for x in 0..WIDTH as i32 {
+                for y in 0..HEIGHT as i32 {
+                    let page = Page { piece: Some(Piece { shape, rotation, pos: (x, y) }), ..Page::default() };
+                    match fumen::encode(std::slice::from_ref(&page)) {
+                        Ok(encoded) => assert_eq!(fumen::decode(&encoded).unwrap(), vec![page]),
+                        Err(error) => assert_eq!(error, "The piece of page 1 is off the field"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn bad_fumens_are_rejected() {
+    assert!(fumen::decode("v114@vhAAgH").is_err());
+    assert!(fumen::decode("vhAAgH").is_err());
+    assert!(fumen::decode("v115@vhAA").is_err());
+    assert!(fumen::decode("v115@vh!AgH").is_err());
+    assert!(fumen::decode("v115@").is_err());
+    let page = Page { field: vec![None; 10], ..Page::default() };
+    assert!(fumen::encode(&[page]).is_err());
+}
+
+#[test]
+fn games_start_from_a_fumen() {
+    let mut page = Page::default();
+    for x in 0..WIDTH - 1 {
+        page.field[(HEIGHT - 1) * WIDTH + x] = Some(BColor::GARBAGE);
+    }
+    page.piece = Some(Piece { shape: Shape::I, rotation: Rotation::R90, pos: (9, 18) });
+    let encoded = fumen::encode(&[page]).unwrap();
+    let config = Config::from_query(&format!("seed=5&fumen={}", encoded));
+    assert_eq!(config.fumen.as_deref(), Some(encoded.as_str()));
+
+    let mut engine = Engine::new(config.clone());
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.block.block.shape(), Shape::I);
+    assert_eq!(snapshot.cells[(config.height + config.buffer - 1) * config.width], BColor::GARBAGE);
+    assert_eq!(engine.fumen().unwrap(), encoded);
+
+    let resumed = Engine::resume(engine.save()).unwrap();
+    assert_eq!(resumed.snapshot().cells, snapshot.cells);
+
+    engine.handle_input(Input::HardDrop);
+    assert_eq!(engine.snapshot().lines, 1);
+    engine.new_game();
+    assert_eq!(engine.fumen().unwrap(), encoded);
+}
+
+#[test]
+fn only_ten_wide_boards_are_fumens() {
+    assert!(Config::from_query("fumen=v115@xyz").fumen.is_none());
+    let engine = Engine::new(Config { width: 8, ..Config::default() });
+    assert!(engine.fumen().is_err());
+}
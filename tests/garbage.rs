@@ -0,0 +1,131 @@
+use rand::{ rngs::StdRng, SeedableRng };
+use wretris::block::{ BColor, Block, Shape };
+use wretris::color_grid::ColorGrid;
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input, Outcome };
+use wretris::garbage::{ AttackTable, GarbageQueue, HolePlacement };
+use wretris::mode::GameModeKind;
+use wretris::rotation::RotationSystemKind;
+use wretris::scoring::Award;
+use wretris::top_out::TopOut;
+use wretris::tspin::TSpin;
+
+fn award(lines: usize, spin: TSpin) -> Award {
+    Award { lines, spin, ..Award::default() }
+}
+
+/// Hole columns of the garbage rows of `engine`, from the bottom up.
+fn holes(engine: &Engine) -> Vec<usize> {
+    let cells = engine.locked_cells();
+    cells
+        .chunks(engine.snapshot().width)
+        .rev()
+        .take_while(|row| row.iter().filter(|cell| **cell == Some(BColor::GARBAGE)).count() == row.len() - 1)
+        .map(|row| row.iter().position(Option::is_none).unwrap())
+        .collect()
+}
+
+#[test]
+fn grids_push_rows_from_below() {
+    let mut grid = ColorGrid::new(4, 6, 2, (BColor::GRID1, BColor::GRID2), BColor::HINT, RotationSystemKind::Srs, Block::new(Shape::T));
+    assert!(!grid.push_garbage(&[0, 3]));
+    assert!(!grid.is_occupied(0, 6));
+    assert!(grid.is_occupied(3, 6));
+    assert!(grid.is_occupied(0, 7));
+    assert!(!grid.is_occupied(3, 7));
+    assert!(grid.push_garbage(&[1; 7]));
+}
+
+#[test]
+fn holes_follow_the_placement() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let clean = HolePlacement::Clean.holes(8, 10, None, &mut rng);
+    assert!(clean.iter().all(|hole| *hole == clean[0]));
+    // A new attack moves the hole.
+    assert_ne!(HolePlacement::Clean.holes(3, 10, Some(clean[0]), &mut rng)[0], clean[0]);
+
+    let messy = HolePlacement::Messy.holes(8, 10, None, &mut rng);
+    assert!(messy.windows(2).all(|pair| pair[0] != pair[1]));
+
+    let steady = HolePlacement::Change(0.0).holes(8, 10, Some(4), &mut rng);
+    assert_eq!(steady, vec![4; 8]);
+    let changing = HolePlacement::Change(1.0).holes(8, 10, Some(4), &mut rng);
+    assert_ne!(changing[0], 4);
+    assert!(changing.windows(2).all(|pair| pair[0] != pair[1]));
+    assert!(HolePlacement::Change(0.3).holes(100, 10, None, &mut rng).iter().all(|hole| *hole < 10));
+
+    assert_eq!(HolePlacement::from_name("change-30"), Some(HolePlacement::Change(0.3)));
+    assert_eq!(HolePlacement::Change(0.3).name(), "change-30");
+    // Fractional percents are written out in full, so rules pass through queries intact.
+    assert_eq!(HolePlacement::Change(0.125).name(), "change-12.5");
+    let config = Config { garbage: HolePlacement::Change(0.125), ..Config::default() };
+    assert_eq!(Config::from_query(&config.to_query()).garbage, HolePlacement::Change(0.125));
+    assert_eq!(HolePlacement::from_name("change-130"), None);
+    assert_eq!(HolePlacement::from_name("messy"), Some(HolePlacement::Messy));
+}
+
+#[test]
+fn attack_table() {
+    let guideline = AttackTable::default();
+    assert_eq!(guideline.attack(&award(1, TSpin::None)), 0);
+    assert_eq!(guideline.attack(&award(2, TSpin::None)), 1);
+    assert_eq!(guideline.attack(&award(4, TSpin::None)), 4);
+    assert_eq!(guideline.attack(&Award { back_to_back: true, ..award(4, TSpin::None) }), 5);
+    assert_eq!(guideline.attack(&award(2, TSpin::Full)), 4);
+    assert_eq!(guideline.attack(&award(0, TSpin::Full)), 0);
+    assert_eq!(guideline.attack(&award(2, TSpin::Mini)), 1);
+    assert_eq!(guideline.attack(&Award { combo: 3, ..award(1, TSpin::None) }), 1);
+    assert_eq!(guideline.attack(&Award { combo: 50, ..award(1, TSpin::None) }), 5);
+    assert_eq!(guideline.attack(&Award { perfect_clear: true, ..award(4, TSpin::None) }), 14);
+
+    let classic = AttackTable::classic();
+    assert_eq!(classic.attack(&Award { back_to_back: true, combo: 5, ..award(3, TSpin::Full) }), 2);
+
+    let custom = AttackTable { back_to_back: 2, combo: vec![0, 1], ..AttackTable::default() };
+    assert_eq!(custom.name(), "0.0.1.2.4/0.2.4.6/0.0.1/2/0.1/10");
+    assert_eq!(AttackTable::from_name(&custom.name()), Some(custom));
+    assert_eq!(AttackTable::from_name("guideline"), Some(guideline));
+    assert_eq!(AttackTable::from_name("0.0.1.2/0.2.4.6/0.0.1/2/0.1/10"), None);
+    assert_eq!(AttackTable::from_name("0.0.1.2.4/0.2.4.6/0.0.1/2//10"), None);
+}
+
+#[test]
+fn attacks_cancel_the_oldest_garbage_first() {
+    let mut queue = GarbageQueue::new();
+    queue.receive(3);
+    queue.receive(2);
+    assert_eq!(queue.rows(), 5);
+    assert_eq!(queue.cancel(4), 0);
+    assert_eq!(queue.rows(), 1);
+    assert_eq!(queue.cancel(3), 2);
+    assert_eq!(queue.rows(), 0);
+}
+
+#[test]
+fn garbage_rises_when_a_block_locks_without_a_clear() {
+    let config = Config { mode: GameModeKind::Versus, garbage: HolePlacement::Messy, seed: Some(3), ..Config::default() };
+    let mut engine = Engine::new(config);
+    engine.receive_garbage(2);
+    engine.receive_garbage(3);
+    assert_eq!(engine.garbage(), 5);
+    assert_eq!(engine.snapshot().garbage, 5);
+    engine.handle_input(Input::HardDrop);
+    assert_eq!(engine.garbage(), 0);
+    let holes = holes(&engine);
+    assert_eq!(holes.len(), 5);
+    assert!(holes.windows(2).all(|pair| pair[0] != pair[1]));
+
+    // The same seed gets the same holes.
+    let mut again = Engine::new(engine.config().clone());
+    again.receive_garbage(5);
+    again.handle_input(Input::HardDrop);
+    assert_eq!(self::holes(&again), holes);
+}
+
+#[test]
+fn garbage_can_push_the_stack_out() {
+    let mut engine = Engine::new(Config { mode: GameModeKind::Versus, seed: Some(5), ..Config::default() });
+    engine.receive_garbage(40);
+    engine.handle_input(Input::HardDrop);
+    assert_eq!(engine.snapshot().outcome, Some(Outcome::ToppedOut(TopOut::GarbageOut)));
+}
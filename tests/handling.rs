@@ -0,0 +1,79 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::handling::Handling;
+
+#[test]
+fn tap_moves_once() {
+    let mut handling = Handling::new(100, 20);
+    assert_eq!(handling.press(Input::Left), vec![Input::Left]);
+    assert!(handling.tick(99).is_empty());
+    assert!(handling.release(Input::Left).is_empty());
+    assert!(handling.tick(1000).is_empty());
+}
+
+#[test]
+fn held_direction_repeats_after_das() {
+    let mut handling = Handling::new(100, 20);
+    handling.press(Input::Right);
+    assert!(handling.tick(99).is_empty());
+    assert_eq!(handling.tick(1), vec![Input::Right]);
+    assert!(handling.tick(19).is_empty());
+    assert_eq!(handling.tick(1), vec![Input::Right]);
+    assert_eq!(handling.tick(60), vec![Input::Right; 3]);
+}
+
+#[test]
+fn zero_arr_goes_to_the_wall() {
+    let mut handling = Handling::new(50, 0);
+    assert_eq!(handling.press(Input::Left), vec![Input::Left]);
+    assert!(handling.tick(49).is_empty());
+    assert_eq!(handling.tick(1), vec![Input::LeftMost]);
+}
+
+#[test]
+fn key_repeat_from_the_os_is_ignored() {
+    let mut handling = Handling::new(100, 20);
+    assert_eq!(handling.press(Input::RotateClockwise), vec![Input::RotateClockwise]);
+    assert!(handling.press(Input::RotateClockwise).is_empty());
+    handling.release(Input::RotateClockwise);
+    assert_eq!(handling.press(Input::RotateClockwise), vec![Input::RotateClockwise]);
+}
+
+#[test]
+fn last_pressed_direction_wins() {
+    let mut handling = Handling::new(100, 20);
+    handling.press(Input::Left);
+    handling.tick(50);
+    assert_eq!(handling.press(Input::Right), vec![Input::Right]);
+    assert!(handling.tick(99).is_empty());
+    assert_eq!(handling.tick(1), vec![Input::Right]);
+    // Letting go of the newer direction recharges the one still held.
+    handling.release(Input::Right);
+    assert!(handling.tick(99).is_empty());
+    assert_eq!(handling.tick(1), vec![Input::Left]);
+}
+
+#[test]
+fn soft_drop_ends_on_release() {
+    let mut handling = Handling::new(100, 20);
+    assert_eq!(handling.press(Input::SoftDrop), vec![Input::SoftDrop]);
+    assert!(handling.is_held(Input::SoftDrop));
+    assert_eq!(handling.release(Input::SoftDrop), vec![Input::SoftDropRelease]);
+    assert!(!handling.is_held(Input::SoftDrop));
+    assert!(handling.release(Input::SoftDrop).is_empty());
+}
+
+#[test]
+fn held_soft_drop_carries_over_to_the_next_block() {
+    let mut engine = Engine::new(Config { seed: Some(4), ..Config::default() });
+    let mut handling = Handling::new(100, 20);
+    for input in handling.press(Input::SoftDrop) {
+        engine.handle_input(input);
+    }
+    assert!(handling.keep_soft_drop(&engine).is_empty());
+    engine.handle_input(Input::HardDrop);
+    assert!(!engine.is_soft_dropping());
+    assert_eq!(handling.keep_soft_drop(&engine), vec![Input::SoftDrop]);
+    handling.release(Input::SoftDrop);
+    assert!(handling.keep_soft_drop(&engine).is_empty());
+}
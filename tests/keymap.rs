@@ -0,0 +1,91 @@
+use wretris::config::Config;
+use wretris::engine::Input;
+use wretris::keymap::{ Action, Conflict, Key, Keymap };
+
+#[test]
+fn default_keymap_matches_the_manual() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.action(Key::Up), Some(Action::RotateClockwise));
+    assert_eq!(keymap.action(Key::Char('e')), Some(Action::RotateClockwise));
+    assert_eq!(keymap.action(Key::Char(' ')), Some(Action::HardDrop));
+    assert_eq!(keymap.action(Key::Char('x')), None);
+    assert_eq!(keymap.label(Action::RotateClockwise), "↑,e");
+    assert_eq!(keymap.label(Action::HardDrop), "space");
+    for action in Action::all() {
+        assert!(!keymap.keys(action).is_empty(), "{:?} has no key", action);
+    }
+}
+
+#[test]
+fn letters_ignore_case() {
+    let mut keymap = Keymap::default();
+    assert_eq!(keymap.action(Key::Char('C')), Some(Action::Hold));
+    keymap.bind(Action::HardDrop, Key::Char('X')).unwrap();
+    assert_eq!(keymap.action(Key::Char('x')), Some(Action::HardDrop));
+    assert_eq!(keymap.keys(Action::HardDrop), &[Key::Char(' '), Key::Char('x')]);
+}
+
+#[test]
+fn binding_a_taken_key_is_a_conflict() {
+    let mut keymap = Keymap::default();
+    let before = keymap.clone();
+    assert_eq!(keymap.bind(Action::HardDrop, Key::Char('c')), Err(Conflict { key: Key::Char('c'), action: Action::Hold }));
+    assert_eq!(keymap, before);
+    assert_eq!(keymap.bind(Action::Hold, Key::Char('c')), Ok(()));
+    assert_eq!(keymap, before);
+
+    assert_eq!(keymap.rebind(Action::HardDrop, Key::Char('c')), Some(Action::Hold));
+    assert_eq!(keymap.action(Key::Char('c')), Some(Action::HardDrop));
+    assert!(keymap.keys(Action::Hold).is_empty());
+    assert_eq!(keymap.label(Action::Hold), "-");
+}
+
+#[test]
+fn clearing_an_action_frees_its_keys() {
+    let mut keymap = Keymap::default();
+    keymap.clear(Action::RotateClockwise);
+    assert_eq!(keymap.action(Key::Up), None);
+    assert_eq!(keymap.bind(Action::HardDrop, Key::Up), Ok(()));
+}
+
+#[test]
+fn profiles_round_trip() {
+    let mut keymap = Keymap::default();
+    keymap.rebind(Action::Hold, Key::Char('x'));
+    keymap.rebind(Action::HardDrop, Key::Enter);
+    keymap.clear(Action::FlipTurn);
+    let profile = keymap.to_profile();
+    assert!(profile.contains("hard_drop = space, enter\n"));
+    assert!(profile.contains("flip_turn = \n"));
+    assert_eq!(Keymap::from_profile(&profile), Ok(keymap));
+}
+
+#[test]
+fn profiles_fill_in_missing_actions_with_free_default_keys() {
+    let keymap = Keymap::from_profile("hold = s\n\nhard_drop = space").unwrap();
+    assert_eq!(keymap.keys(Action::Hold), &[Key::Char('s')]);
+    assert!(keymap.keys(Action::FlipTurn).is_empty());
+    assert_eq!(keymap.keys(Action::RotateClockwise), &[Key::Up, Key::Char('e')]);
+}
+
+#[test]
+fn bad_profiles_are_rejected() {
+    assert!(Keymap::from_profile("jump = space").is_err());
+    assert!(Keymap::from_profile("hold = ctrl").is_err());
+    assert_eq!(Keymap::from_profile("hold = c\nhard_drop = c"), Err("c is already bound to hold".to_string()));
+}
+
+#[test]
+fn game_actions_map_to_inputs() {
+    assert_eq!(Action::SoftDrop.input(), Some(Input::SoftDrop));
+    assert_eq!(Action::Pause.input(), None);
+    for action in Action::all() {
+        assert_eq!(Action::from_name(action.name()), Some(action));
+    }
+}
+
+#[test]
+fn profile_from_query() {
+    assert_eq!(Config::from_query("keymap=lefty").keymap, "lefty");
+    assert_eq!(Config::from_query("keymap=").keymap, "default");
+}
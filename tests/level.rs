@@ -0,0 +1,74 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::level::{ self, GravityCurve, MAX_GRAVITY };
+
+#[test]
+fn levels_advance_every_n_lines() {
+    assert_eq!(level::level(1, 0, 10), 1);
+    assert_eq!(level::level(1, 9, 10), 1);
+    assert_eq!(level::level(1, 10, 10), 2);
+    assert_eq!(level::level(5, 25, 10), 7);
+    assert_eq!(level::level(0, 12, 4), 3);
+}
+
+#[test]
+fn guideline_curve_matches_the_formula() {
+    let curve = GravityCurve::Guideline;
+    assert!((curve.gravity(1) - 1.0 / 60.0).abs() < 1e-9);
+    // Level 15 takes about 7 ms per row.
+    let seconds_per_row = 1.0 / (60.0 * curve.gravity(15));
+    assert!((seconds_per_row - 0.00706).abs() < 0.00001);
+    for level in 1..20 {
+        assert!(curve.gravity(level) <= curve.gravity(level + 1));
+    }
+    assert_eq!(curve.gravity(20), MAX_GRAVITY);
+}
+
+#[test]
+fn nes_and_tgm_presets() {
+    assert_eq!(GravityCurve::Nes.gravity(0), 1.0 / 48.0);
+    assert_eq!(GravityCurve::Nes.gravity(9), 1.0 / 6.0);
+    assert_eq!(GravityCurve::Nes.gravity(12), 1.0 / 5.0);
+    assert_eq!(GravityCurve::Nes.gravity(29), 1.0);
+    assert_eq!(GravityCurve::Tgm.gravity(0), 4.0 / 256.0);
+    assert_eq!(GravityCurve::Tgm.gravity(20), 4.0 / 256.0);
+    assert_eq!(GravityCurve::Tgm.gravity(30), 2.0);
+    assert_eq!(GravityCurve::Tgm.gravity(50), MAX_GRAVITY);
+    assert_eq!(GravityCurve::Fixed(0.5).gravity(15), 0.5);
+}
+
+#[test]
+fn gravity_from_query() {
+    assert_eq!(Config::from_query("gravity=nes").gravity_curve, GravityCurve::Nes);
+    assert_eq!(Config::from_query("gravity=0.25").gravity_curve, GravityCurve::Fixed(0.25));
+    assert_eq!(Config::from_query("gravity=fast").gravity_curve, GravityCurve::Guideline);
+    assert_eq!(Config::from_query("gravity=NaN").gravity_curve, GravityCurve::Guideline);
+    assert_eq!(Config::from_query("gravity=inf").gravity_curve, GravityCurve::Guideline);
+    assert_eq!(Config::from_query("gravity=-1").gravity_curve, GravityCurve::Guideline);
+    let config = Config::from_query("level=7&lines_per_level=5");
+    assert_eq!(config.start_level, 7);
+    assert_eq!(config.lines_per_level, 5);
+}
+
+#[test]
+fn guideline_gravity_stays_at_twenty_g_past_level_twenty() {
+    for level in 20..=200 {
+        assert_eq!(GravityCurve::Guideline.gravity(level), MAX_GRAVITY);
+    }
+    let mut engine = Engine::new(Config { seed: Some(1), start_level: 150, ..Config::default() });
+    engine.tick(1);
+    let snapshot = engine.snapshot();
+    assert_eq!(snapshot.block.pos, snapshot.hint.pos);
+}
+
+#[test]
+fn new_game_starts_at_the_chosen_level() {
+    let mut engine = Engine::new(Config { seed: Some(1), ..Config::default() });
+    assert_eq!(engine.snapshot().level, 1);
+    engine.set_start_level(8);
+    engine.new_game();
+    assert_eq!(engine.snapshot().level, 8);
+    assert_eq!(engine.gravity(), GravityCurve::Guideline.gravity(8));
+    engine.handle_input(Input::HardDrop);
+    assert_eq!(engine.snapshot().level, 8);
+}
@@ -0,0 +1,108 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, GameEvent, Input };
+use wretris::lock_delay::{ LockDelay, LockReset };
+
+fn locked(events: &[GameEvent]) -> bool {
+    events.iter().any(|e| matches!(e, GameEvent::Locked { .. }))
+}
+
+#[test]
+fn timer_runs_out_after_the_delay() {
+    let mut lock = LockDelay::new(500, LockReset::Move, 15);
+    lock.renew(0);
+    assert!(!lock.tick(300));
+    assert!((lock.progress() - 0.6).abs() < 1e-9);
+    assert!(lock.tick(200));
+}
+
+#[test]
+fn move_reset_is_limited() {
+    let mut lock = LockDelay::new(500, LockReset::Move, 15);
+    lock.renew(0);
+    for _ in 0..15 {
+        assert!(!lock.tick(400));
+        lock.on_move();
+    }
+    assert_eq!(lock.resets_left(), 0);
+    lock.on_move();
+    assert!(lock.tick(0));
+}
+
+#[test]
+fn new_lowest_row_gives_resets_back() {
+    let mut lock = LockDelay::new(500, LockReset::Move, 15);
+    lock.renew(0);
+    for _ in 0..15 {
+        lock.on_move();
+    }
+    lock.on_step_down(1);
+    assert_eq!(lock.resets_left(), 15);
+    assert_eq!(lock.progress(), 0.0);
+}
+
+#[test]
+fn step_reset_ignores_moves() {
+    let mut lock = LockDelay::new(500, LockReset::Step, 15);
+    lock.renew(0);
+    assert!(!lock.tick(400));
+    lock.on_move();
+    assert!(lock.tick(100));
+    lock.on_step_down(0);
+    assert!(!lock.tick(100));
+}
+
+fn is_grounded(engine: &Engine) -> bool {
+    engine.snapshot().hint.pos == engine.snapshot().block.pos
+}
+
+fn grounded_engine(config: Config) -> Engine {
+    let mut engine = Engine::new(config);
+    while !is_grounded(&engine) {
+        engine.tick(16);
+    }
+    engine
+}
+
+#[test]
+fn grounded_block_locks_after_the_delay_in_milliseconds() {
+    let mut engine = grounded_engine(Config { seed: Some(1), lock_delay: 500, ..Config::default() });
+    assert!(!locked(&engine.tick(250)));
+    assert!(engine.snapshot().lock_progress > 0.0);
+    assert!(locked(&engine.tick(250)));
+}
+
+#[test]
+fn moves_in_the_air_keep_the_resets() {
+    // A block resting on the one dropped before it.
+    let mut engine = Engine::new(Config { seed: Some(1), lock_delay: 500, ..Config::default() });
+    engine.handle_input(Input::HardDrop);
+    while !is_grounded(&engine) {
+        engine.tick(16);
+    }
+    // Off the ledge, back and forth in the air without falling a row, and back onto it.
+    let mut shifts = 0;
+    while is_grounded(&engine) {
+        engine.handle_input(Input::Right);
+        shifts += 1;
+    }
+    for i in 0..20 {
+        engine.handle_input(if i % 2 == 0 { Input::Right } else { Input::Left });
+    }
+    for _ in 0..shifts {
+        engine.handle_input(Input::Left);
+    }
+    assert!(is_grounded(&engine));
+    assert!(!locked(&engine.tick(400)));
+}
+
+#[test]
+fn moving_a_grounded_block_resets_the_lock_delay_fifteen_times() {
+    let mut engine = grounded_engine(Config { seed: Some(1), lock_delay: 500, ..Config::default() });
+    for i in 0..15 {
+        assert!(!locked(&engine.tick(400)));
+        let input = if i % 2 == 0 { Input::Left } else { Input::Right };
+        engine.handle_input(input);
+    }
+    engine.handle_input(Input::Left);
+    assert!(locked(&engine.tick(1)));
+}
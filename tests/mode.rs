@@ -0,0 +1,72 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, Outcome };
+use wretris::mode::{ GameModeKind, Progress };
+
+fn progress(lines: usize, level: usize, elapsed: u64) -> Progress {
+    Progress { points: 0, lines, level, elapsed }
+}
+
+#[test]
+fn sprint_ends_after_its_lines() {
+    let sprint = GameModeKind::Sprint { lines: 20 }.create();
+    assert!(!sprint.is_complete(&progress(19, 2, 0)));
+    assert!(sprint.is_complete(&progress(20, 3, 0)));
+    assert_eq!(sprint.hud(&progress(7, 1, 0))[0], "Lines: 07 / 20");
+}
+
+#[test]
+fn marathon_ends_after_its_last_level_unless_endless() {
+    let marathon = GameModeKind::Marathon { max_level: Some(15) }.create();
+    assert!(!marathon.is_complete(&progress(149, 15, 0)));
+    assert!(marathon.is_complete(&progress(150, 16, 0)));
+    let endless = GameModeKind::Marathon { max_level: None }.create();
+    assert!(!endless.is_complete(&progress(10_000, 1001, 0)));
+}
+
+#[test]
+fn ultra_counts_down() {
+    let ultra = GameModeKind::Ultra { duration: 120_000 }.create();
+    assert_eq!(ultra.clock(&progress(0, 1, 20_000)), 100_000);
+    assert!(!ultra.is_complete(&progress(0, 1, 119_999)));
+    assert!(ultra.is_complete(&progress(0, 1, 120_000)));
+}
+
+#[test]
+fn mode_names() {
+    for kind in GameModeKind::all() {
+        assert_eq!(GameModeKind::from_name(&kind.name()), Some(kind));
+    }
+    assert_eq!(GameModeKind::from_name("sprint"), Some(GameModeKind::Sprint { lines: 40 }));
+    assert_eq!(GameModeKind::from_name("ultra-3"), Some(GameModeKind::Ultra { duration: 180_000 }));
+    assert_eq!(GameModeKind::from_name("zen-1"), None);
+    assert_eq!(GameModeKind::from_name("tetris"), None);
+}
+
+#[test]
+fn ultra_game_ends_when_time_is_up() {
+    let mut engine = Engine::new(Config { mode: GameModeKind::Ultra { duration: 2000 }, ..Config::default() });
+    engine.tick(1999);
+    assert!(!engine.is_gameover());
+    assert_eq!(engine.snapshot().clock, 1);
+    engine.tick(100);
+    assert_eq!(engine.outcome(), Some(Outcome::Complete));
+    assert_eq!(engine.snapshot().elapsed, 2000);
+}
+
+#[test]
+fn zen_never_tops_out() {
+    let mut engine = Engine::new(Config { seed: Some(2), mode: GameModeKind::Zen, ..Config::default() });
+    for _ in 0..100_000 {
+        engine.tick(16);
+    }
+    assert!(!engine.is_gameover());
+}
+
+#[test]
+fn new_game_switches_mode() {
+    let mut engine = Engine::default();
+    engine.set_mode(GameModeKind::Zen);
+    engine.new_game();
+    assert_eq!(engine.snapshot().mode, GameModeKind::Zen);
+    assert_eq!(engine.snapshot().hud[0], "Lines: 00");
+}
@@ -0,0 +1,100 @@
+use wretris::config::Config;
+use wretris::engine::Input;
+use wretris::net::{ ClientMessage, Match, ServerMessage, State, Step };
+
+/// What the server makes of a message of the other player.
+fn relayed(message: ClientMessage) -> Option<ServerMessage> {
+    match message {
+        ClientMessage::Sync { round, elapsed, steps } => Some(ServerMessage::Sync { round, elapsed, steps }),
+        ClientMessage::Attack { round, lines } => Some(ServerMessage::Attack { round, lines }),
+        _ => None,
+    }
+}
+
+/// Two players seated in a room, playing the first round.
+fn playing() -> (Match, Match) {
+    let mut players = (Match::new(Config::default(), "room", "alice"), Match::new(Config::default(), "room", "bob"));
+    let rules = Config { best_of: 1, ..Config::default() }.to_query();
+    for (seat, player) in [&mut players.0, &mut players.1].iter_mut().enumerate() {
+        player.outgoing();
+        let lobby = ServerMessage::Lobby { room: "room".to_string(), players: vec!["alice".to_string(), "bob".to_string()], ready: vec![false; 2], seat };
+        player.on_message(lobby).unwrap();
+        player.on_message(ServerMessage::Start { round: 1, seed: 7, config: rules.clone() }).unwrap();
+    }
+    players
+}
+
+#[test]
+fn messages_are_json() {
+    let message = ClientMessage::Sync { round: 2, elapsed: 120, steps: vec![(100, Step::Input(Input::HardDrop)), (110, Step::Garbage(3))] };
+    let json = message.encode();
+    assert_eq!(json, r#"{"type":"sync","round":2,"elapsed":120,"steps":[[100,{"input":"hard_drop"}],[110,{"garbage":3}]]}"#);
+    assert_eq!(ClientMessage::decode(&json), Ok(message));
+    let message = ServerMessage::RoundOver { round: 1, winner: 0, wins: vec![1, 0] };
+    assert_eq!(ServerMessage::decode(&message.encode()), Ok(message));
+    assert!(ServerMessage::decode(r#"{"type":"nonsense"}"#).is_err());
+}
+
+#[test]
+fn clients_join_and_start_on_the_shared_rules() {
+    let mut player = Match::new(Config { best_of: 5, ..Config::default() }, "room", "alice");
+    assert_eq!(player.state(), State::Lobby);
+    match &player.outgoing()[..] {
+        [ClientMessage::Join { room, name, config }] => {
+            assert_eq!((room.as_str(), name.as_str()), ("room", "alice"));
+            assert_eq!(Config::from_query(config).best_of, 5);
+        }
+        messages => panic!("{:?}", messages),
+    }
+    player.ready();
+    assert_eq!(player.outgoing(), vec![ClientMessage::Ready]);
+
+    let (alice, bob) = playing();
+    assert_eq!((alice.seat(), bob.seat()), (0, 1));
+    assert_eq!(alice.state(), State::Playing);
+    assert_eq!(alice.config().best_of, 1);
+    assert_eq!(alice.engine().seed(), 7);
+    assert_eq!(bob.engine().seed(), 7);
+    assert_eq!(alice.engine().snapshot().block.block.shape(), bob.engine().snapshot().block.block.shape());
+}
+
+#[test]
+fn opponents_follow_the_synced_game() {
+    let (mut alice, mut bob) = playing();
+    for input in [Input::Left, Input::RotateClockwise, Input::HardDrop, Input::Right, Input::Hold, Input::HardDrop].iter() {
+        alice.tick(150);
+        alice.handle_input(*input);
+    }
+    alice.tick(2000);
+    bob.on_message(ServerMessage::Attack { round: 1, lines: 2 }).unwrap();
+    bob.tick(5);
+    bob.handle_input(Input::HardDrop);
+    for message in alice.outgoing() {
+        bob.on_message(relayed(message).unwrap()).unwrap();
+    }
+    for message in bob.outgoing() {
+        alice.on_message(relayed(message).unwrap()).unwrap();
+    }
+    assert_eq!(bob.opponent().elapsed(), alice.engine().elapsed());
+    assert_eq!(bob.opponent().snapshot().cells, alice.engine().snapshot().cells);
+    assert_eq!(alice.opponent().snapshot().cells, bob.engine().snapshot().cells);
+    assert_eq!(alice.opponent().garbage(), 0);
+    // Nothing new to sync.
+    assert!(alice.outgoing().is_empty());
+}
+
+#[test]
+fn topping_out_is_reported_once() {
+    let (mut alice, _) = playing();
+    let mut reports = 0;
+    for _ in 0..100 {
+        alice.handle_input(Input::HardDrop);
+        reports += alice.outgoing().iter().filter(|message| **message == ClientMessage::ToppedOut { round: 1 }).count();
+    }
+    assert_eq!(reports, 1);
+    alice.on_message(ServerMessage::MatchOver { winner: 1, wins: vec![0, 1] }).unwrap();
+    assert_eq!(alice.state(), State::MatchOver { winner: 1 });
+    assert_eq!(alice.wins(), [0, 1]);
+    assert_eq!(alice.on_message(ServerMessage::Left { name: "bob".to_string() }), Err("bob left the match".to_string()));
+    assert_eq!(alice.state(), State::Lobby);
+}
@@ -0,0 +1,86 @@
+use wretris::block::Shape;
+use wretris::randomizer::RandomizerKind;
+
+use rand::{ rngs::StdRng, SeedableRng };
+
+fn deal(kind: RandomizerKind, seed: u64, count: usize) -> Vec<Shape> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut randomizer = kind.create();
+    (0..count).map(|_| randomizer.next(&mut rng)).collect()
+}
+
+fn counts(shapes: &[Shape]) -> [usize; 7] {
+    let mut counts = [0; 7];
+    for shape in shapes {
+        counts[*shape as usize] += 1;
+    }
+    counts
+}
+
+#[test]
+fn seven_bag_deals_every_shape_once_per_bag() {
+    for seed in 0..20 {
+        for bag in deal(RandomizerKind::SevenBag, seed, 700).chunks(7) {
+            assert_eq!(counts(bag), [1; 7]);
+        }
+    }
+}
+
+#[test]
+fn seven_bag_never_waits_more_than_twelve_pieces() {
+    let shapes = deal(RandomizerKind::SevenBag, 1, 7000);
+    for shape in 0..7 {
+        let positions: Vec<usize> = shapes.iter().enumerate().filter(|(_, s)| **s as usize == shape).map(|(i, _)| i).collect();
+        assert!(positions.windows(2).all(|w| w[1] - w[0] <= 13));
+    }
+}
+
+#[test]
+fn fourteen_bag_deals_every_shape_twice_per_bag() {
+    for seed in 0..20 {
+        for bag in deal(RandomizerKind::FourteenBag, seed, 1400).chunks(14) {
+            assert_eq!(counts(bag), [2; 7]);
+        }
+    }
+}
+
+#[test]
+fn memoryless_is_roughly_uniform() {
+    let shapes = deal(RandomizerKind::Memoryless, 3, 70_000);
+    for count in counts(&shapes).iter() {
+        assert!(*count > 9_000 && *count < 11_000, "count {}", count);
+    }
+}
+
+#[test]
+fn memoryless_repeats_pieces() {
+    let shapes = deal(RandomizerKind::Memoryless, 4, 7000);
+    let repeats = shapes.windows(2).filter(|w| w[0] == w[1]).count();
+    assert!(repeats > 700, "repeats {}", repeats);
+}
+
+#[test]
+fn tgm_never_starts_with_s_z_or_o() {
+    for seed in 0..200 {
+        let first = deal(RandomizerKind::Tgm, seed, 1)[0];
+        assert!(first != Shape::S && first != Shape::Z && first != Shape::O);
+    }
+}
+
+#[test]
+fn tgm_rarely_repeats_recent_pieces() {
+    let shapes = deal(RandomizerKind::Tgm, 5, 70_000);
+    let repeats = shapes.windows(2).filter(|w| w[0] == w[1]).count();
+    assert!(repeats < 1400, "repeats {}", repeats);
+    for count in counts(&shapes).iter() {
+        assert!(*count > 8_000 && *count < 12_000, "count {}", count);
+    }
+}
+
+#[test]
+fn randomizers_are_deterministic_per_seed() {
+    for kind in RandomizerKind::all() {
+        assert_eq!(deal(kind, 9, 100), deal(kind, 9, 100));
+        assert_eq!(RandomizerKind::from_name(kind.name()), Some(kind));
+    }
+}
@@ -0,0 +1,118 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::level::GravityCurve;
+use wretris::mode::GameModeKind;
+use wretris::replay::{ Playback, Replay };
+use wretris::top_out::TopOutRules;
+
+const INPUTS: [Input; 8] = [
+    Input::Left, Input::Right, Input::RotateClockwise, Input::RotateCounterClockwise,
+    Input::SoftDrop, Input::SoftDropRelease, Input::HardDrop, Input::Hold,
+];
+
+/// Plays a game of irregular ticks and inputs.
+fn play(config: Config) -> Engine {
+    let mut engine = Engine::new(config);
+    let mut state: u64 = 1;
+    for _ in 0..400 {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        engine.tick(state >> 59);
+        engine.handle_input(INPUTS[(state >> 40) as usize % INPUTS.len()]);
+    }
+    engine
+}
+
+fn assert_same_game(a: &Engine, b: &Engine) {
+    let (a, b) = (a.snapshot(), b.snapshot());
+    assert_eq!(a.cells, b.cells);
+    assert_eq!(a.block.pos, b.block.pos);
+    assert_eq!(a.points, b.points);
+    assert_eq!(a.lines, b.lines);
+    assert_eq!(a.elapsed, b.elapsed);
+    assert_eq!(a.outcome, b.outcome);
+}
+
+#[test]
+fn playback_repeats_the_game_exactly() {
+    let engine = play(Config { gravity_curve: GravityCurve::Fixed(0.3), ..Config::default() });
+    let replay = Replay::decode(&Replay::of(&engine).encode()).unwrap();
+    assert_eq!(replay, Replay::of(&engine));
+    let mut playback = Playback::new(replay);
+    while !playback.is_finished() {
+        playback.advance(16);
+    }
+    assert_same_game(playback.engine(), &engine);
+}
+
+#[test]
+fn config_round_trips_through_the_query() {
+    let config = Config {
+        mode: GameModeKind::Marathon { max_level: None },
+        top_out: Some(TopOutRules::none()),
+        width: 8,
+        seed: Some(99),
+        previews: 5,
+        hold: false,
+        gravity_curve: GravityCurve::Fixed(0.05),
+        soft_drop_factor: 20.5,
+        max_lock_resets: 3,
+        ..Config::default()
+    };
+    assert_eq!(Config::from_query(&config.to_query()), config);
+    assert_eq!(Config::from_query(&Config::default().to_query()), Config::default());
+}
+
+#[test]
+fn speed_scales_the_clock() {
+    let replay = Replay::of(&play(Config::default()));
+    let mut playback = Playback::new(replay);
+    playback.set_speed(2.0);
+    playback.advance(100);
+    assert_eq!(playback.time(), 200);
+    playback.set_speed(0.1);
+    assert_eq!(playback.speed(), 0.25);
+    playback.advance(10);
+    playback.advance(10);
+    assert_eq!(playback.time(), 205);
+    playback.set_speed(9.0);
+    assert_eq!(playback.speed(), 4.0);
+}
+
+#[test]
+fn paused_playback_only_moves_by_frames() {
+    let mut playback = Playback::new(Replay::of(&play(Config::default())));
+    assert!(playback.toggle_pause());
+    playback.advance(500);
+    assert_eq!(playback.time(), 0);
+    playback.step_frame();
+    assert_eq!(playback.time(), 17);
+    playback.step_frame();
+    assert_eq!(playback.time(), 34);
+    playback.step_frame();
+    assert_eq!(playback.time(), 50);
+}
+
+#[test]
+fn seeking_back_and_forth_lands_on_the_same_state() {
+    let replay = Replay::of(&play(Config::default()));
+    let middle = replay.duration / 2;
+    let mut straight = Playback::new(replay.clone());
+    straight.seek(middle);
+    let mut around = Playback::new(replay);
+    around.seek(middle + 1000);
+    around.seek(middle);
+    assert_eq!(around.time(), middle);
+    assert_same_game(around.engine(), straight.engine());
+    around.seek(u64::MAX);
+    assert!(around.is_finished());
+}
+
+#[test]
+fn bad_replays_are_rejected() {
+    let text = Replay::of(&play(Config::default())).encode();
+    assert!(Replay::decode(&text.replacen("wretris-replay 1", "wretris-replay 0", 1)).is_err());
+    assert!(Replay::decode("hello").is_err());
+    assert!(Replay::decode("wretris-replay 1\nwidth=10\n100\n0h").is_err());
+    assert!(Replay::decode("wretris-replay 1\nseed=1\n100\n0h 5x").is_err());
+    assert_eq!(Replay::decode("wretris-replay 1\nseed=1\n100\n0h 5L").unwrap().inputs, vec![(0, Input::HardDrop), (5, Input::LeftMost)]);
+}
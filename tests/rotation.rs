@@ -0,0 +1,169 @@
+use wretris::block::{ BColor, Block, BlockWithPos, Rotation, Shape };
+use wretris::color_grid::ColorGrid;
+use wretris::pos::Pos;
+use wretris::rotation::{ RotationSystem, RotationSystemKind, Srs, Turn };
+
+// Guideline piece states inside their bounding box, y pointing down.
+fn srs_cells(shape: Shape, state: usize) -> Vec<Pos> {
+    let states: [[Pos; 4]; 4] = match shape {
+        Shape::I => [[(0, 1), (1, 1), (2, 1), (3, 1)], [(2, 0), (2, 1), (2, 2), (2, 3)], [(0, 2), (1, 2), (2, 2), (3, 2)], [(1, 0), (1, 1), (1, 2), (1, 3)]],
+        Shape::J => [[(0, 0), (0, 1), (1, 1), (2, 1)], [(1, 0), (2, 0), (1, 1), (1, 2)], [(0, 1), (1, 1), (2, 1), (2, 2)], [(1, 0), (1, 1), (0, 2), (1, 2)]],
+        Shape::L => [[(2, 0), (0, 1), (1, 1), (2, 1)], [(1, 0), (1, 1), (1, 2), (2, 2)], [(0, 1), (1, 1), (2, 1), (0, 2)], [(0, 0), (1, 0), (1, 1), (1, 2)]],
+        Shape::S => [[(1, 0), (2, 0), (0, 1), (1, 1)], [(1, 0), (1, 1), (2, 1), (2, 2)], [(1, 1), (2, 1), (0, 2), (1, 2)], [(0, 0), (0, 1), (1, 1), (1, 2)]],
+        Shape::Z => [[(0, 0), (1, 0), (1, 1), (2, 1)], [(2, 0), (1, 1), (2, 1), (1, 2)], [(0, 1), (1, 1), (1, 2), (2, 2)], [(1, 0), (0, 1), (1, 1), (0, 2)]],
+        Shape::T => [[(1, 0), (0, 1), (1, 1), (2, 1)], [(1, 0), (1, 1), (2, 1), (1, 2)], [(0, 1), (1, 1), (2, 1), (1, 2)], [(1, 0), (0, 1), (1, 1), (1, 2)]],
+        Shape::O => [[(1, 0), (2, 0), (1, 1), (2, 1)]; 4],
+    };
+    states[state].to_vec()
+}
+
+// Published kick tables, y pointing up: (from, to, kicks).
+const JLSTZ: [(usize, usize, [Pos; 5]); 8] = [
+    (0, 1, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+    (1, 0, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+    (1, 2, [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)]),
+    (2, 1, [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)]),
+    (2, 3, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+    (3, 2, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+    (3, 0, [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)]),
+    (0, 3, [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]),
+];
+
+const I: [(usize, usize, [Pos; 5]); 8] = [
+    (0, 1, [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]),
+    (1, 0, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]),
+    (1, 2, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+    (2, 1, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+    (2, 3, [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)]),
+    (3, 2, [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)]),
+    (3, 0, [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)]),
+    (0, 3, [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)]),
+];
+
+const FLIP: [(usize, usize, [Pos; 6]); 4] = [
+    (0, 2, [(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)]),
+    (1, 3, [(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)]),
+    (2, 0, [(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)]),
+    (3, 1, [(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)]),
+];
+
+fn empty_grid() -> ColorGrid {
+    ColorGrid::new(10, 20, 0, (BColor::GRID1, BColor::GRID2), BColor::HINT, RotationSystemKind::Srs, Block::new(Shape::T))
+}
+
+fn sorted(mut cells: Vec<Pos>) -> Vec<Pos> {
+    cells.sort_unstable();
+    cells
+}
+
+fn in_box(shape: Shape, state: usize, origin: Pos) -> Vec<Pos> {
+    sorted(srs_cells(shape, state).into_iter().map(|(x, y)| (x + origin.0, y + origin.1)).collect())
+}
+
+fn turn_between(from: usize, to: usize) -> Turn {
+    match (to + 4 - from) % 4 {
+        1 => Turn::Clockwise,
+        2 => Turn::Flip,
+        _ => Turn::CounterClockwise,
+    }
+}
+
+// Brings a freshly spawned piece into `state` on an empty grid and moves its box to `origin`.
+fn piece_in_state(shape: Shape, state: usize, origin: Pos) -> BlockWithPos {
+    let grid = empty_grid();
+    let spawn_origin = (3, 5);
+    let pivot = if shape == Shape::I { (2, 1) } else { (1, 1) };
+    let mut block = BlockWithPos::from(Block::new(shape), (spawn_origin.0 + pivot.0, spawn_origin.1 + pivot.1));
+    for _ in 0..state {
+        block = Srs.rotate(&grid, &block, Turn::Clockwise).unwrap().0;
+    }
+    assert_eq!(sorted(block.cells()), in_box(shape, state, spawn_origin));
+    block.pos = (block.pos.0 + origin.0 - spawn_origin.0, block.pos.1 + origin.1 - spawn_origin.1);
+    block
+}
+
+fn check_kicks(shape: Shape, from: usize, to: usize, kicks: &[Pos]) {
+    let origin = (3, 8);
+    let candidates: Vec<Vec<Pos>> = kicks.iter()
+        .map(|(dx, dy)| in_box(shape, to, (origin.0 + dx, origin.1 - dy)))
+        .collect();
+    for (expected, target) in candidates.iter().enumerate() {
+        let mut grid = empty_grid();
+        for blocked in &candidates[..expected] {
+            for (x, y) in blocked.iter().filter(|cell| !target.contains(cell)) {
+                grid.set(*x as usize, *y as usize, BColor::Z);
+            }
+        }
+        let block = piece_in_state(shape, from, origin);
+        let (rotated, kick) = Srs.rotate(&grid, &block, turn_between(from, to))
+            .unwrap_or_else(|| panic!("{:?} {} -> {} kick {} failed", shape, from, to, expected));
+        assert_eq!(kick, expected, "{:?} {} -> {}", shape, from, to);
+        assert_eq!(sorted(rotated.cells()), *target, "{:?} {} -> {} kick {}", shape, from, to, expected);
+    }
+
+    let mut grid = empty_grid();
+    for blocked in &candidates {
+        for (x, y) in blocked {
+            grid.set(*x as usize, *y as usize, BColor::Z);
+        }
+    }
+    let block = piece_in_state(shape, from, origin);
+    assert!(Srs.rotate(&grid, &block, turn_between(from, to)).is_none());
+}
+
+#[test]
+fn srs_jlstz_kicks() {
+    for shape in [Shape::J, Shape::L, Shape::S, Shape::Z, Shape::T].iter() {
+        for (from, to, kicks) in JLSTZ.iter() {
+            check_kicks(*shape, *from, *to, kicks);
+        }
+    }
+}
+
+#[test]
+fn srs_i_kicks() {
+    for (from, to, kicks) in I.iter() {
+        check_kicks(Shape::I, *from, *to, kicks);
+    }
+}
+
+#[test]
+fn srs_flip_kicks() {
+    for shape in [Shape::I, Shape::J, Shape::L, Shape::S, Shape::Z, Shape::T].iter() {
+        for (from, to, kicks) in FLIP.iter() {
+            check_kicks(*shape, *from, *to, kicks);
+        }
+    }
+}
+
+#[test]
+fn srs_o_does_not_move() {
+    let grid = empty_grid();
+    let block = BlockWithPos::from(Block::new(Shape::O), (4, 5));
+    for turn in [Turn::Clockwise, Turn::CounterClockwise, Turn::Flip].iter() {
+        let (rotated, kick) = Srs.rotate(&grid, &block, *turn).unwrap();
+        assert_eq!(kick, 0);
+        assert_eq!(sorted(rotated.cells()), sorted(block.cells()));
+    }
+    assert_eq!(Srs::kicks(Shape::O, Rotation::R0, Turn::Flip), vec![(0, 0)]);
+}
+
+#[test]
+fn srs_kicks_off_the_left_wall() {
+    let grid = empty_grid();
+    let block = piece_in_state(Shape::I, 1, (-2, 5));
+    let (rotated, kick) = Srs.rotate(&grid, &block, Turn::CounterClockwise).unwrap();
+    assert_eq!(kick, 1);
+    assert!(rotated.cells().iter().all(|(x, _)| *x >= 0));
+}
+
+#[test]
+fn classic_turns_in_place_or_not_at_all() {
+    let classic = RotationSystemKind::Classic.system();
+    let grid = empty_grid();
+    let block = BlockWithPos::from(Block::new(Shape::T), (0, 5));
+    assert!(classic.rotate(&grid, &block, Turn::CounterClockwise).is_none());
+    let (rotated, kick) = classic.rotate(&grid, &block, Turn::Clockwise).unwrap();
+    assert_eq!(kick, 0);
+    assert_eq!(rotated.pos, block.pos);
+}
@@ -0,0 +1,85 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::randomizer::RandomizerKind;
+use wretris::replay::{ Playback, Replay };
+use wretris::save::Save;
+
+const INPUTS: [Input; 8] = [
+    Input::Left, Input::RotateClockwise, Input::Hold, Input::RightMost,
+    Input::SoftDrop, Input::SoftDropRelease, Input::HardDrop, Input::FlipTurn,
+];
+
+/// Plays `moves` rounds of irregular ticks and inputs, continuing from `state`.
+fn play(engine: &mut Engine, state: &mut u64, moves: usize) {
+    for _ in 0..moves {
+        *state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        engine.tick(*state >> 59);
+        engine.handle_input(INPUTS[(*state >> 40) as usize % INPUTS.len()]);
+    }
+}
+
+fn assert_same_game(a: &Engine, b: &Engine) {
+    let (a, b) = (a.snapshot(), b.snapshot());
+    assert_eq!(a.cells, b.cells);
+    assert_eq!(a.block.pos, b.block.pos);
+    assert_eq!(a.block.block.shape(), b.block.block.shape());
+    assert_eq!(a.block.block.rotation(), b.block.block.rotation());
+    let shapes = |queue: &[wretris::block::Block]| queue.iter().map(|block| block.shape()).collect::<Vec<_>>();
+    assert_eq!(shapes(&a.queue), shapes(&b.queue));
+    assert_eq!(a.hold.map(|block| block.shape()), b.hold.map(|block| block.shape()));
+    assert_eq!(a.can_hold, b.can_hold);
+    assert_eq!((a.points, a.lines, a.combo, a.back_to_back), (b.points, b.lines, b.combo, b.back_to_back));
+    assert_eq!(a.elapsed, b.elapsed);
+    assert_eq!(a.outcome, b.outcome);
+}
+
+#[test]
+fn resumed_games_go_on_exactly() {
+    for randomizer in RandomizerKind::all() {
+        let config = Config { seed: Some(21), randomizer, hold: true, ..Config::default() };
+        let mut original = Engine::new(config);
+        let mut state = 1;
+        play(&mut original, &mut state, 60);
+        let save = Save::decode(&original.save().encode()).unwrap();
+        assert_eq!(save, original.save());
+        let mut resumed = Engine::resume(save).unwrap();
+        assert_same_game(&resumed, &original);
+
+        let mut other = state;
+        play(&mut original, &mut state, 120);
+        play(&mut resumed, &mut other, 120);
+        assert_same_game(&resumed, &original);
+    }
+}
+
+#[test]
+fn replays_cover_resumed_games() {
+    let mut original = Engine::new(Config { seed: Some(4), ..Config::default() });
+    let mut state = 7;
+    play(&mut original, &mut state, 50);
+    let mut resumed = Engine::resume(original.save()).unwrap();
+    play(&mut resumed, &mut state, 50);
+    let mut playback = Playback::new(Replay::of(&resumed));
+    playback.seek(u64::MAX);
+    assert_same_game(playback.engine(), &resumed);
+}
+
+#[test]
+fn incompatible_saves_are_rejected() {
+    let text = Engine::new(Config { seed: Some(1), ..Config::default() }).save().encode();
+    assert!(Save::decode(&text).is_ok());
+    assert_eq!(
+        Save::decode(&text.replacen("wretris-save 1", "wretris-save 2", 1)),
+        Err("Saves of version 2 can't be resumed".to_string())
+    );
+    assert!(Save::decode("wretris-replay 1").is_err());
+    assert!(Save::decode(&text.replacen("..........", ".....", 1)).is_err());
+    assert!(Save::decode(&text.replacen("points=0", "points=x", 1)).is_err());
+
+    let mut save = Save::decode(&text).unwrap();
+    save.cells.pop();
+    assert!(Engine::resume(save).is_err());
+    let mut save = Save::decode(&text).unwrap();
+    save.pos = (-5, 0);
+    assert!(Engine::resume(save).is_err());
+}
@@ -0,0 +1,117 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, GameEvent, Input };
+use wretris::scoring::{ Award, Scoring };
+use wretris::tspin::TSpin;
+
+#[test]
+fn line_clears_scale_with_level() {
+    let mut scoring = Scoring::new();
+    assert_eq!(scoring.lock(1, TSpin::None, 1, false).points, 100);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.lock(2, TSpin::None, 1, false).points, 300);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.lock(3, TSpin::None, 2, false).points, 1000);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.lock(4, TSpin::None, 3, false).points, 2400);
+    assert_eq!(scoring.points(), 100 + 300 + 1000 + 2400);
+}
+
+#[test]
+fn locks_without_lines_score_nothing_and_break_the_combo() {
+    let mut scoring = Scoring::new();
+    assert_eq!(scoring.lock(0, TSpin::None, 5, false), Award::default());
+    scoring.lock(1, TSpin::None, 1, false);
+    assert_eq!(scoring.combo(), Some(0));
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.combo(), None);
+}
+
+#[test]
+fn drops_score_per_cell() {
+    let mut scoring = Scoring::new();
+    scoring.soft_drop(3);
+    scoring.hard_drop(10);
+    assert_eq!(scoring.points(), 23);
+}
+
+#[test]
+fn combos_add_fifty_per_step_and_level() {
+    let mut scoring = Scoring::new();
+    let awards: Vec<Award> = (0..4).map(|_| scoring.lock(1, TSpin::None, 2, false)).collect();
+    let combos: Vec<usize> = awards.iter().map(|a| a.combo).collect();
+    assert_eq!(combos, vec![0, 1, 2, 3]);
+    let points: Vec<u64> = awards.iter().map(|a| a.points).collect();
+    assert_eq!(points, vec![200, 300, 400, 500]);
+}
+
+#[test]
+fn back_to_back_tetrises_get_half_again() {
+    let mut scoring = Scoring::new();
+    assert!(!scoring.lock(4, TSpin::None, 1, false).back_to_back);
+    scoring.lock(0, TSpin::None, 1, false);
+    let award = scoring.lock(4, TSpin::None, 1, false);
+    assert!(award.back_to_back);
+    assert_eq!(award.points, 1200);
+    // A lower clear in between breaks the chain, a lock without lines doesn't.
+    scoring.lock(0, TSpin::None, 1, false);
+    scoring.lock(2, TSpin::None, 1, false);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert!(!scoring.lock(4, TSpin::None, 1, false).back_to_back);
+}
+
+#[test]
+fn perfect_clears_add_a_bonus() {
+    let mut scoring = Scoring::new();
+    let award = scoring.lock(1, TSpin::None, 1, true);
+    assert!(award.perfect_clear);
+    assert_eq!(award.points, 100 + 800);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.lock(4, TSpin::None, 1, true).points, 800 + 2000);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.lock(4, TSpin::None, 2, true).points, 2400 + 6400);
+}
+
+#[test]
+fn engine_scores_hard_drops() {
+    let mut engine = Engine::new(Config { seed: Some(4), ..Config::default() });
+    let snapshot = engine.snapshot();
+    let rows = (snapshot.hint.pos.1 - snapshot.block.pos.1) as u64;
+    let events = engine.handle_input(Input::HardDrop);
+    assert!(!events.iter().any(|e| matches!(e, GameEvent::Scored(_))));
+    assert_eq!(engine.snapshot().points, 2 * rows);
+}
+
+#[test]
+fn engine_scores_soft_drops() {
+    let mut engine = Engine::new(Config { seed: Some(4), ..Config::default() });
+    let y = engine.snapshot().block.pos.1;
+    engine.handle_input(Input::SoftDrop);
+    engine.tick(1000);
+    let fallen = (engine.snapshot().block.pos.1 - y) as u64;
+    assert!(fallen > 0);
+    assert_eq!(engine.snapshot().points, fallen);
+}
+
+#[test]
+fn t_spins_score_and_chain_back_to_back() {
+    let mut scoring = Scoring::new();
+    assert_eq!(scoring.lock(0, TSpin::Full, 1, false).points, 400);
+    assert_eq!(scoring.lock(0, TSpin::Mini, 1, false).points, 100);
+    assert_eq!(scoring.lock(1, TSpin::Mini, 1, false).points, 200);
+    scoring.lock(0, TSpin::None, 1, false);
+    let award = scoring.lock(2, TSpin::Full, 2, false);
+    assert!(award.back_to_back);
+    assert_eq!(award.points, 3600);
+    scoring.lock(0, TSpin::None, 1, false);
+    assert_eq!(scoring.lock(3, TSpin::Full, 1, false).points, 2400);
+}
+
+#[test]
+fn awards_describe_themselves() {
+    let mut scoring = Scoring::new();
+    scoring.lock(4, TSpin::None, 1, false);
+    let award = scoring.lock(2, TSpin::Full, 1, true);
+    assert_eq!(award.labels(), vec!["Back-to-Back", "T-Spin Double", "Combo 1", "Perfect Clear"]);
+    assert_eq!(scoring.lock(0, TSpin::Mini, 1, false).labels(), vec!["T-Spin Mini"]);
+    assert!(scoring.lock(0, TSpin::None, 1, false).labels().is_empty());
+}
@@ -0,0 +1,50 @@
+use wretris::block::{ BColor, Block, BlockWithPos, Rotation, Shape };
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input };
+use wretris::fumen::{ self, Page, Piece, HEIGHT, WIDTH };
+use wretris::search;
+
+use std::collections::HashSet;
+
+/// An engine whose field has `filled` cells, given as columns and rows up from the floor,
+/// and whose first block is `shape`.
+fn engine_with(filled: &[(usize, usize)], shape: Shape, config: Config) -> Engine {
+    let mut page = Page::default();
+    for (x, y) in filled {
+        page.field[(HEIGHT - 1 - y) * WIDTH + x] = Some(BColor::GARBAGE);
+    }
+    page.piece = Some(Piece { shape, rotation: Rotation::R0, pos: (4, 4) });
+    Engine::new(Config { fumen: Some(fumen::encode(&[page]).unwrap()), seed: Some(1), ..config })
+}
+
+#[test]
+fn every_resting_place_on_an_empty_board() {
+    for (shape, count) in [(Shape::I, 17), (Shape::O, 9), (Shape::T, 34), (Shape::S, 17), (Shape::Z, 17), (Shape::J, 34), (Shape::L, 34)] {
+        let engine = engine_with(&[], shape, Config::default());
+        let placements = search::placements(engine.grid(), false);
+        let places: HashSet<_> = placements.iter().map(|placement| placement.cells()).collect();
+        assert_eq!(places.len(), count, "{:?}", shape);
+        for placement in placements {
+            let mut engine = engine_with(&[], shape, Config::default());
+            for input in placement.inputs.iter().chain(&[Input::HardDrop]) {
+                engine.handle_input(*input);
+            }
+            let cells = engine.locked_cells();
+            assert!(placement.cells().iter().all(|(x, y)| cells[*y as usize * 10 + *x as usize].is_some()));
+        }
+    }
+}
+
+#[test]
+fn blocks_tuck_under_overhangs() {
+    let roof: Vec<_> = (0..4).map(|x| (x, 1)).collect();
+    let mut engine = engine_with(&roof, Shape::I, Config::default());
+    let target = BlockWithPos::from(Block::new(Shape::I), (2, 39));
+    let inputs = search::path(engine.grid(), &target, false, false).unwrap();
+    assert!(inputs.contains(&Input::SonicDrop));
+    for input in inputs.iter().chain(&[Input::HardDrop]) {
+        engine.handle_input(*input);
+    }
+    let cells = engine.locked_cells();
+    assert!((0..4).all(|x| cells[39 * 10 + x].is_some()));
+}
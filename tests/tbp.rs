@@ -0,0 +1,150 @@
+use wretris::block::{ BColor, Block, Rotation, Shape };
+use wretris::config::Config;
+use wretris::engine::{ Engine, GameEvent };
+use wretris::fumen::{ self, Page, Piece as FumenPiece, HEIGHT, WIDTH };
+use wretris::rotation::RotationSystemKind;
+use wretris::search;
+use wretris::tbp::{ BotMessage, FrontendMessage, Location, Move, Orientation, Piece, Session, Spin };
+use wretris::tspin::TSpin;
+
+/// A session past the handshake, with the `start` message it sent.
+fn started(engine: &mut Engine) -> (Session, FrontendMessage) {
+    let mut session = Session::new();
+    let info = BotMessage::decode(r#"{"type":"info","name":"test","version":"1.0","author":"someone","features":[]}"#).unwrap();
+    assert!(session.on_message(engine, info).unwrap().is_empty());
+    assert_eq!(session.name(), Some("test"));
+    assert_eq!(session.outgoing(), vec![FrontendMessage::Rules]);
+    session.on_message(engine, BotMessage::decode(r#"{"type":"ready"}"#).unwrap()).unwrap();
+    let mut outgoing = session.outgoing();
+    assert_eq!(outgoing.len(), 1);
+    (session, outgoing.remove(0))
+}
+
+#[test]
+fn messages_follow_the_protocol() {
+    let mv = Move {
+        location: Location { piece: Piece::T, orientation: Orientation::North, x: 4, y: 0 },
+        spin: Spin::None,
+    };
+    assert_eq!(FrontendMessage::Rules.encode(), r#"{"type":"rules"}"#);
+    assert_eq!(
+        FrontendMessage::Play { mv }.encode(),
+        r#"{"type":"play","move":{"location":{"type":"T","orientation":"north","x":4,"y":0},"spin":"none"}}"#
+    );
+    assert_eq!(FrontendMessage::NewPiece { piece: Piece::S }.encode(), r#"{"type":"new_piece","piece":"S"}"#);
+    let suggestion = r#"{"type":"suggestion","moves":[{"location":{"type":"T","orientation":"north","x":4,"y":0},"spin":"none"}],"move_info":{"nodes":10}}"#;
+    assert_eq!(BotMessage::decode(suggestion).unwrap(), BotMessage::Suggestion { moves: vec![mv] });
+    assert!(BotMessage::decode(r#"{"type":"dance"}"#).is_err());
+}
+
+#[test]
+fn the_bot_plays_its_suggestions() {
+    let mut engine = Engine::new(Config { seed: Some(7), ..Config::default() });
+    let (mut session, start) = started(&mut engine);
+    let snapshot = engine.snapshot();
+    match start {
+        FrontendMessage::Start { hold, queue, combo, back_to_back, board } => {
+            assert_eq!(hold, None);
+            assert_eq!(queue[0], Piece::of(snapshot.block.block.shape()));
+            assert_eq!(queue.len(), 4);
+            assert_eq!((combo, back_to_back), (0, false));
+            assert_eq!(board.len(), 40);
+            assert!(board.iter().all(|row| row.len() == 10 && row.iter().all(Option::is_none)));
+        }
+        message => panic!("{:?}", message),
+    }
+    assert!(session.is_idle());
+    session.suggest();
+    assert_eq!(session.outgoing(), vec![FrontendMessage::Suggest]);
+
+    let mv = Move { location: Location::of(&snapshot.hint, 40), spin: Spin::None };
+    let events = session.on_message(&mut engine, BotMessage::Suggestion { moves: vec![mv] }).unwrap();
+    assert_eq!(events, vec![GameEvent::Locked { lines: 0 }]);
+    let last = engine.snapshot().queue.last().unwrap().shape();
+    assert_eq!(session.outgoing(), vec![FrontendMessage::Play { mv }, FrontendMessage::NewPiece { piece: Piece::of(last) }]);
+    let cells = engine.locked_cells();
+    assert!(snapshot.hint.cells().iter().all(|(x, y)| cells[*y as usize * 10 + *x as usize].is_some()));
+}
+
+#[test]
+fn blocks_locked_while_the_bot_thinks_start_it_over() {
+    let mut engine = Engine::new(Config { seed: Some(7), ..Config::default() });
+    let (mut session, _) = started(&mut engine);
+    let snapshot = engine.snapshot();
+    session.suggest();
+    session.outgoing();
+    let before = engine.drawn();
+    engine.tick(60_000);
+    let drawn = engine.drawn();
+    assert!(drawn > before);
+    let mv = Move { location: Location::of(&snapshot.hint, 40), spin: Spin::None };
+    assert!(session.on_message(&mut engine, BotMessage::Suggestion { moves: vec![mv] }).unwrap().is_empty());
+    assert_eq!(engine.drawn(), drawn);
+    let outgoing = session.outgoing();
+    assert_eq!(outgoing[0], FrontendMessage::Stop);
+    assert_eq!(outgoing[1], FrontendMessage::start(&engine).unwrap());
+    assert!(session.is_idle());
+}
+
+#[test]
+fn moves_for_the_next_piece_hold_first() {
+    let mut engine = Engine::new(Config { seed: Some(3), ..Config::default() });
+    let (mut session, _) = started(&mut engine);
+    let snapshot = engine.snapshot();
+    let (current, next) = (snapshot.block.block.shape(), snapshot.queue[0].shape());
+    assert_ne!(current, next);
+    let target = search::respawned(engine.grid(), Block::new(next)).hint();
+    let mv = Move { location: Location::of(&target, 40), spin: Spin::None };
+    session.suggest();
+    session.outgoing();
+    session.on_message(&mut engine, BotMessage::Suggestion { moves: vec![mv] }).unwrap();
+    assert_eq!(engine.snapshot().hold.map(|block| block.shape()), Some(current));
+    let outgoing = session.outgoing();
+    assert_eq!(outgoing.len(), 3);
+    assert!(outgoing[1..].iter().all(|message| matches!(message, FrontendMessage::NewPiece { .. })));
+}
+
+#[test]
+fn suggested_spins_are_played_as_spins() {
+    let mut page = Page::default();
+    let filled = (0..10).filter(|x| *x != 4).map(|x| (x, 0))
+        .chain((0..10).filter(|x| !(3..=5).contains(x)).map(|x| (x, 1)))
+        .chain((0..4).map(|x| (x, 2)));
+    for (x, y) in filled {
+        page.field[(HEIGHT - 1 - y) * WIDTH + x] = Some(BColor::GARBAGE);
+    }
+    page.piece = Some(FumenPiece { shape: Shape::T, rotation: Rotation::R0, pos: (4, 4) });
+    let config = Config {
+        rotation_system: RotationSystemKind::Srs,
+        fumen: Some(fumen::encode(&[page]).unwrap()),
+        seed: Some(1),
+        ..Config::default()
+    };
+    let mut engine = Engine::new(config);
+    let (mut session, _) = started(&mut engine);
+    session.suggest();
+    let mv = Move {
+        location: Location { piece: Piece::T, orientation: Orientation::South, x: 4, y: 1 },
+        spin: Spin::Full,
+    };
+    let events = session.on_message(&mut engine, BotMessage::Suggestion { moves: vec![mv] }).unwrap();
+    match events[..] {
+        [GameEvent::Locked { lines: 2 }, GameEvent::Scored(award)] => assert_eq!(award.spin, TSpin::Full),
+        _ => panic!("{:?}", events),
+    }
+}
+
+#[test]
+fn unplayable_suggestions_are_errors() {
+    let mut engine = Engine::new(Config { seed: Some(7), ..Config::default() });
+    let (mut session, _) = started(&mut engine);
+    session.suggest();
+    let mv = Move {
+        location: Location { piece: Piece::of(engine.snapshot().block.block.shape()), orientation: Orientation::North, x: 4, y: 20 },
+        spin: Spin::None,
+    };
+    assert!(session.on_message(&mut engine, BotMessage::Suggestion { moves: vec![mv] }).is_err());
+    assert!(session.on_message(&mut engine, BotMessage::Ready).is_err());
+    let narrow = Engine::new(Config { width: 8, ..Config::default() });
+    assert!(FrontendMessage::start(&narrow).is_err());
+}
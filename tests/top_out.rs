@@ -0,0 +1,58 @@
+use wretris::config::Config;
+use wretris::engine::{ Engine, Input, Outcome };
+use wretris::mode::GameModeKind;
+use wretris::top_out::{ TopOut, TopOutRules };
+
+fn drop_until_gameover(engine: &mut Engine) -> Option<Outcome> {
+    for _ in 0..1000 {
+        if engine.is_gameover() {
+            break;
+        }
+        engine.handle_input(Input::HardDrop);
+    }
+    engine.outcome()
+}
+
+#[test]
+fn spawning_into_the_stack_is_a_block_out() {
+    let mut engine = Engine::new(Config { seed: Some(1), buffer: 0, ..Config::default() });
+    assert_eq!(drop_until_gameover(&mut engine), Some(Outcome::ToppedOut(TopOut::BlockOut)));
+}
+
+#[test]
+fn locking_in_the_buffer_is_a_lock_out() {
+    let mut engine = Engine::new(Config { seed: Some(0), height: 6, buffer: 4, ..Config::default() });
+    assert_eq!(drop_until_gameover(&mut engine), Some(Outcome::ToppedOut(TopOut::LockOut)));
+}
+
+#[test]
+fn partial_lock_out_is_optional() {
+    let config = Config { seed: Some(1), height: 6, buffer: 4, top_out: TopOutRules::from_name("block,lock,partial"), ..Config::default() };
+    let mut engine = Engine::new(config);
+    assert_eq!(drop_until_gameover(&mut engine), Some(Outcome::ToppedOut(TopOut::PartialLockOut)));
+}
+
+#[test]
+fn zen_clears_the_stack_instead() {
+    let mut engine = Engine::new(Config { seed: Some(1), height: 6, buffer: 4, mode: GameModeKind::Zen, ..Config::default() });
+    assert_eq!(drop_until_gameover(&mut engine), None);
+}
+
+#[test]
+fn lock_rules() {
+    let rules = TopOutRules::default();
+    assert_eq!(rules.on_lock(4, 4, 0), Some(TopOut::LockOut));
+    assert_eq!(rules.on_lock(2, 4, 0), None);
+    let rules = TopOutRules { partial_lock_out: true, ..rules };
+    assert_eq!(rules.on_lock(2, 4, 0), Some(TopOut::PartialLockOut));
+    assert_eq!(rules.on_lock(2, 4, 1), None);
+    assert_eq!(TopOutRules::none().on_lock(4, 4, 0), None);
+}
+
+#[test]
+fn rules_from_query() {
+    assert_eq!(Config::from_query("top_out=none").top_out, Some(TopOutRules::none()));
+    let rules = Config::from_query("top_out=block,partial").top_out.unwrap();
+    assert!(rules.block_out && !rules.lock_out && rules.partial_lock_out);
+    assert_eq!(Config::from_query("top_out=sometimes").top_out, None);
+}
@@ -0,0 +1,74 @@
+use wretris::block::{ Block, BlockWithPos, BColor, Shape };
+use wretris::color_grid::{ ColorGrid, Move };
+use wretris::rotation::{ RotationSystemKind, Turn };
+use wretris::tspin::{ self, TSpin };
+
+fn grid(filled: &[(usize, usize)], full_rows: &[(usize, &[usize])]) -> ColorGrid {
+    let mut grid = ColorGrid::new(10, 20, 0, (BColor::GRID1, BColor::GRID2), BColor::HINT, RotationSystemKind::Srs, Block::new(Shape::T));
+    for (y, holes) in full_rows {
+        for x in (0..10).filter(|x| !holes.contains(x)) {
+            grid.set(x, *y, BColor::I);
+        }
+    }
+    for (x, y) in filled {
+        grid.set(*x, *y, BColor::I);
+    }
+    grid
+}
+
+fn place(grid: &mut ColorGrid, pos: (i32, i32), clockwise_turns: usize) {
+    let mut block = Block::new(Shape::T);
+    for _ in 0..clockwise_turns {
+        block = block.rotate(true);
+    }
+    grid.block = BlockWithPos::from(block, pos);
+}
+
+#[test]
+fn t_spin_double_fills_both_front_corners() {
+    let mut grid = grid(&[(3, 17)], &[(18, &[3, 4, 5]), (19, &[4])]);
+    place(&mut grid, (4, 18), 1);
+    assert!(grid.rotate(true));
+    assert_eq!(grid.last_move(), Some(Move::Turn { turn: Turn::Clockwise, kick: 0 }));
+    assert_eq!(tspin::detect(&grid), TSpin::Full);
+    assert_eq!(grid.merge_block(), 2);
+}
+
+#[test]
+fn mini_against_the_wall() {
+    let mut grid = grid(&[], &[(19, &[0])]);
+    place(&mut grid, (1, 18), 0);
+    assert!(grid.rotate(true));
+    assert_eq!(grid.last_move(), Some(Move::Turn { turn: Turn::Clockwise, kick: 1 }));
+    assert_eq!(tspin::detect(&grid), TSpin::Mini);
+    assert_eq!(grid.merge_block(), 1);
+}
+
+#[test]
+fn last_kick_makes_a_full_spin() {
+    let mut grid = grid(&[(4, 15)], &[(17, &[4]), (18, &[4, 5]), (19, &[4, 5])]);
+    place(&mut grid, (5, 16), 0);
+    assert!(grid.rotate(true));
+    assert_eq!(grid.block.pos, (4, 18));
+    assert_eq!(grid.last_move(), Some(Move::Turn { turn: Turn::Clockwise, kick: 4 }));
+    assert_eq!(tspin::detect(&grid), TSpin::Full);
+}
+
+#[test]
+fn moving_after_the_turn_is_no_spin() {
+    let mut grid = grid(&[(3, 17)], &[(18, &[3, 4, 5]), (19, &[4])]);
+    place(&mut grid, (4, 16), 0);
+    assert!(grid.rotate(true));
+    assert!(grid.step_down());
+    assert!(grid.step_down());
+    assert_eq!(grid.last_move(), Some(Move::Fall));
+    assert_eq!(tspin::detect(&grid), TSpin::None);
+}
+
+#[test]
+fn only_t_pieces_spin() {
+    let mut grid = grid(&[(3, 17)], &[(18, &[3, 4, 5]), (19, &[4])]);
+    grid.block = BlockWithPos::from(Block::new(Shape::J), (4, 18));
+    grid.rotate(true);
+    assert_eq!(tspin::detect(&grid), TSpin::None);
+}
@@ -0,0 +1,124 @@
+use wretris::block::{ BColor, Rotation, Shape };
+use wretris::config::Config;
+use wretris::engine::Input;
+use wretris::fumen::{ self, Page, Piece, HEIGHT, WIDTH };
+use wretris::garbage::AttackTable;
+use wretris::keymap::{ Action, Key, Keymap };
+use wretris::mode::GameModeKind;
+use wretris::versus::{ State, Versus };
+
+/// Both players start over four rows open in the last column, with an I block.
+fn tetris_ready() -> Config {
+    let mut page = Page::default();
+    for y in 0..4 {
+        for x in 0..WIDTH - 1 {
+            page.field[(HEIGHT - 1 - y) * WIDTH + x] = Some(BColor::GARBAGE);
+        }
+    }
+    page.piece = Some(Piece { shape: Shape::I, rotation: Rotation::R90, pos: (9, 4) });
+    Config { fumen: Some(fumen::encode(&[page]).unwrap()), seed: Some(4), ..Config::default() }
+}
+
+/// Hard drops `player`'s blocks until one of the players tops out.
+fn top_out(versus: &mut Versus, player: usize) {
+    while versus.state() == State::Playing {
+        versus.handle_input(player, Input::HardDrop);
+    }
+}
+
+#[test]
+fn players_share_the_seed() {
+    let versus = Versus::new(Config::default());
+    let (a, b) = (versus.engine(0).snapshot(), versus.engine(1).snapshot());
+    assert_eq!(a.seed, b.seed);
+    assert_eq!(a.block.block.shape(), b.block.block.shape());
+    assert_eq!(a.queue.iter().map(|block| block.shape()).collect::<Vec<_>>(), b.queue.iter().map(|block| block.shape()).collect::<Vec<_>>());
+    assert_eq!(a.mode, GameModeKind::Versus);
+}
+
+#[test]
+fn clears_send_garbage() {
+    let mut versus = Versus::new(Config { attack: AttackTable::classic(), ..tetris_ready() });
+    versus.handle_input(0, Input::HardDrop);
+    assert_eq!(versus.engine(0).snapshot().lines, 4);
+    assert_eq!(versus.engine(1).garbage(), 4);
+    // The garbage rises when the second player's block locks without clearing a line.
+    versus.handle_input(1, Input::LeftMost);
+    versus.handle_input(1, Input::HardDrop);
+    let engine = versus.engine(1);
+    assert_eq!(engine.garbage(), 0);
+    let cells = engine.locked_cells();
+    let rows: Vec<&[Option<BColor>]> = cells.chunks(10).rev().take(8).collect();
+    assert!(rows.iter().all(|row| row.iter().filter(|cell| **cell == Some(BColor::GARBAGE)).count() == 9));
+    let hole = rows[0].iter().position(Option::is_none).unwrap();
+    assert!(rows[..4].iter().all(|row| row[hole].is_none()));
+}
+
+#[test]
+fn attacks_cancel_waiting_garbage() {
+    let mut versus = Versus::new(tetris_ready());
+    // A tetris that clears the whole board.
+    versus.handle_input(0, Input::HardDrop);
+    assert_eq!(versus.engine(1).garbage(), 14);
+    versus.handle_input(1, Input::HardDrop);
+    assert_eq!(versus.engine(1).garbage(), 0);
+    assert_eq!(versus.engine(0).garbage(), 0);
+    assert_eq!(versus.engine(1).snapshot().garbage, 0);
+}
+
+#[test]
+fn matches_are_won_by_knock_outs() {
+    let mut versus = Versus::new(Config { best_of: 3, ..Config::default() });
+    assert_eq!(versus.rounds_to_win(), 2);
+    top_out(&mut versus, 1);
+    assert_eq!(versus.state(), State::RoundOver { winner: Some(0) });
+    // Nothing moves until the next round starts.
+    let before = versus.engine(0).snapshot();
+    versus.tick(1000);
+    versus.handle_input(0, Input::HardDrop);
+    assert_eq!(versus.engine(0).snapshot().cells, before.cells);
+
+    versus.next_round();
+    assert!(!versus.engine(1).is_gameover());
+    top_out(&mut versus, 0);
+    assert_eq!(versus.wins(), [1, 1]);
+    versus.next_round();
+    top_out(&mut versus, 1);
+    assert_eq!(versus.state(), State::MatchOver { winner: 0 });
+    assert_eq!(versus.wins(), [2, 1]);
+
+    versus.next_round();
+    assert_eq!(versus.wins(), [0, 0]);
+    assert_eq!(versus.state(), State::Playing);
+}
+
+#[test]
+fn pausing_stops_both_games() {
+    let mut versus = Versus::new(Config::default());
+    assert!(versus.toggle_pause());
+    versus.tick(5000);
+    assert_eq!(versus.engine(0).elapsed(), 0);
+    assert_eq!(versus.engine(1).elapsed(), 0);
+    assert!(!versus.toggle_pause());
+    versus.tick(10);
+    assert_eq!(versus.engine(1).elapsed(), 10);
+}
+
+#[test]
+fn players_have_their_own_keys() {
+    let (first, second) = (Keymap::versus(0), Keymap::versus(1));
+    assert_eq!(first.action(Key::Char('a')), Some(Action::Left));
+    assert_eq!(second.action(Key::Left), Some(Action::Left));
+    for action in Action::all().into_iter().filter(|action| action.input().is_some() && *action != Action::LeftMost && *action != Action::RightMost) {
+        for key in first.keys(action) {
+            assert_eq!(second.action(*key), None, "{:?}", key);
+        }
+    }
+}
+
+#[test]
+fn best_of_comes_from_the_query() {
+    assert_eq!(Config::from_query("mode=versus&best_of=5").best_of, 5);
+    assert_eq!(Config::from_query("best_of=0").best_of, 3);
+    assert_eq!(GameModeKind::from_name("versus"), Some(GameModeKind::Versus));
+}
@@ -9,4 +9,6 @@ ctx.fillStyle = "green";
 // Add a rectangle at (10, 10) with size 100x100 pixels
 ctx.fillRect(0, 0, 1000, 1000);
 console.log("canvas is loaded", canvas !== null, ctx !== null);
-Cursive.retris_with_canvas(canvas);
+// Without a mode in the URL the game opens with the start menu.
+const mode = new URLSearchParams(window.location.search).get("mode");
+Cursive.retris_with_canvas(canvas, window.location.search, mode);